@@ -1,3 +1,6 @@
+use crate::error::AgfError;
+use crate::launch;
+use crate::live::Live;
 use crate::model::{Action, Agent, Session};
 use crate::shell::CommandShell;
 
@@ -10,32 +13,40 @@ pub fn generate_command(
     let quoted_path = shell.quote(&session.project_path);
 
     match action {
-        Action::Resume => {
-            // NOTE: Pi/Kiro CLI only resume latest; session_id ignored.
-            let cmd = session.agent.resume_cmd(&session.session_id);
-            Some(shell.cd_and(&quoted_path, &cmd))
-        }
+        Action::Resume => Some(resume_with_flags(session, "")),
         Action::NewSession => {
             let agent = new_agent.unwrap_or(session.agent);
-            let cmd = agent.new_session_cmd();
-            Some(shell.cd_and(&quoted_path, cmd))
+            new_session_with_flags(session, agent, "")
         }
         Action::Open => {
             let editor = detect_editor();
-            Some(shell.cd_and(&quoted_path, &format!("{editor} .")))
+            let cmd = shell.cd_and(&quoted_path, &format!("{editor} ."));
+            Some(launch::place(action, session, cmd))
         }
         Action::Cd => Some(shell.cd_only(&quoted_path)),
-        Action::Delete | Action::Back | Action::Pin => None,
+        Action::Handoff | Action::Delete | Action::Back | Action::Pin | Action::Export => None,
     }
 }
 
 pub fn action_preview(session: &Session, action: Action) -> String {
     match action {
-        Action::Resume => session.agent.resume_cmd(&session.session_id),
+        Action::Resume => match session.agent {
+            // The menu redraws every frame; a plugin is only asked once
+            // Resume is chosen.
+            Agent::Custom(c) if c.known_resume_cmd(&session.session_id).is_none() => {
+                format!("ask {} for the resume command", c.cli)
+            }
+            _ => resume_cmd(CommandShell::from_env(), session, ""),
+        },
         Action::NewSession => "choose agent CLI...".to_string(),
+        Action::Handoff => "continue in another agent with this context".to_string(),
         Action::Open => format!("{} .", detect_editor()),
         Action::Cd => CommandShell::from_env().cd_only(&session.display_path()),
         Action::Pin => "toggle pin".to_string(),
+        Action::Export => format!(
+            "write ./{}",
+            crate::export::file_name(session, crate::export::ExportFormat::Markdown)
+        ),
         Action::Delete => "remove session data".to_string(),
         Action::Back => "return to session list".to_string(),
     }
@@ -63,16 +74,131 @@ pub fn detect_editor() -> String {
 }
 
 pub fn resume_with_flags(session: &Session, flags: &str) -> String {
+    launch::place(Action::Resume, session, resume_here(session, flags))
+}
+
+/// The resume command as it runs in the current terminal, without window
+/// placement. Builds the string only; nothing is queried or written.
+pub fn resume_here(session: &Session, flags: &str) -> String {
     let shell = CommandShell::from_env();
     let quoted_path = shell.quote(&session.project_path);
-    // NOTE: Pi/Kiro CLI only resume latest; session_id ignored.
-    let base_cmd = session.agent.resume_cmd(&session.session_id);
-    shell.cd_and(&quoted_path, &format!("{base_cmd}{flags}"))
+    let mut cmd = resume_cmd(shell, session, flags);
+    if let Some(warning) = resume_warning(session) {
+        cmd = shell.warn_and(&warning, &cmd);
+    }
+    shell.cd_and(&quoted_path, &cmd)
+}
+
+/// Call once a resume command of `session` is about to run: make the agent
+/// resume `session` itself where its CLI can't be given the id. Returns
+/// `cmd`, prefixed with a warning when that can't be guaranteed.
+pub fn prepare_resume(session: &Session, cmd: String) -> String {
+    match session.agent {
+        Agent::Kiro => match crate::scanner::kiro::make_latest(session) {
+            Ok(()) => cmd,
+            Err(e) => CommandShell::from_env().warn_and(
+                &format!(
+                    "can't select Kiro conversation {} ({e}); kiro-cli resumes the latest one here",
+                    session.session_id
+                ),
+                &cmd,
+            ),
+        },
+        _ => cmd,
+    }
+}
+
+/// A running agent already has the session that is about to be resumed.
+/// Resuming it again would interleave two writers on the same log.
+pub struct Held {
+    pub pid: u32,
+    /// Resume command that continues in a fork instead, when the agent
+    /// supports forking.
+    pub fork: Option<String>,
+}
+
+/// Check that no running agent holds `session` before resuming it with
+/// `flags`.
+pub fn resume_guard(session: &Session, flags: &str, live: &Live) -> Option<Held> {
+    let pid = live.holder(session)?;
+    // Switching to the window that already runs it is what the user wants.
+    if launch::resumes_existing(session) {
+        return None;
+    }
+    let fork = session
+        .agent
+        .fork_flag()
+        .map(|fork| resume_with_flags(session, &format!("{flags}{fork}")));
+    Some(Held { pid, fork })
+}
+
+/// Warning for a resume that may not land on `session` itself.
+fn resume_warning(session: &Session) -> Option<String> {
+    match session.agent {
+        Agent::Pi if pi_session_file(session).is_none() => Some(format!(
+            "pi session file for {} not found; pick it in pi's session list",
+            session.session_id
+        )),
+        _ => None,
+    }
+}
+
+/// The file pi resumes `session` from, as found by the scan; looking it up
+/// again would walk pi's whole sessions tree.
+fn pi_session_file(session: &Session) -> Option<&std::path::Path> {
+    session.session_file.as_deref().filter(|p| p.is_file())
+}
+
+/// The agent's resume command plus `flags`, pointed at the session's data
+/// root when that isn't where the CLI looks by default.
+fn resume_cmd(shell: CommandShell, session: &Session, flags: &str) -> String {
+    let base = match session.agent {
+        // pi resumes a specific session by file.
+        Agent::Pi => match pi_session_file(session) {
+            Some(path) => format!("pi --session {}", shell.quote(&path.to_string_lossy())),
+            None => session.agent.resume_cmd(&session.session_id),
+        },
+        _ => session.agent.resume_cmd(&session.session_id),
+    };
+    let cmd = format!("{base}{flags}");
+    match session
+        .data_root
+        .as_deref()
+        .and_then(|root| crate::config::root_env(session.agent, root))
+    {
+        Some((var, value)) => shell.with_env(var, &value.to_string_lossy(), &cmd),
+        None => cmd,
+    }
 }
 
 pub fn new_session_with_flags(session: &Session, agent: Agent, flags: &str) -> Option<String> {
     let shell = CommandShell::from_env();
     let quoted_path = shell.quote(&session.project_path);
     let base = agent.new_session_cmd();
-    Some(shell.cd_and(&quoted_path, &format!("{base}{flags}")))
+    let cmd = shell.cd_and(&quoted_path, &format!("{base}{flags}"));
+    Some(launch::place(Action::NewSession, session, cmd))
+}
+
+/// Start `agent` in the session's project with a condensed transcript of
+/// `session` (see `handoff`) as its first prompt.
+pub fn handoff(session: &Session, agent: Agent, flags: &str) -> Result<String, AgfError> {
+    let shell = CommandShell::from_env();
+    let quoted_path = shell.quote(&session.project_path);
+    let context = crate::handoff::write_context(session)?;
+    let base = agent.new_session_cmd();
+    let cmd = match agent.prompt_arg() {
+        Some(arg) => {
+            let prompt = crate::handoff::prompt(session, &context);
+            format!("{base}{flags} {arg}{}", shell.quote(&prompt))
+        }
+        None => shell.warn_and(
+            &format!(
+                "{agent} takes no initial prompt; point it at {}",
+                context.display()
+            ),
+            &format!("{base}{flags}"),
+        ),
+    };
+    let cmd = shell.cd_and(&quoted_path, &cmd);
+    Ok(launch::place(Action::Handoff, session, cmd))
 }
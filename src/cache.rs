@@ -1,47 +1,108 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
-use serde::{Deserialize, Serialize};
+use rusqlite::{params, params_from_iter, Connection};
 
 use crate::model::{Agent, Session};
 use crate::plugin;
 
-const CACHE_VERSION: u32 = 2;
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run, so a newer agf upgrades an older store in place instead of
+/// throwing it away. Append new steps; never edit a released one. A step that
+/// changes what scanners produce should end with `DELETE FROM roots;` so
+/// every agent is rescanned once.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE agents (
+         agent TEXT PRIMARY KEY,
+         mtime INTEGER NOT NULL -- Unix seconds of data source last modification
+     );
+     CREATE TABLE sessions (
+         agent        TEXT NOT NULL,
+         session_id   TEXT NOT NULL,
+         project_name TEXT NOT NULL,
+         project_path TEXT NOT NULL,
+         summaries    TEXT NOT NULL, -- JSON array, newest first
+         timestamp    INTEGER NOT NULL,
+         git_branch   TEXT,
+         worktree     TEXT,
+         recap        TEXT,
+         usage        TEXT NOT NULL, -- JSON array of ModelUsage
+         PRIMARY KEY (agent, session_id)
+     );
+     CREATE INDEX sessions_by_time ON sessions (timestamp DESC);
+     CREATE INDEX sessions_by_agent_time ON sessions (agent, timestamp DESC);",
+    // 2: sessions and mtimes are kept per data root (config::data_roots);
+    // '' is the root of custom agents.
+    "DROP TABLE sessions;
+     DROP TABLE agents;
+     CREATE TABLE roots (
+         agent TEXT NOT NULL,
+         root  TEXT NOT NULL,
+         mtime INTEGER NOT NULL, -- Unix seconds of data source last modification
+         PRIMARY KEY (agent, root)
+     );
+     CREATE TABLE sessions (
+         agent        TEXT NOT NULL,
+         root         TEXT NOT NULL,
+         session_id   TEXT NOT NULL,
+         project_name TEXT NOT NULL,
+         project_path TEXT NOT NULL,
+         summaries    TEXT NOT NULL, -- JSON array, newest first
+         timestamp    INTEGER NOT NULL,
+         git_branch   TEXT,
+         worktree     TEXT,
+         recap        TEXT,
+         usage        TEXT NOT NULL, -- JSON array of ModelUsage
+         PRIMARY KEY (agent, root, session_id)
+     );
+     CREATE INDEX sessions_by_time ON sessions (timestamp DESC);
+     CREATE INDEX sessions_by_agent_time ON sessions (agent, timestamp DESC);",
+    // 3: pi lists every session, not just the newest per project.
+    "DELETE FROM roots WHERE agent = 'Pi';",
+    // 4: HEAD commit recorded by the agent; branches filled in for agents
+    // that don't record one.
+    "ALTER TABLE sessions ADD COLUMN git_commit TEXT;
+     DELETE FROM roots;",
+    // 5: Claude Code branches come from the session log; branches filled in
+    // from the project's checkout are marked as such.
+    "ALTER TABLE sessions ADD COLUMN branch_is_live INTEGER NOT NULL DEFAULT 0;
+     DELETE FROM roots;",
+    // 6: file each session's transcript is read from, found by the scan.
+    "ALTER TABLE sessions ADD COLUMN session_file TEXT;
+     DELETE FROM roots;",
+];
 
-#[derive(Serialize, Deserialize)]
-struct CacheFile {
-    version: u32,
-    agents: HashMap<String, AgentCache>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct AgentCache {
-    mtime: u64, // Unix seconds of data source last modification
-    sessions: Vec<CachedSession>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct CachedSession {
-    agent: String,
-    session_id: String,
-    project_name: String,
-    project_path: String,
-    summaries: Vec<String>,
-    timestamp: i64,
-    git_branch: Option<String>,
-    worktree: Option<String>,
-    recap: Option<String>,
-}
-
-fn cache_path() -> PathBuf {
+pub(crate) fn cache_path() -> PathBuf {
     dirs::cache_dir()
         .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".cache"))
         .join("agf")
-        .join("sessions.json")
+        .join("sessions.db")
 }
 
-fn agent_from_str(s: &str) -> Option<Agent> {
+/// Replace `path` with `contents` through a temp file and a rename, so
+/// readers see the old file or the new one. Temp names are unique per call:
+/// concurrent writers, threads or processes, never share one, and the last
+/// rename wins.
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp = path.with_file_name(name);
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+pub(crate) fn agent_from_str(s: &str) -> Option<Agent> {
     match s {
         "ClaudeCode" => Some(Agent::ClaudeCode),
         "Codex" => Some(Agent::Codex),
@@ -50,11 +111,14 @@ fn agent_from_str(s: &str) -> Option<Agent> {
         "Kiro" => Some(Agent::Kiro),
         "CursorAgent" => Some(Agent::CursorAgent),
         "Gemini" => Some(Agent::Gemini),
-        _ => None,
+        _ => crate::plugin::custom_agents()
+            .iter()
+            .find(|c| c.key == s)
+            .map(Agent::Custom),
     }
 }
 
-fn agent_to_str(a: Agent) -> &'static str {
+pub(crate) fn agent_to_str(a: Agent) -> &'static str {
     match a {
         Agent::ClaudeCode => "ClaudeCode",
         Agent::Codex => "Codex",
@@ -63,36 +127,53 @@ fn agent_to_str(a: Agent) -> &'static str {
         Agent::Kiro => "Kiro",
         Agent::CursorAgent => "CursorAgent",
         Agent::Gemini => "Gemini",
+        Agent::Custom(c) => &c.key,
     }
 }
 
-fn to_cached(s: &Session) -> CachedSession {
-    CachedSession {
-        agent: agent_to_str(s.agent).to_string(),
-        session_id: s.session_id.clone(),
-        project_name: s.project_name.clone(),
-        project_path: s.project_path.clone(),
-        summaries: s.summaries.iter().take(10).cloned().collect(),
-        timestamp: s.timestamp,
-        git_branch: s.git_branch.clone(),
-        worktree: s.worktree.clone(),
-        recap: s.recap.clone(),
+/// Open the session store, creating and migrating it as needed. WAL mode and
+/// a busy timeout let the TUI, `agf watch` and one-shot commands share it.
+fn open() -> rusqlite::Result<Connection> {
+    let path = cache_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+        // Pre-SQLite cache; superseded by the store.
+        let _ = fs::remove_file(parent.join("sessions.json"));
     }
+    let mut conn = Connection::open(&path)?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    migrate(&mut conn)?;
+    Ok(conn)
 }
 
-fn from_cached(c: &CachedSession) -> Option<Session> {
-    let agent = agent_from_str(&c.agent)?;
-    Some(Session {
-        agent,
-        session_id: c.session_id.clone(),
-        project_name: c.project_name.clone(),
-        project_path: c.project_path.clone(),
-        summaries: c.summaries.clone(),
-        timestamp: c.timestamp,
-        git_branch: c.git_branch.clone(),
-        worktree: c.worktree.clone(),
-        recap: c.recap.clone(),
-    })
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    // IMMEDIATE takes the write lock up front, so two processes opening a
+    // fresh store can't both run the same step; the loser re-reads the
+    // version once the winner commits.
+    let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+    let version: usize = tx.pragma_query_value(None, "user_version", |r| r.get(0))?;
+    if version > MIGRATIONS.len() {
+        // Written by a newer agf; don't touch it.
+        return Err(rusqlite::Error::InvalidQuery);
+    }
+    for sql in &MIGRATIONS[version..] {
+        tx.execute_batch(sql)?;
+    }
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    tx.commit()
+}
+
+fn open_or_log() -> Option<Connection> {
+    match open() {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            if std::env::var("AGF_DEBUG").is_ok() {
+                eprintln!("[agf] session store unavailable: {e} → scanning directly");
+            }
+            None
+        }
+    }
 }
 
 fn get_max_mtime(paths: &[PathBuf]) -> u64 {
@@ -120,176 +201,375 @@ fn get_max_mtime(paths: &[PathBuf]) -> u64 {
     max
 }
 
-/// Load cached sessions. Returns (sessions, stale_agents).
-/// stale_agents are agents whose data sources have changed since cache was written.
-pub fn load_cache() -> (Vec<Session>, Vec<Agent>) {
-    let path = cache_path();
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return (Vec::new(), Agent::all().to_vec()),
+/// Store key of a data root; custom agents have none.
+fn root_key(root: Option<&Path>) -> String {
+    root.map(|r| r.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Data roots of one agent with their current data-source mtimes.
+type RootMtimes = Vec<(Option<PathBuf>, u64)>;
+
+/// Current data-source mtime of every root of every installed agent in
+/// `agents`.
+fn current_mtimes(agents: &[Agent]) -> Vec<(Agent, RootMtimes)> {
+    let installed: HashSet<Agent> = crate::config::installed_agents().into_iter().collect();
+    plugin::all_plugins()
+        .iter()
+        .filter(|p| installed.contains(&p.agent()) && agents.contains(&p.agent()))
+        .map(|p| {
+            let roots = p
+                .data_sources()
+                .into_iter()
+                .map(|d| {
+                    let mtime = get_max_mtime(&d.sources);
+                    (d.root, mtime)
+                })
+                .collect();
+            (p.agent(), roots)
+        })
+        .collect()
+}
+
+/// Stored mtime of each root of `agent`, by root key.
+fn stored_mtimes(conn: &Connection, agent: Agent) -> HashMap<String, i64> {
+    let Ok(mut stmt) = conn.prepare("SELECT root, mtime FROM roots WHERE agent = ?1") else {
+        return HashMap::new();
     };
+    stmt.query_map([agent_to_str(agent)], |r| Ok((r.get(0)?, r.get(1)?)))
+        .map(|rows| rows.flatten().collect())
+        .unwrap_or_default()
+}
 
-    let cache: CacheFile = match serde_json::from_str::<CacheFile>(&content) {
-        Ok(c) if c.version == CACHE_VERSION => c,
-        Ok(c) => {
-            if std::env::var("AGF_DEBUG").is_ok() {
-                eprintln!(
-                    "[agf] cache version {} != {} → rescanning",
-                    c.version, CACHE_VERSION
-                );
-            }
-            return (Vec::new(), Agent::all().to_vec());
-        }
-        Err(e) => {
-            if std::env::var("AGF_DEBUG").is_ok() {
-                eprintln!("[agf] cache parse failed: {e} → rescanning");
-            }
-            return (Vec::new(), Agent::all().to_vec());
+fn is_fresh(stored: &HashMap<String, i64>, root: Option<&Path>, current: u64) -> bool {
+    current > 0
+        && stored
+            .get(&root_key(root))
+            .is_some_and(|m| *m as u64 >= current)
+}
+
+/// Split installed `agents` into (fresh, stale) against the stored mtimes.
+/// An agent is stale when any of its roots changed or the set of roots did.
+fn partition_fresh(conn: &Connection, agents: &[Agent]) -> (Vec<Agent>, Vec<Agent>) {
+    let mut fresh = Vec::new();
+    let mut stale = Vec::new();
+    for (agent, roots) in current_mtimes(agents) {
+        let stored = stored_mtimes(conn, agent);
+        let all_fresh = stored.len() == roots.len()
+            && roots
+                .iter()
+                .all(|(root, current)| is_fresh(&stored, root.as_deref(), *current));
+        if all_fresh {
+            fresh.push(agent);
+        } else {
+            stale.push(agent);
         }
+    }
+    (fresh, stale)
+}
+
+const SESSION_COLUMNS: &str = "agent, root, session_id, project_name, project_path, summaries,
+     timestamp, git_branch, worktree, recap, usage, git_commit, branch_is_live, session_file";
+
+fn session_from_row(r: &rusqlite::Row) -> rusqlite::Result<(String, Session)> {
+    let root: String = r.get(1)?;
+    Ok((
+        r.get(0)?,
+        Session {
+            agent: Agent::ClaudeCode, // replaced by the caller
+            session_id: r.get(2)?,
+            project_name: r.get(3)?,
+            project_path: r.get(4)?,
+            summaries: serde_json::from_str(&r.get::<_, String>(5)?).unwrap_or_default(),
+            timestamp: r.get(6)?,
+            git_branch: r.get(7)?,
+            commit: r.get(11)?,
+            branch_is_live: r.get(12)?,
+            worktree: r.get(8)?,
+            recap: r.get(9)?,
+            usage: serde_json::from_str(&r.get::<_, String>(10)?).unwrap_or_default(),
+            data_root: (!root.is_empty()).then(|| PathBuf::from(root)),
+            session_file: r.get::<_, Option<String>>(13)?.map(PathBuf::from),
+        },
+    ))
+}
+
+/// Read stored sessions of `agents`, newest first, via the
+/// `(agent, timestamp)` index.
+fn read_sessions(conn: &Connection, agents: &[Agent], limit: Option<usize>) -> Vec<Session> {
+    if agents.is_empty() {
+        return Vec::new();
+    }
+    let placeholders = vec!["?"; agents.len()].join(", ");
+    let limit = limit.map(|n| n as i64).unwrap_or(-1);
+    let sql = format!(
+        "SELECT {SESSION_COLUMNS}
+         FROM sessions WHERE agent IN ({placeholders})
+         ORDER BY timestamp DESC LIMIT {limit}"
+    );
+    let Ok(mut stmt) = conn.prepare(&sql) else {
+        return Vec::new();
     };
+    let rows = stmt.query_map(
+        params_from_iter(agents.iter().map(|a| agent_to_str(*a))),
+        session_from_row,
+    );
+    let Ok(rows) = rows else {
+        return Vec::new();
+    };
+    rows.flatten()
+        .filter_map(|(agent, mut session)| {
+            session.agent = agent_from_str(&agent)?;
+            Some(session)
+        })
+        .collect()
+}
 
-    let installed: std::collections::HashSet<Agent> =
-        crate::config::installed_agents().into_iter().collect();
-    let plugins = plugin::all_plugins();
-    let mut sessions = Vec::new();
-    let mut stale = Vec::new();
+/// Stored sessions of one root of `agent`.
+fn read_root(conn: &Connection, agent: Agent, root: Option<&Path>) -> Vec<Session> {
+    let sql = format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE agent = ?1 AND root = ?2");
+    let Ok(mut stmt) = conn.prepare(&sql) else {
+        return Vec::new();
+    };
+    let rows = stmt.query_map(
+        params![agent_to_str(agent), root_key(root)],
+        session_from_row,
+    );
+    match rows {
+        Ok(rows) => rows
+            .flatten()
+            .map(|(_, mut session)| {
+                session.agent = agent;
+                session
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
 
-    for p in &plugins {
-        if !installed.contains(&p.agent()) {
-            continue;
-        }
-        let key = agent_to_str(p.agent());
-        let current_mtime = get_max_mtime(&p.data_sources());
-
-        match cache.agents.get(key) {
-            Some(ac) if ac.mtime >= current_mtime && current_mtime > 0 => {
-                // Cache is fresh
-                for cs in &ac.sessions {
-                    if let Some(s) = from_cached(cs) {
-                        sessions.push(s);
-                    }
-                }
-            }
-            _ => {
-                stale.push(p.agent());
-            }
+/// Replace one root's sessions in a single transaction, so concurrent
+/// readers see either the old list or the new one, never a mix.
+fn store_root(
+    conn: &mut Connection,
+    agent: Agent,
+    root: Option<&Path>,
+    mtime: u64,
+    sessions: &[Session],
+) -> rusqlite::Result<()> {
+    let key = agent_to_str(agent);
+    let root = root_key(root);
+    let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+    tx.execute(
+        "DELETE FROM sessions WHERE agent = ?1 AND root = ?2",
+        [key, &root],
+    )?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT OR REPLACE INTO sessions
+                 (agent, root, session_id, project_name, project_path, summaries, timestamp,
+                  git_branch, worktree, recap, usage, git_commit, branch_is_live, session_file)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        )?;
+        for s in sessions.iter().filter(|s| s.agent == agent) {
+            let summaries: Vec<&String> = s.summaries.iter().take(10).collect();
+            insert.execute(params![
+                key,
+                root,
+                s.session_id,
+                s.project_name,
+                s.project_path,
+                serde_json::to_string(&summaries).unwrap_or_else(|_| "[]".into()),
+                s.timestamp,
+                s.git_branch,
+                s.worktree,
+                s.recap,
+                serde_json::to_string(&s.usage).unwrap_or_else(|_| "[]".into()),
+                s.commit,
+                s.branch_is_live,
+                s.session_file.as_deref().map(|p| p.to_string_lossy()),
+            ])?;
         }
     }
+    tx.execute(
+        "INSERT INTO roots (agent, root, mtime) VALUES (?1, ?2, ?3)
+         ON CONFLICT (agent, root) DO UPDATE SET mtime = excluded.mtime",
+        params![key, root, mtime as i64],
+    )?;
+    tx.commit()
+}
 
-    sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
-    (sessions, stale)
+/// Drop stored rows of `agent` for roots no longer configured.
+fn forget_roots_except(conn: &Connection, agent: Agent, keep: &[String]) -> rusqlite::Result<()> {
+    let placeholders = vec!["?"; keep.len()].join(", ");
+    let key = agent_to_str(agent);
+    for table in ["sessions", "roots"] {
+        let sql = format!("DELETE FROM {table} WHERE agent = ? AND root NOT IN ({placeholders})");
+        conn.execute(
+            &sql,
+            params_from_iter(std::iter::once(key).chain(keep.iter().map(String::as_str))),
+        )?;
+    }
+    Ok(())
 }
 
-/// Write all sessions to cache, grouped by agent.
-///
-/// `skip_agents` lists agents whose background scan did not complete this
-/// run (e.g. the user exited the TUI before the worker finished); for those
-/// we preserve the prior cache entry verbatim so we don't accidentally
-/// persist an empty session list with a fresh `mtime`, which would mark the
-/// agent "fresh" on the next launch and hide its sessions.
-pub fn write_cache(sessions: &[Session], skip_agents: &std::collections::HashSet<Agent>) {
-    let path = cache_path();
-    if let Some(parent) = path.parent() {
-        let _ = fs::create_dir_all(parent);
+/// Mark `agents` stale so the next load rescans them. Deleting or restoring
+/// sessions can land within the second of the stored mtime.
+pub fn invalidate(agents: &[Agent]) {
+    let Some(conn) = open_or_log() else {
+        return;
+    };
+    for &agent in agents {
+        let _ = conn.execute("DELETE FROM roots WHERE agent = ?1", [agent_to_str(agent)]);
     }
+}
 
-    let installed: std::collections::HashSet<Agent> =
-        crate::config::installed_agents().into_iter().collect();
-    let plugins = plugin::all_plugins();
-    let mut agents: HashMap<String, AgentCache> = HashMap::new();
-
-    // Carry over prior cache entries for in-flight agents.
-    if !skip_agents.is_empty() {
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(prior) = serde_json::from_str::<CacheFile>(&content) {
-                if prior.version == CACHE_VERSION {
-                    for skip in skip_agents {
-                        let key = agent_to_str(*skip).to_string();
-                        if let Some(entry) = prior.agents.get(&key) {
-                            agents.insert(
-                                key,
-                                AgentCache {
-                                    mtime: entry.mtime,
-                                    sessions: entry.sessions.iter().map(clone_cached).collect(),
-                                },
-                            );
-                        }
-                    }
+/// Bring every root of `agent` up to date and return all of its sessions.
+/// Unchanged roots are read back from the store; changed ones are scanned
+/// and stored. Each root's mtime is taken before scanning so writes that
+/// land mid-scan mark it stale again.
+fn scan_and_store(agent: Agent, mut conn: Option<&mut Connection>) -> Vec<Session> {
+    let roots = current_mtimes(&[agent])
+        .pop()
+        .map(|(_, roots)| roots)
+        .unwrap_or_default();
+    let stored = conn
+        .as_deref()
+        .map(|c| stored_mtimes(c, agent))
+        .unwrap_or_default();
+
+    let mut sessions = Vec::new();
+    for (root, mtime) in &roots {
+        let root = root.as_deref();
+        if let Some(conn) = conn.as_deref() {
+            if is_fresh(&stored, root, *mtime) {
+                sessions.extend(read_root(conn, agent, root));
+                continue;
+            }
+        }
+        let found = crate::scanner::scan_root(agent, root).unwrap_or_else(|e| {
+            if std::env::var("AGF_DEBUG").is_ok() {
+                eprintln!("[agf] {agent:?} scan of {}: {e}", root_key(root));
+            }
+            Vec::new()
+        });
+        if let Some(conn) = conn.as_deref_mut() {
+            if let Err(e) = store_root(conn, agent, root, *mtime, &found) {
+                if std::env::var("AGF_DEBUG").is_ok() {
+                    eprintln!("[agf] {agent:?} store failed: {e}");
                 }
             }
         }
+        sessions.extend(found);
     }
-
-    for p in &plugins {
-        if !installed.contains(&p.agent()) {
-            continue;
-        }
-        if skip_agents.contains(&p.agent()) {
-            continue;
-        }
-        let key = agent_to_str(p.agent()).to_string();
-        let agent_sessions: Vec<CachedSession> = sessions
-            .iter()
-            .filter(|s| s.agent == p.agent())
-            .map(to_cached)
-            .collect();
-        let mtime = get_max_mtime(&p.data_sources());
-        agents.insert(
-            key,
-            AgentCache {
-                mtime,
-                sessions: agent_sessions,
-            },
-        );
+    if let Some(conn) = conn.as_deref() {
+        let keep: Vec<String> = roots.iter().map(|(r, _)| root_key(r.as_deref())).collect();
+        let _ = forget_roots_except(conn, agent, &keep);
     }
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+    sessions
+}
 
-    let cache = CacheFile {
-        version: CACHE_VERSION,
-        agents,
+/// Load cached sessions. Returns (sessions, stale_agents).
+/// stale_agents are agents whose data sources have changed since they were
+/// last stored. Rescanning a stale agent is incremental: scanners memoize
+/// per-file parse results (`scanner::file_cache`), so only the changed logs
+/// are read again.
+pub fn load_cache() -> (Vec<Session>, Vec<Agent>) {
+    let Some(conn) = open_or_log() else {
+        return (Vec::new(), Agent::all().to_vec());
     };
+    let (fresh, stale) = partition_fresh(&conn, Agent::all());
+    (read_sessions(&conn, &fresh, None), stale)
+}
+
+/// Which sessions a one-shot command needs from the store.
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    /// Restrict to these agents; `None` means every agent.
+    pub agents: Option<Vec<Agent>>,
+    /// Newest `limit` sessions only.
+    pub limit: Option<usize>,
+}
 
-    if let Ok(json) = serde_json::to_string(&cache) {
-        let tmp = path.with_extension("json.tmp");
-        if fs::write(&tmp, json).is_ok() {
-            let _ = fs::rename(&tmp, &path);
+impl SessionFilter {
+    /// Agents matching a CLI `--agent` value (`None` → all agents).
+    pub fn for_agent(name: Option<&str>) -> Self {
+        Self {
+            agents: name.map(|n| {
+                Agent::all()
+                    .iter()
+                    .copied()
+                    .filter(|a| a.matches_name(n))
+                    .collect()
+            }),
+            limit: None,
         }
     }
-}
 
-fn clone_cached(c: &CachedSession) -> CachedSession {
-    CachedSession {
-        agent: c.agent.clone(),
-        session_id: c.session_id.clone(),
-        project_name: c.project_name.clone(),
-        project_path: c.project_path.clone(),
-        summaries: c.summaries.clone(),
-        timestamp: c.timestamp,
-        git_branch: c.git_branch.clone(),
-        worktree: c.worktree.clone(),
-        recap: c.recap.clone(),
+    /// The agent named exactly by a `--agent` value (`Agent::named`), for
+    /// commands that delete; `None` → all agents.
+    pub fn for_agent_named(name: Option<&str>) -> Result<Self, String> {
+        Ok(Self {
+            agents: name.map(Agent::named).transpose()?.map(|a| vec![a]),
+            limit: None,
+        })
     }
 }
 
+/// Sessions for CLI commands (`list`, `stats`, `resume`, …): stale agents are
+/// rescanned and stored first, then the answer is read from the store with
+/// the agent filter and limit pushed into the query. Without a usable store
+/// this degrades to a plain scan.
+pub fn load_sessions(filter: &SessionFilter) -> Vec<Session> {
+    let agents: Vec<Agent> = filter
+        .agents
+        .clone()
+        .unwrap_or_else(|| Agent::all().to_vec());
+
+    let Some(conn) = open_or_log() else {
+        let mut sessions: Vec<Session> = agents
+            .iter()
+            .flat_map(|a| scan_and_store(*a, None))
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+        if let Some(limit) = filter.limit {
+            sessions.truncate(limit);
+        }
+        return sessions;
+    };
+
+    let (mut installed, stale) = partition_fresh(&conn, &agents);
+    installed.extend(&stale);
+    std::thread::scope(|scope| {
+        for agent in stale {
+            scope.spawn(move || {
+                let mut conn = open_or_log();
+                scan_and_store(agent, conn.as_mut());
+            });
+        }
+    });
+
+    read_sessions(&conn, &installed, filter.limit)
+}
+
 /// One agent's scan result, streamed back from a worker thread.
 pub struct ScanResult {
     pub agent: Agent,
     pub sessions: Vec<Session>,
 }
 
-/// Spawn one worker thread per stale agent. Each worker sends its result on
-/// `tx` as soon as it finishes — the TUI can ingest results progressively
-/// without blocking on the slowest scanner.
-///
-/// The returned `JoinHandle` resolves once every worker has finished; the
-/// caller can use it to know when the channel will close.
+/// Spawn one worker thread per stale agent. Each worker stores its result
+/// (one transaction per agent) and sends it on the returned channel as soon
+/// as it finishes — the TUI can ingest results progressively without
+/// blocking on the slowest scanner. A worker cut short by the TUI exiting
+/// leaves that agent's previous rows untouched.
 pub fn start_stale_scan(stale: &[Agent]) -> std::sync::mpsc::Receiver<ScanResult> {
     use std::sync::mpsc;
     use std::thread;
     use std::time::Instant;
 
     let debug = std::env::var("AGF_DEBUG").is_ok();
-    let installed: std::collections::HashSet<Agent> =
-        crate::config::installed_agents().into_iter().collect();
+    let installed: HashSet<Agent> = crate::config::installed_agents().into_iter().collect();
     let stale: Vec<Agent> = stale
         .iter()
         .copied()
@@ -301,15 +581,8 @@ pub fn start_stale_scan(stale: &[Agent]) -> std::sync::mpsc::Receiver<ScanResult
         let tx = tx.clone();
         thread::spawn(move || {
             let start = Instant::now();
-            let sessions = match agent {
-                Agent::ClaudeCode => crate::scanner::claude::scan().unwrap_or_default(),
-                Agent::Codex => crate::scanner::codex::scan().unwrap_or_default(),
-                Agent::OpenCode => crate::scanner::opencode::scan().unwrap_or_default(),
-                Agent::Pi => crate::scanner::pi::scan().unwrap_or_default(),
-                Agent::Kiro => crate::scanner::kiro::scan().unwrap_or_default(),
-                Agent::CursorAgent => crate::scanner::cursor_agent::scan().unwrap_or_default(),
-                Agent::Gemini => crate::scanner::gemini::scan().unwrap_or_default(),
-            };
+            let mut conn = open_or_log();
+            let sessions = scan_and_store(agent, conn.as_mut());
             if debug {
                 eprintln!(
                     "[agf] {:?} scan: {} sessions in {:?}",
@@ -326,3 +599,76 @@ pub fn start_stale_scan(stale: &[Agent]) -> std::sync::mpsc::Receiver<ScanResult
     drop(tx);
     rx
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(agent: Agent, id: &str, timestamp: i64) -> Session {
+        Session {
+            project_name: "agf".to_string(),
+            project_path: "/src/agf".to_string(),
+            summaries: vec!["first".to_string()],
+            timestamp,
+            git_branch: Some("main".to_string()),
+            ..Session::stub(agent, id)
+        }
+    }
+
+    #[test]
+    fn store_replaces_one_root_and_reads_newest_first() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        // Re-running migrations on an up-to-date store is a no-op.
+        migrate(&mut conn).unwrap();
+
+        let work = Path::new("/home/me/.claude-work");
+        let claude = [
+            session(Agent::ClaudeCode, "a", 1),
+            session(Agent::ClaudeCode, "b", 3),
+        ];
+        store_root(&mut conn, Agent::ClaudeCode, Some(work), 10, &claude).unwrap();
+        store_root(
+            &mut conn,
+            Agent::Codex,
+            None,
+            10,
+            &[Session {
+                session_file: Some(PathBuf::from("/codex/rollout-c.jsonl")),
+                ..session(Agent::Codex, "c", 2)
+            }],
+        )
+        .unwrap();
+        store_root(&mut conn, Agent::ClaudeCode, Some(work), 11, &claude[..1]).unwrap();
+
+        let all = read_sessions(&conn, Agent::all(), None);
+        let ids: Vec<&str> = all.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["c", "a"]);
+        assert_eq!(all[1].git_branch.as_deref(), Some("main"));
+        assert_eq!(all[1].summaries, ["first"]);
+        assert_eq!(all[1].data_root.as_deref(), Some(work));
+        assert_eq!(all[0].data_root, None);
+        assert_eq!(
+            all[0].session_file.as_deref(),
+            Some(Path::new("/codex/rollout-c.jsonl"))
+        );
+
+        let newest = read_sessions(&conn, &[Agent::ClaudeCode, Agent::Codex], Some(1));
+        assert_eq!(newest.len(), 1);
+        assert_eq!(newest[0].agent, Agent::Codex);
+
+        // A second root is kept apart from the first, and dropping a root
+        // from the config forgets its rows.
+        let home = Path::new("/home/me/.claude");
+        let other = [session(Agent::ClaudeCode, "a", 5)];
+        store_root(&mut conn, Agent::ClaudeCode, Some(home), 12, &other).unwrap();
+        assert_eq!(read_root(&conn, Agent::ClaudeCode, Some(work)).len(), 1);
+        assert_eq!(stored_mtimes(&conn, Agent::ClaudeCode).len(), 2);
+        forget_roots_except(&conn, Agent::ClaudeCode, &[root_key(Some(home))]).unwrap();
+        assert!(read_root(&conn, Agent::ClaudeCode, Some(work)).is_empty());
+        assert_eq!(
+            read_root(&conn, Agent::ClaudeCode, Some(home))[0].timestamp,
+            5
+        );
+    }
+}
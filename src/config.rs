@@ -1,32 +1,57 @@
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 use crate::error::AgfError;
-use crate::model::Agent;
+use crate::model::{Agent, Session};
 
 pub fn home_dir() -> Result<PathBuf, AgfError> {
     dirs::home_dir().ok_or(AgfError::NoHomeDir)
 }
 
+/// A directory taken from `var`, when set and non-empty.
+fn env_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+// Default data roots, honoring each agent's own relocation variable.
+
 pub fn claude_dir() -> Result<PathBuf, AgfError> {
-    Ok(home_dir()?.join(".claude"))
+    match env_dir("CLAUDE_CONFIG_DIR") {
+        Some(dir) => Ok(dir),
+        None => Ok(home_dir()?.join(".claude")),
+    }
 }
 
 pub fn codex_dir() -> Result<PathBuf, AgfError> {
-    Ok(home_dir()?.join(".codex"))
+    match env_dir("CODEX_HOME") {
+        Some(dir) => Ok(dir),
+        None => Ok(home_dir()?.join(".codex")),
+    }
 }
 
 pub fn opencode_data_dir() -> Result<PathBuf, AgfError> {
-    Ok(home_dir()?.join(".local/share/opencode"))
+    match env_dir("XDG_DATA_HOME") {
+        Some(dir) => Ok(dir.join("opencode")),
+        None => Ok(home_dir()?.join(".local/share/opencode")),
+    }
 }
 
-pub fn pi_sessions_dir() -> Result<PathBuf, AgfError> {
-    Ok(home_dir()?.join(".pi/agent/sessions"))
+/// pi's agent directory; sessions live in its `sessions/` subdirectory.
+pub fn pi_agent_dir() -> Result<PathBuf, AgfError> {
+    match env_dir("PI_CODING_AGENT_DIR") {
+        Some(dir) => Ok(dir),
+        None => Ok(home_dir()?.join(".pi/agent")),
+    }
 }
 
 pub fn gemini_dir() -> Result<PathBuf, AgfError> {
-    Ok(home_dir()?.join(".gemini"))
+    match env_dir("GEMINI_CLI_HOME") {
+        Some(dir) => Ok(dir.join(".gemini")),
+        None => Ok(home_dir()?.join(".gemini")),
+    }
 }
 
 pub fn cursor_dir() -> Result<PathBuf, AgfError> {
@@ -36,12 +61,91 @@ pub fn cursor_dir() -> Result<PathBuf, AgfError> {
 pub fn kiro_data_dir() -> Result<PathBuf, AgfError> {
     // Kiro CLI stores data via dirs::data_local_dir()
     // macOS: ~/Library/Application Support/kiro-cli/
-    // Linux: ~/.local/share/kiro-cli/
+    // Linux: ~/.local/share/kiro-cli/ (or $XDG_DATA_HOME/kiro-cli)
     dirs::data_local_dir()
         .map(|d| d.join("kiro-cli"))
         .ok_or(AgfError::NoHomeDir)
 }
 
+/// The default data root of a built-in agent.
+pub fn default_root(agent: Agent) -> Result<PathBuf, AgfError> {
+    match agent {
+        Agent::ClaudeCode => claude_dir(),
+        Agent::Codex => codex_dir(),
+        Agent::OpenCode => opencode_data_dir(),
+        Agent::Pi => pi_agent_dir(),
+        Agent::Kiro => kiro_data_dir(),
+        Agent::CursorAgent => cursor_dir(),
+        Agent::Gemini => gemini_dir(),
+        Agent::Custom(c) => Err(AgfError::NoDataDir(c.name.to_string())),
+    }
+}
+
+/// Every data root of a built-in agent: the `[data_dirs]` entry keyed by its
+/// CLI name (`claude`, `kiro-cli`, …) in config.toml when present, else the
+/// default root. Custom agents have none.
+pub fn data_roots(agent: Agent) -> Vec<PathBuf> {
+    static CONFIGURED: OnceLock<Vec<(String, Vec<PathBuf>)>> = OnceLock::new();
+    if let Agent::Custom(_) = agent {
+        return Vec::new();
+    }
+    let configured = CONFIGURED.get_or_init(|| {
+        crate::settings::Settings::load()
+            .data_dirs
+            .into_iter()
+            .filter(|(name, _)| {
+                let known = Agent::all().iter().any(|a| a.cli_name() == name);
+                if !known {
+                    eprintln!("[agf] [data_dirs] {name}: not an agent's CLI name — ignored");
+                }
+                known
+            })
+            .map(|(name, dirs)| {
+                let dirs = dirs.iter().map(|d| crate::plugin::expand_home(d)).collect();
+                (name, dirs)
+            })
+            .collect()
+    });
+    match configured
+        .iter()
+        .find(|(name, dirs)| !dirs.is_empty() && name == agent.cli_name())
+    {
+        Some((_, dirs)) => dirs.clone(),
+        None => default_root(agent).map(|d| vec![d]).unwrap_or_default(),
+    }
+}
+
+/// The data root `session` was found in, falling back to the agent's
+/// default root for sessions scanned before roots were recorded.
+pub fn session_root(session: &Session) -> Result<PathBuf, AgfError> {
+    match &session.data_root {
+        Some(root) => Ok(root.clone()),
+        None => default_root(session.agent),
+    }
+}
+
+/// Environment variable that points `agent`'s CLI at `root`, when `root`
+/// isn't already where the CLI looks by default.
+pub fn root_env(agent: Agent, root: &Path) -> Option<(&'static str, PathBuf)> {
+    if default_root(agent).ok().as_deref() == Some(root) {
+        return None;
+    }
+    let parent_if = |name: &str| {
+        (root.file_name()? == name)
+            .then(|| root.parent().map(Path::to_path_buf))
+            .flatten()
+    };
+    match agent {
+        Agent::ClaudeCode => Some(("CLAUDE_CONFIG_DIR", root.to_path_buf())),
+        Agent::Codex => Some(("CODEX_HOME", root.to_path_buf())),
+        Agent::Pi => Some(("PI_CODING_AGENT_DIR", root.to_path_buf())),
+        Agent::Gemini => Some(("GEMINI_CLI_HOME", parent_if(".gemini")?)),
+        Agent::OpenCode => Some(("XDG_DATA_HOME", parent_if("opencode")?)),
+        Agent::Kiro if cfg!(target_os = "linux") => Some(("XDG_DATA_HOME", parent_if("kiro-cli")?)),
+        _ => None,
+    }
+}
+
 /// Cached set of executable names found in `$PATH`, built once per process.
 /// On Windows entries are lower-cased and `%PATHEXT%` stems are inserted
 /// alongside the full filename so bare-name lookups match `.exe`/`.cmd`/etc.
@@ -97,7 +201,14 @@ fn insert_executable_name(set: &mut HashSet<String>, filename: &str, pathext: &[
 }
 
 pub fn is_agent_installed(agent: Agent) -> bool {
-    let name = agent.cli_name();
+    match agent {
+        Agent::Custom(c) => c.is_installed(),
+        _ => is_executable_on_path(agent.cli_name()),
+    }
+}
+
+/// Whether `name` is an executable somewhere on `$PATH`.
+pub fn is_executable_on_path(name: &str) -> bool {
     let execs = path_executables();
     if cfg!(windows) {
         execs.contains(&name.to_lowercase())
@@ -1,71 +1,294 @@
+use std::collections::HashMap;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use walkdir::WalkDir;
 
 use crate::config;
 use crate::model::{Agent, Session};
+use crate::trash::{Batch, Item, Manifest, TrashedSession};
+
+/// Delete a session's data files, moving them to the trash as a batch of
+/// its own. Only removes session data, NOT the project directory.
+pub fn delete_session(session: &Session) -> Result<Option<String>, io::Error> {
+    let (batch, mut failed) = delete_sessions(&[session])?;
+    match failed.pop() {
+        Some((_, e)) => Err(e),
+        None => Ok(batch.map(|m| m.id)),
+    }
+}
 
-/// Delete a session's data files. Returns Ok(()) on success.
-/// Only removes session data, NOT the project directory.
-pub fn delete_session(session: &Session) -> Result<(), io::Error> {
-    // Defense in depth: never accept a session_id that could escape the intended
-    // directory via path traversal. Agent scanners should already sanitize IDs.
-    debug_assert!(
-        !session.session_id.contains('/') && !session.session_id.contains(".."),
-        "session_id must not contain path separators or parent traversal",
-    );
-    match session.agent {
-        Agent::ClaudeCode => delete_claude_session(session),
-        Agent::Codex => delete_codex_session(session),
-        Agent::OpenCode => delete_opencode_session(session),
-        Agent::Pi => delete_pi_session(session),
-        Agent::Kiro => delete_kiro_session(session),
-        Agent::CursorAgent => delete_cursor_agent_session(session),
-        Agent::Gemini => delete_gemini_session(session),
+/// Sessions that could not be deleted, with the reason.
+pub type Failures<'a> = Vec<(&'a Session, io::Error)>;
+
+/// Delete several sessions as one trash batch, so a single undo restores all
+/// of them. Returns the batch's manifest (`None` when nothing was moved,
+/// e.g. only `[[plugins]]` sessions, which their plugin deletes for good)
+/// and the sessions that failed.
+pub fn delete_sessions<'a>(
+    sessions: &[&'a Session],
+) -> Result<(Option<Manifest>, Failures<'a>), io::Error> {
+    let mut batch = Batch::new();
+    let mut walks = Walks::default();
+    let mut failed = Vec::new();
+    for &session in sessions {
+        batch.begin(session);
+        if let Err(e) = delete_into(session, &mut batch, &mut walks) {
+            failed.push((session, e));
+        }
     }
+    for (i, e) in batch.remove_queued_lines() {
+        if !failed.iter().any(|(s, _)| std::ptr::eq(*s, sessions[i])) {
+            failed.push((sessions[i], e));
+        }
+    }
+    let agents: Vec<Agent> = sessions.iter().map(|s| s.agent).collect();
+    crate::cache::invalidate(&agents);
+    Ok((batch.finish()?, failed))
 }
 
-// ---------------------------------------------------------------------------
-// Shared JSONL helpers
-// ---------------------------------------------------------------------------
+/// What `delete_sessions` moved for each of `deleted`, read back from the
+/// batch manifest. `[[plugins]]` sessions have no entry there and get an
+/// empty one.
+pub fn moved(deleted: &[&Session], batch: Option<&Manifest>) -> Vec<TrashedSession> {
+    deleted
+        .iter()
+        .map(|s| {
+            batch
+                .iter()
+                .flat_map(|b| &b.sessions)
+                .find(|t| {
+                    t.session_id == s.session_id && t.agent == crate::cache::agent_to_str(s.agent)
+                })
+                .cloned()
+                .unwrap_or_else(|| TrashedSession::new(s))
+        })
+        .collect()
+}
 
-/// Rewrite a JSONL file, excluding all lines where `json_key` matches `value`.
-fn rewrite_jsonl_excluding(path: &Path, json_key: &str, value: &str) -> Result<(), io::Error> {
-    let content = fs::read_to_string(path)?;
-    let mut kept_lines: Vec<&str> = Vec::new();
+/// Per session, what was (or would be) removed, plus the failures: the
+/// report behind `agf delete --json` and the server's `delete`.
+pub fn report_json(
+    sessions: &[&Session],
+    plans: &[TrashedSession],
+    failed: &[(&Session, String)],
+    batch: Option<&str>,
+    dry_run: bool,
+) -> serde_json::Value {
+    let deleted: Vec<serde_json::Value> = sessions
+        .iter()
+        .zip(plans)
+        .map(|(s, plan)| {
+            let impact = Impact::of(std::slice::from_ref(plan));
+            serde_json::json!({
+                "agent": s.agent.to_string(),
+                "session_id": s.session_id,
+                "project_path": s.project_path,
+                "removed": impact.removed,
+                "rewritten": impact.rewritten.iter().map(|(file, lines)| serde_json::json!({
+                    "file": file,
+                    "lines": lines,
+                })).collect::<Vec<_>>(),
+                "rows": impact.rows.iter().map(|(db, table, rows)| serde_json::json!({
+                    "db": db,
+                    "table": table,
+                    "rows": rows,
+                })).collect::<Vec<_>>(),
+                "bytes": impact.bytes,
+            })
+        })
+        .collect();
+    let failed: Vec<serde_json::Value> = failed
+        .iter()
+        .map(|(s, e)| {
+            serde_json::json!({
+                "agent": s.agent.to_string(),
+                "session_id": s.session_id,
+                "error": e,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "dry_run": dry_run,
+        "trash_batch": batch,
+        "deleted": deleted,
+        "failed": failed,
+    })
+}
 
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        if line_has_field_value(trimmed, json_key, value) {
-            continue;
+/// Work out what deleting `sessions` would remove, without touching
+/// anything. One entry per session, in order; `[[plugins]]` sessions come
+/// back empty since their plugin decides what goes.
+pub fn plan_sessions(sessions: &[&Session]) -> Vec<TrashedSession> {
+    let mut batch = Batch::plan();
+    let mut walks = Walks::default();
+    for &session in sessions {
+        batch.begin(session);
+        let _ = delete_into(session, &mut batch, &mut walks);
+    }
+    batch.into_plan()
+}
+
+/// What a deletion touches on disk, merged across sessions: shown before
+/// deleting in the TUI and by `agf delete --dry-run`.
+#[derive(Debug, Default)]
+pub struct Impact {
+    pub sessions: usize,
+    /// Files and directories removed outright.
+    pub removed: Vec<PathBuf>,
+    /// JSONL files rewritten, with the number of lines taken out.
+    pub rewritten: Vec<(PathBuf, usize)>,
+    /// SQLite databases and tables, with the number of rows deleted.
+    pub rows: Vec<(PathBuf, String, usize)>,
+    pub bytes: u64,
+}
+
+impl Impact {
+    pub fn of(plans: &[TrashedSession]) -> Self {
+        let mut impact = Self {
+            sessions: plans.len(),
+            ..Self::default()
+        };
+        for plan in plans {
+            impact.bytes += plan.bytes;
+            for item in &plan.items {
+                match item {
+                    Item::Path { original, .. } => impact.removed.push(original.clone()),
+                    Item::Lines { file, lines, .. } => {
+                        match impact.rewritten.iter_mut().find(|(f, _)| f == file) {
+                            Some((_, n)) => *n += lines.len(),
+                            None => impact.rewritten.push((file.clone(), lines.len())),
+                        }
+                    }
+                    Item::Rows {
+                        db, table, rows, ..
+                    } => {
+                        match impact
+                            .rows
+                            .iter_mut()
+                            .find(|(d, t, _)| d == db && t == table)
+                        {
+                            Some((_, _, n)) => *n += rows.len(),
+                            None => impact.rows.push((db.clone(), table.clone(), rows.len())),
+                        }
+                    }
+                }
+            }
         }
-        kept_lines.push(line);
+        impact
     }
 
-    let new_content = if kept_lines.is_empty() {
-        String::new()
-    } else {
-        let mut out = kept_lines.join("\n");
-        out.push('\n');
+    /// One line per thing touched, e.g. `rewrite ~/.claude/history.jsonl (-3 lines)`.
+    pub fn lines(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        for path in &self.removed {
+            let kind = if path.is_dir() {
+                "remove dir"
+            } else {
+                "remove"
+            };
+            out.push(format!("{kind} {}", tilde(path)));
+        }
+        for (file, n) in &self.rewritten {
+            out.push(format!("rewrite {} (-{n} lines)", tilde(file)));
+        }
+        for (db, table, n) in &self.rows {
+            out.push(format!("delete {n} rows from {table} in {}", tilde(db)));
+        }
         out
-    };
+    }
+}
+
+/// `path` with the home directory shown as `~`.
+fn tilde(path: &Path) -> String {
+    match dirs::home_dir().and_then(|h| path.strip_prefix(h).ok().map(Path::to_path_buf)) {
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
 
-    fs::write(path, new_content)
+/// What walking an agent's data directories found, kept for one
+/// `plan_sessions` or `delete_sessions` call so that a batch walks each tree
+/// once rather than once per session.
+#[derive(Default)]
+struct Walks {
+    /// Subdirectories below a base directory, by name.
+    dirs: HashMap<PathBuf, HashMap<String, Vec<PathBuf>>>,
+    /// JSONL logs below a base directory, by the session id their first
+    /// line declares.
+    logs: HashMap<PathBuf, HashMap<String, PathBuf>>,
 }
 
-/// Check if a JSON line contains `"key": "value"`.
-fn line_has_field_value(line: &str, key: &str, value: &str) -> bool {
-    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(line) {
-        if let Some(v) = parsed.get(key).and_then(|v| v.as_str()) {
-            return v == value;
-        }
+impl Walks {
+    /// Every directory named `name` below `base`.
+    fn dirs_named(&mut self, base: &Path, name: &str) -> Vec<PathBuf> {
+        let dirs = self.dirs.entry(base.to_path_buf()).or_insert_with(|| {
+            let mut dirs: HashMap<String, Vec<PathBuf>> = HashMap::new();
+            for entry in WalkDir::new(base).into_iter().filter_map(|e| e.ok()) {
+                if let (true, Some(name)) = (entry.file_type().is_dir(), entry.file_name().to_str())
+                {
+                    dirs.entry(name.to_string())
+                        .or_default()
+                        .push(entry.path().to_path_buf());
+                }
+            }
+            dirs
+        });
+        dirs.get(name).cloned().unwrap_or_default()
+    }
+
+    /// The JSONL log below `base` whose first line `id_of` reads as
+    /// `session_id`. Only first lines are read.
+    fn log_of(
+        &mut self,
+        base: &Path,
+        session_id: &str,
+        id_of: fn(&serde_json::Value) -> Option<&str>,
+    ) -> Option<PathBuf> {
+        let logs = self.logs.entry(base.to_path_buf()).or_insert_with(|| {
+            let mut logs = HashMap::new();
+            for entry in WalkDir::new(base).into_iter().filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if !entry.file_type().is_file()
+                    || path.extension().and_then(|e| e.to_str()) != Some("jsonl")
+                {
+                    continue;
+                }
+                let Some(line) = crate::scanner::read_first_line(path) else {
+                    continue;
+                };
+                let Ok(value) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+                    continue;
+                };
+                if let Some(id) = id_of(&value) {
+                    logs.entry(id.to_string())
+                        .or_insert_with(|| path.to_path_buf());
+                }
+            }
+            logs
+        });
+        logs.get(session_id).cloned()
+    }
+}
+
+fn delete_into(session: &Session, batch: &mut Batch, walks: &mut Walks) -> Result<(), io::Error> {
+    // Defense in depth: never accept a session_id that could escape the intended
+    // directory via path traversal. Agent scanners should already sanitize IDs.
+    debug_assert!(
+        !session.session_id.contains('/') && !session.session_id.contains(".."),
+        "session_id must not contain path separators or parent traversal",
+    );
+    match session.agent {
+        Agent::ClaudeCode => delete_claude_session(session, batch, walks),
+        Agent::Codex => delete_codex_session(session, batch, walks),
+        Agent::OpenCode => delete_opencode_session(session, batch),
+        Agent::Pi => delete_pi_session(session, batch, walks),
+        Agent::Kiro => delete_kiro_session(session, batch),
+        Agent::CursorAgent => delete_cursor_agent_session(session, batch, walks),
+        Agent::Gemini => delete_gemini_session(session, batch),
+        Agent::Custom(c) => c.delete(&session.session_id, batch),
     }
-    false
 }
 
 // ---------------------------------------------------------------------------
@@ -74,33 +297,46 @@ fn line_has_field_value(line: &str, key: &str, value: &str) -> bool {
 
 /// Claude sessions are stored as lines in `~/.claude/history.jsonl`.
 /// We rewrite the file excluding all lines whose `sessionId` matches.
-/// We also remove any project-specific session data under
+/// We also remove the transcript `~/.claude/projects/<project>/<sessionId>.jsonl`
+/// and any project-specific session data under
 /// `~/.claude/projects/<project>/sessions/<sessionId>/`.
-fn delete_claude_session(session: &Session) -> Result<(), io::Error> {
-    let claude_dir = config::claude_dir().map_err(io::Error::other)?;
+fn delete_claude_session(
+    session: &Session,
+    batch: &mut Batch,
+    walks: &mut Walks,
+) -> Result<(), io::Error> {
+    let claude_dir = config::session_root(session).map_err(io::Error::other)?;
 
     let history_path = claude_dir.join("history.jsonl");
     if history_path.exists() {
-        rewrite_jsonl_excluding(&history_path, "sessionId", &session.session_id)?;
+        batch.remove_lines(&history_path, "sessionId", &session.session_id);
+    }
+
+    if let Some(log) = crate::scanner::claude::session_file(&claude_dir, &session.session_id) {
+        batch.remove_path(&log)?;
     }
 
     let projects_dir = claude_dir.join("projects");
     if projects_dir.exists() {
-        remove_dirs_matching_name(&projects_dir, &session.session_id)?;
+        remove_dirs_matching_name(&projects_dir, &session.session_id, batch, walks)?;
     }
 
     Ok(())
 }
 
-/// Walk a directory tree and remove any subdirectory whose name matches the target.
-fn remove_dirs_matching_name(base: &Path, name: &str) -> Result<(), io::Error> {
+/// Trash any subdirectory of the tree whose name matches the target.
+fn remove_dirs_matching_name(
+    base: &Path,
+    name: &str,
+    batch: &mut Batch,
+    walks: &mut Walks,
+) -> Result<(), io::Error> {
     if !base.is_dir() {
         return Ok(());
     }
-    for entry in WalkDir::new(base).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if path.is_dir() && path.file_name().and_then(|n| n.to_str()) == Some(name) {
-            fs::remove_dir_all(path)?;
+    for path in walks.dirs_named(base, name) {
+        if path.exists() {
+            batch.remove_path(&path)?;
         }
     }
     Ok(())
@@ -113,78 +349,45 @@ fn remove_dirs_matching_name(base: &Path, name: &str) -> Result<(), io::Error> {
 /// Codex session files live under `~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl`.
 /// We find the file whose first line's `payload.id` matches and delete it.
 /// We also rewrite `~/.codex/history.jsonl` excluding matching `session_id` entries.
-fn delete_codex_session(session: &Session) -> Result<(), io::Error> {
-    let codex_dir = config::codex_dir().map_err(io::Error::other)?;
+fn delete_codex_session(
+    session: &Session,
+    batch: &mut Batch,
+    walks: &mut Walks,
+) -> Result<(), io::Error> {
+    let codex_dir = config::session_root(session).map_err(io::Error::other)?;
 
     // 1. Find and delete the session rollout file
     let sessions_dir = codex_dir.join("sessions");
-    if sessions_dir.exists() {
-        delete_codex_session_file(&sessions_dir, &session.session_id)?;
+    fn rollout_id(v: &serde_json::Value) -> Option<&str> {
+        v.get("payload")?.get("id")?.as_str()
+    }
+    if let Some(path) = walks.log_of(&sessions_dir, &session.session_id, rollout_id) {
+        batch.remove_path(&path)?;
     }
 
     // 2. Rewrite history.jsonl excluding lines with matching session_id
     let history_path = codex_dir.join("history.jsonl");
     if history_path.exists() {
-        rewrite_jsonl_excluding(&history_path, "session_id", &session.session_id)?;
+        batch.remove_lines(&history_path, "session_id", &session.session_id);
     }
 
     Ok(())
 }
 
-/// Find and delete the Codex rollout JSONL file matching the given session ID.
-fn delete_codex_session_file(sessions_dir: &Path, session_id: &str) -> Result<(), io::Error> {
-    for entry in WalkDir::new(sessions_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
-            continue;
-        }
-
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-
-        let first_line = match content.lines().next() {
-            Some(line) if !line.trim().is_empty() => line.trim(),
-            _ => continue,
-        };
-
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(first_line) {
-            let payload_id = value
-                .get("payload")
-                .and_then(|p| p.get("id"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            if payload_id == session_id {
-                fs::remove_file(path)?;
-                return Ok(());
-            }
-        }
-    }
-    Ok(())
-}
-
 // ---------------------------------------------------------------------------
 // OpenCode
 // ---------------------------------------------------------------------------
 
 /// OpenCode sessions are stored in a SQLite database at
 /// `~/.local/share/opencode/opencode.db`.
-fn delete_opencode_session(session: &Session) -> Result<(), io::Error> {
-    let opencode_dir = config::opencode_data_dir().map_err(io::Error::other)?;
+fn delete_opencode_session(session: &Session, batch: &mut Batch) -> Result<(), io::Error> {
+    let opencode_dir = config::session_root(session).map_err(io::Error::other)?;
     let db_path = opencode_dir.join("opencode.db");
     if !db_path.exists() {
         return Ok(());
     }
 
-    let conn = rusqlite::Connection::open(&db_path)
-        .map_err(|e| io::Error::other(format!("SQLite open error: {e}")))?;
-
-    conn.execute("DELETE FROM session WHERE id = ?1", [&session.session_id])
-        .map_err(|e| io::Error::other(format!("SQLite delete error: {e}")))?;
+    batch.delete_rows(&db_path, "session", "id", &session.session_id)?;
 
     // Also remove JSON storage mirror if it exists
     let session_storage = opencode_dir.join("storage/session");
@@ -197,7 +400,7 @@ fn delete_opencode_session(session: &Session) -> Result<(), io::Error> {
             if path.is_file()
                 && path.file_stem().and_then(|n| n.to_str()) == Some(&session.session_id)
             {
-                let _ = fs::remove_file(path);
+                let _ = batch.remove_path(path);
             }
         }
     }
@@ -211,41 +414,23 @@ fn delete_opencode_session(session: &Session) -> Result<(), io::Error> {
 
 /// Pi sessions are stored as JSONL files under
 /// `~/.pi/agent/sessions/<encoded-cwd>/<timestamp>_<sessionId>.jsonl`.
-fn delete_pi_session(session: &Session) -> Result<(), io::Error> {
-    let sessions_dir = config::pi_sessions_dir().map_err(io::Error::other)?;
-    if !sessions_dir.exists() {
-        return Ok(());
-    }
-
-    for entry in WalkDir::new(&sessions_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
-        if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
-            continue;
-        }
-
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-
-        let first_line = match content.lines().next() {
-            Some(line) if !line.trim().is_empty() => line.trim(),
-            _ => continue,
-        };
-
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(first_line) {
-            if value.get("type").and_then(|v| v.as_str()) == Some("session")
-                && value.get("id").and_then(|v| v.as_str()) == Some(&session.session_id)
-            {
-                fs::remove_file(path)?;
-                return Ok(());
-            }
+fn delete_pi_session(
+    session: &Session,
+    batch: &mut Batch,
+    walks: &mut Walks,
+) -> Result<(), io::Error> {
+    let sessions_dir = config::session_root(session)
+        .map_err(io::Error::other)?
+        .join("sessions");
+    fn header_id(v: &serde_json::Value) -> Option<&str> {
+        match v.get("type")?.as_str()? {
+            "session" => v.get("id")?.as_str(),
+            _ => None,
         }
     }
-
+    if let Some(path) = walks.log_of(&sessions_dir, &session.session_id, header_id) {
+        batch.remove_path(&path)?;
+    }
     Ok(())
 }
 
@@ -256,22 +441,19 @@ fn delete_pi_session(session: &Session) -> Result<(), io::Error> {
 /// Kiro sessions are stored in a SQLite database at
 /// `~/Library/Application Support/kiro-cli/data.sqlite3` (macOS) or
 /// `~/.local/share/kiro-cli/data.sqlite3` (Linux).
-fn delete_kiro_session(session: &Session) -> Result<(), io::Error> {
-    let data_dir = config::kiro_data_dir().map_err(io::Error::other)?;
+fn delete_kiro_session(session: &Session, batch: &mut Batch) -> Result<(), io::Error> {
+    let data_dir = config::session_root(session).map_err(io::Error::other)?;
     let db_path = data_dir.join("data.sqlite3");
     if !db_path.exists() {
         return Ok(());
     }
 
-    let conn = rusqlite::Connection::open(&db_path)
-        .map_err(|e| io::Error::other(format!("SQLite open error: {e}")))?;
-
-    conn.execute(
-        "DELETE FROM conversations_v2 WHERE conversation_id = ?1",
-        [&session.session_id],
-    )
-    .map_err(|e| io::Error::other(format!("SQLite delete error: {e}")))?;
-
+    batch.delete_rows(
+        &db_path,
+        "conversations_v2",
+        "conversation_id",
+        &session.session_id,
+    )?;
     Ok(())
 }
 
@@ -282,13 +464,17 @@ fn delete_kiro_session(session: &Session) -> Result<(), io::Error> {
 /// Cursor Agent sessions are stored in two locations:
 /// 1. `~/.cursor/chats/<workspace-hash>/<session_id>/store.db` (SQLite)
 /// 2. `~/.cursor/projects/*/agent-transcripts/<session_id>.txt` (transcript files)
-fn delete_cursor_agent_session(session: &Session) -> Result<(), io::Error> {
-    let cursor_dir = config::cursor_dir().map_err(io::Error::other)?;
+fn delete_cursor_agent_session(
+    session: &Session,
+    batch: &mut Batch,
+    walks: &mut Walks,
+) -> Result<(), io::Error> {
+    let cursor_dir = config::session_root(session).map_err(io::Error::other)?;
 
     // 1. Remove chat directory: ~/.cursor/chats/*/<session_id>/
     let chats_dir = cursor_dir.join("chats");
     if chats_dir.exists() {
-        remove_dirs_matching_name(&chats_dir, &session.session_id)?;
+        remove_dirs_matching_name(&chats_dir, &session.session_id, batch, walks)?;
     }
 
     // 2. Remove transcript files: ~/.cursor/projects/*/agent-transcripts/<session_id>.txt
@@ -302,7 +488,7 @@ fn delete_cursor_agent_session(session: &Session) -> Result<(), io::Error> {
             let path = entry.path();
             if path.is_file() && path.file_name().and_then(|n| n.to_str()) == Some(&transcript_name)
             {
-                let _ = fs::remove_file(path);
+                let _ = batch.remove_path(path);
             }
         }
     }
@@ -316,8 +502,8 @@ fn delete_cursor_agent_session(session: &Session) -> Result<(), io::Error> {
 
 /// Gemini sessions are stored as JSON files under
 /// `~/.gemini/tmp/<project-name-or-hash>/chats/session-<date>-<short-id>.json`.
-fn delete_gemini_session(session: &Session) -> Result<(), io::Error> {
-    let gemini_dir = config::gemini_dir().map_err(io::Error::other)?;
+fn delete_gemini_session(session: &Session, batch: &mut Batch) -> Result<(), io::Error> {
+    let gemini_dir = config::session_root(session).map_err(io::Error::other)?;
     let tmp_dir = gemini_dir.join("tmp");
     if !tmp_dir.exists() {
         return Ok(());
@@ -346,7 +532,7 @@ fn delete_gemini_session(session: &Session) -> Result<(), io::Error> {
                     .map(|id| id == session.session_id)
                     .unwrap_or(false)
                 {
-                    fs::remove_file(&path)?;
+                    batch.remove_path(&path)?;
                     return Ok(());
                 }
             }
@@ -355,3 +541,69 @@ fn delete_gemini_session(session: &Session) -> Result<(), io::Error> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impact_merges_shared_files_across_sessions() {
+        let plan = |id: &str| TrashedSession {
+            items: vec![
+                Item::Lines {
+                    file: PathBuf::from("/data/history.jsonl"),
+                    lines: vec!["{}".into(), "{}".into()],
+                    at: vec![0, 3],
+                },
+                Item::Path {
+                    original: PathBuf::from(format!("/data/projects/p/{id}.jsonl")),
+                    stored: String::new(),
+                },
+            ],
+            bytes: 100,
+            ..TrashedSession::new(&Session::stub(Agent::ClaudeCode, id))
+        };
+        let impact = Impact::of(&[plan("a"), plan("b")]);
+        assert_eq!(impact.sessions, 2);
+        assert_eq!(impact.bytes, 200);
+        assert_eq!(impact.removed.len(), 2);
+        assert_eq!(
+            impact.rewritten,
+            [(PathBuf::from("/data/history.jsonl"), 4)]
+        );
+        assert_eq!(impact.lines()[2], "rewrite /data/history.jsonl (-4 lines)");
+    }
+
+    #[test]
+    fn walks_index_each_tree_once() {
+        let base = std::env::temp_dir().join(format!("agf-walks-{}", std::process::id()));
+        let day = base.join("2026/01/02");
+        fs::create_dir_all(base.join("p/s1")).unwrap();
+        fs::create_dir_all(&day).unwrap();
+        for id in ["s1", "s2"] {
+            let header = serde_json::json!({"type": "session_meta", "payload": {"id": id}});
+            fs::write(
+                day.join(format!("rollout-{id}.jsonl")),
+                format!("{header}\n"),
+            )
+            .unwrap();
+        }
+        fn rollout_id(v: &serde_json::Value) -> Option<&str> {
+            v.get("payload")?.get("id")?.as_str()
+        }
+
+        let mut walks = Walks::default();
+        assert_eq!(walks.dirs_named(&base, "s1"), [base.join("p/s1")]);
+        assert_eq!(
+            walks.log_of(&base, "s2", rollout_id),
+            Some(day.join("rollout-s2.jsonl"))
+        );
+        // Later lookups come from the index, not the disk.
+        fs::remove_dir_all(&base).unwrap();
+        assert_eq!(
+            walks.log_of(&base, "s1", rollout_id),
+            Some(day.join("rollout-s1.jsonl"))
+        );
+        assert!(walks.dirs_named(&base, "s3").is_empty());
+    }
+}
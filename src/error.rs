@@ -13,4 +13,13 @@ pub enum AgfError {
 
     #[error("No home directory found")]
     NoHomeDir,
+
+    #[error("Session data not found: {0}")]
+    SessionNotFound(String),
+
+    #[error("No data directory for {0}")]
+    NoDataDir(String),
+
+    #[error("Plugin failed: {0}")]
+    Plugin(String),
 }
@@ -0,0 +1,387 @@
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, TimeZone};
+
+use crate::error::AgfError;
+use crate::model::Session;
+use crate::transcript::{self, Message, Role};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Read the whole transcript of `session` and render it in `format`.
+pub fn export(session: &Session, format: ExportFormat) -> Result<String, AgfError> {
+    let messages: Vec<Message> = transcript::open(session)?.collect();
+    Ok(render(session, &messages, format))
+}
+
+pub fn render(session: &Session, messages: &[Message], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => render_markdown(session, messages),
+        ExportFormat::Html => render_html(session, messages),
+        ExportFormat::Json => render_json(session, messages),
+    }
+}
+
+/// Default file name for an export: `<project>-<id prefix>.<ext>`.
+pub fn file_name(session: &Session, format: ExportFormat) -> String {
+    let project: String = session
+        .project_name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let id: String = session.session_id.chars().take(8).collect();
+    format!("{project}-{id}.{}", format.extension())
+}
+
+/// Write `doc` into `dir` under `file_name`, or `<name>-2.<ext>`, `-3`, …
+/// when that is taken; an existing export is never overwritten. Returns the
+/// path written.
+pub fn write_new(
+    dir: &Path,
+    session: &Session,
+    format: ExportFormat,
+    doc: &str,
+) -> io::Result<PathBuf> {
+    let name = file_name(session, format);
+    let stem = name.trim_end_matches(&format!(".{}", format.extension()));
+    for n in 1..1000 {
+        let path = match n {
+            1 => dir.join(&name),
+            n => dir.join(format!("{stem}-{n}.{}", format.extension())),
+        };
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => return file.write_all(doc.as_bytes()).map(|()| path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("{name} and its numbered copies exist"),
+    ))
+}
+
+fn role_title(msg: &Message) -> String {
+    match (msg.role, &msg.tool) {
+        (Role::User, _) => "User".to_string(),
+        (Role::Assistant, _) => "Assistant".to_string(),
+        (Role::Tool, Some(name)) => format!("Tool call: {name}"),
+        (Role::Tool, None) => "Tool call".to_string(),
+        (Role::ToolResult, _) => "Tool result".to_string(),
+    }
+}
+
+fn format_time(ms: i64) -> Option<String> {
+    match Local.timestamp_millis_opt(ms) {
+        chrono::LocalResult::Single(dt) => Some(dt.format("%Y-%m-%d %H:%M").to_string()),
+        _ => None,
+    }
+}
+
+/// Metadata lines shared by the Markdown and HTML headers.
+fn header_fields(session: &Session) -> Vec<(&'static str, String)> {
+    let mut fields = vec![
+        ("Agent", session.agent.to_string()),
+        ("Session", session.session_id.clone()),
+        ("Path", session.project_path.clone()),
+    ];
+    if let Some(branch) = &session.git_branch {
+        fields.push(("Branch", branch.clone()));
+    }
+    if let Some(commit) = &session.commit {
+        fields.push(("Commit", commit.clone()));
+    }
+    if let Some(wt) = &session.worktree {
+        fields.push(("Worktree", wt.clone()));
+    }
+    if let Some(time) = format_time(session.timestamp) {
+        fields.push(("Last active", time));
+    }
+    fields
+}
+
+// ---------------------------------------------------------------------------
+// Markdown
+// ---------------------------------------------------------------------------
+
+fn render_markdown(session: &Session, messages: &[Message]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", session.project_name);
+    for (label, value) in header_fields(session) {
+        let _ = writeln!(out, "- **{label}:** `{value}`");
+    }
+    let _ = writeln!(out, "\n---");
+
+    for msg in messages {
+        let mut heading = role_title(msg);
+        if let Some(time) = msg.timestamp.and_then(format_time) {
+            heading.push_str(&format!(" · {time}"));
+        }
+        let _ = writeln!(out, "\n### {heading}\n");
+        match msg.role {
+            // Agent prose is already Markdown (including its own code fences).
+            Role::User | Role::Assistant => {
+                let _ = writeln!(out, "{}", msg.text.trim_end());
+            }
+            Role::Tool | Role::ToolResult => {
+                let _ = writeln!(out, "{}", fenced(msg.text.trim_end()));
+            }
+        }
+    }
+    out
+}
+
+/// Wrap `text` in a code fence long enough not to collide with any backtick
+/// run inside it.
+fn fenced(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in text.chars() {
+        if ch == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}\n{text}\n{fence}")
+}
+
+// ---------------------------------------------------------------------------
+// HTML
+// ---------------------------------------------------------------------------
+
+const HTML_STYLE: &str =
+    "body{font:15px/1.5 -apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;\
+max-width:860px;margin:2rem auto;padding:0 1rem;color:#1f2328}\
+dl{display:grid;grid-template-columns:max-content 1fr;gap:.2rem 1rem;color:#57606a}\
+dd{margin:0;font-family:monospace}\
+section{border-left:3px solid #d0d7de;padding:.2rem 1rem;margin:1.2rem 0}\
+section.user{border-color:#22d3ee}section.assistant{border-color:#8b5cf6}\
+section.tool,section.tool_result{border-color:#a3a3a3;font-size:13px}\
+h3{font-size:13px;text-transform:uppercase;letter-spacing:.04em;color:#57606a;margin:.2rem 0}\
+time{font-weight:normal;margin-left:.5rem}\
+pre{background:#f6f8fa;padding:.6rem;overflow-x:auto;border-radius:6px}";
+
+fn render_html(session: &Session, messages: &[Message]) -> String {
+    let mut out = String::new();
+    let title = escape_html(&session.project_name);
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<dl>\n"
+    );
+    for (label, value) in header_fields(session) {
+        let _ = writeln!(out, "<dt>{label}</dt><dd>{}</dd>", escape_html(&value));
+    }
+    let _ = writeln!(out, "</dl>");
+
+    for msg in messages {
+        let class = match msg.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+            Role::ToolResult => "tool_result",
+        };
+        let _ = write!(
+            out,
+            "<section class=\"{class}\">\n<h3>{}",
+            escape_html(&role_title(msg))
+        );
+        if let Some(time) = msg.timestamp.and_then(format_time) {
+            let _ = write!(out, "<time>{time}</time>");
+        }
+        let _ = writeln!(out, "</h3>");
+        match msg.role {
+            Role::User | Role::Assistant => out.push_str(&prose_html(&msg.text)),
+            Role::Tool | Role::ToolResult => {
+                let _ = writeln!(
+                    out,
+                    "<pre><code>{}</code></pre>",
+                    escape_html(msg.text.trim_end())
+                );
+            }
+        }
+        let _ = writeln!(out, "</section>");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Minimal Markdown-ish rendering for message prose: fenced code blocks
+/// become `<pre>`, blank-line separated runs become paragraphs.
+fn prose_html(text: &str) -> String {
+    let mut out = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<(String, Vec<&str>)> = None; // (language, lines)
+
+    let flush = |out: &mut String, paragraph: &mut Vec<&str>| {
+        if !paragraph.is_empty() {
+            let body: Vec<String> = paragraph.iter().map(|l| escape_html(l)).collect();
+            let _ = writeln!(out, "<p>{}</p>", body.join("<br>\n"));
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some((lang, lines)) = code.as_mut() {
+            if trimmed.starts_with("```") {
+                let class = if lang.is_empty() {
+                    String::new()
+                } else {
+                    format!(" class=\"language-{}\"", escape_html(lang))
+                };
+                let _ = writeln!(
+                    out,
+                    "<pre><code{class}>{}</code></pre>",
+                    escape_html(&lines.join("\n"))
+                );
+                code = None;
+            } else {
+                lines.push(line);
+            }
+        } else if let Some(lang) = trimmed.strip_prefix("```") {
+            flush(&mut out, &mut paragraph);
+            code = Some((lang.trim().to_string(), Vec::new()));
+        } else if trimmed.is_empty() {
+            flush(&mut out, &mut paragraph);
+        } else {
+            paragraph.push(line);
+        }
+    }
+    // An unterminated fence still renders as code.
+    if let Some((_, lines)) = code {
+        let _ = writeln!(
+            out,
+            "<pre><code>{}</code></pre>",
+            escape_html(&lines.join("\n"))
+        );
+    }
+    flush(&mut out, &mut paragraph);
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// JSON
+// ---------------------------------------------------------------------------
+
+fn render_json(session: &Session, messages: &[Message]) -> String {
+    serde_json::to_string_pretty(&transcript_json(session, messages)).unwrap_or_default() + "\n"
+}
+
+/// A session and its messages as the JSON export document.
+pub fn transcript_json(session: &Session, messages: &[Message]) -> serde_json::Value {
+    let items: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "role": m.role.label(),
+                "tool": m.tool,
+                "timestamp": m.timestamp,
+                "text": m.text,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "agent": session.agent.to_string(),
+        "session_id": session.session_id,
+        "project_name": session.project_name,
+        "project_path": session.project_path,
+        "git_branch": session.git_branch,
+        "commit": session.commit,
+        "worktree": session.worktree,
+        "timestamp": session.timestamp,
+        "messages": items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_new_never_overwrites() {
+        let dir = std::env::temp_dir().join(format!("agf-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let session = Session {
+            project_name: "my app".to_string(),
+            ..Session::stub(crate::model::Agent::Codex, "abcdef123456")
+        };
+        let first = write_new(&dir, &session, ExportFormat::Markdown, "one").unwrap();
+        let second = write_new(&dir, &session, ExportFormat::Markdown, "two").unwrap();
+        assert_eq!(first, dir.join("my-app-abcdef12.md"));
+        assert_eq!(second, dir.join("my-app-abcdef12-2.md"));
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "one");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fenced_outgrows_inner_backticks() {
+        assert_eq!(fenced("ls"), "```\nls\n```");
+        assert_eq!(fenced("a ```b``` c"), "````\na ```b``` c\n````");
+    }
+
+    #[test]
+    fn prose_html_renders_code_blocks_and_escapes() {
+        let html = prose_html("Run <this>:\n\n```rust\nfn main() {}\n```\nafter");
+        assert_eq!(
+            html,
+            "<p>Run &lt;this&gt;:</p>\n\
+             <pre><code class=\"language-rust\">fn main() {}</code></pre>\n\
+             <p>after</p>\n"
+        );
+    }
+}
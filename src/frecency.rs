@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::Session;
+
+const HOUR_MS: i64 = 3_600_000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+const WEEK_MS: i64 = 7 * DAY_MS;
+/// Once the visit counts add up to more than this, they are all scaled down
+/// so old habits fade and the file stays small.
+const MAX_TOTAL: f64 = 1_000.0;
+
+/// How often agf resumed or opened each session, and when it last did.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Frecency {
+    /// Keyed by `<agent>:<session id>`.
+    visits: HashMap<String, Visit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Visit {
+    count: f64,
+    /// Unix ms of the latest visit.
+    last: i64,
+}
+
+/// `~/.local/share/agf/frecency.json` (platform data dir).
+pub fn store_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".local/share"))
+        .join("agf")
+        .join("frecency.json")
+}
+
+/// Count a resume or open of `session` now. The store is read and written
+/// back under a lock, so agf processes recording at once don't drop each
+/// other's visits.
+pub fn record(session: &Session) {
+    let _lock = StoreLock::take(&store_path());
+    let mut frecency = Frecency::load();
+    frecency.visit(session, chrono::Utc::now().timestamp_millis());
+    frecency.save();
+}
+
+/// `<store>.lock`, held from load to save in `record`. Created exclusively,
+/// so only one process holds it; removed on drop.
+struct StoreLock(Option<PathBuf>);
+
+impl StoreLock {
+    /// Left behind by a process that died while holding it.
+    const STALE: Duration = Duration::from_secs(10);
+    const WAIT: Duration = Duration::from_secs(2);
+
+    /// Wait for the lock; after `WAIT` go on without it, since losing a
+    /// visit beats hanging a resume.
+    fn take(store: &Path) -> Self {
+        let path = store.with_extension("json.lock");
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let deadline = Instant::now() + Self::WAIT;
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Self(Some(path)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|t| t.elapsed().ok());
+                    if age.is_some_and(|age| age > Self::STALE) {
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                }
+                Err(_) => return Self(None),
+            }
+            if Instant::now() >= deadline {
+                return Self(None);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        if let Some(path) = &self.0 {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn key(session: &Session) -> String {
+    format!(
+        "{}:{}",
+        crate::cache::agent_to_str(session.agent),
+        session.session_id
+    )
+}
+
+/// Weight of something that happened `age` ms ago.
+fn weight(age: i64) -> f64 {
+    match age {
+        a if a < HOUR_MS => 4.0,
+        a if a < DAY_MS => 2.0,
+        a if a < WEEK_MS => 1.0,
+        a if a < 4 * WEEK_MS => 0.5,
+        _ => 0.25,
+    }
+}
+
+impl Frecency {
+    /// The stored visits; empty when there is no (readable) store yet.
+    pub fn load() -> Self {
+        fs::read_to_string(store_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = store_path();
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string(self) {
+            let _ = crate::cache::write_atomic(&path, &json);
+        }
+    }
+
+    fn visit(&mut self, session: &Session, now: i64) {
+        let visit = self.visits.entry(key(session)).or_insert(Visit {
+            count: 0.0,
+            last: now,
+        });
+        visit.count += 1.0;
+        visit.last = now;
+
+        let total: f64 = self.visits.values().map(|v| v.count).sum();
+        if total > MAX_TOTAL {
+            for visit in self.visits.values_mut() {
+                visit.count *= 0.9;
+            }
+            self.visits.retain(|_, v| v.count >= 1.0);
+        }
+    }
+
+    /// Visits to `session`, weighted by how long ago the last one was.
+    /// Zero for sessions agf never resumed or opened.
+    pub fn score(&self, session: &Session, now: i64) -> f64 {
+        self.visits
+            .get(&key(session))
+            .map_or(0.0, |v| v.count * weight(now - v.last))
+    }
+
+    /// Sort key for `SortMode::Frecency`: the visit score plus the weight of
+    /// the session's own last activity, so a session worked on today
+    /// outranks one opened a few times last month.
+    pub fn rank(&self, session: &Session, now: i64) -> f64 {
+        self.score(session, now) + weight(now - session.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Agent;
+
+    fn session(id: &str, timestamp: i64) -> Session {
+        Session {
+            project_name: "agf".to_string(),
+            project_path: "/src/agf".to_string(),
+            timestamp,
+            ..Session::stub(Agent::ClaudeCode, id)
+        }
+    }
+
+    #[test]
+    fn visits_outweigh_recency_and_fade_over_time() {
+        let now = 100 * WEEK_MS;
+        let habit = session("habit", now - 2 * DAY_MS);
+        let fresh = session("fresh", now - 2 * HOUR_MS);
+        let mut frecency = Frecency::default();
+        for _ in 0..3 {
+            frecency.visit(&habit, now - 3 * HOUR_MS);
+        }
+        assert_eq!(frecency.score(&fresh, now), 0.0);
+        assert!(frecency.rank(&habit, now) > frecency.rank(&fresh, now));
+        // A month later the habit is worth less than a session used today.
+        let later = now + 5 * WEEK_MS;
+        let today = session("today", later - HOUR_MS);
+        assert!(frecency.rank(&habit, later) < frecency.rank(&today, later));
+
+        for i in 0..2_000 {
+            frecency.visit(&session(&i.to_string(), 0), now);
+        }
+        let total: f64 = frecency.visits.values().map(|v| v.count).sum();
+        assert!(total <= MAX_TOTAL + 1.0);
+    }
+
+    #[test]
+    fn store_lock_is_exclusive_and_breaks_when_stale() {
+        let dir = std::env::temp_dir().join(format!("agf-frecency-{}", std::process::id()));
+        let store = dir.join("frecency.json");
+        let lock_file = dir.join("frecency.json.lock");
+
+        let lock = StoreLock::take(&store);
+        assert_eq!(lock.0.as_deref(), Some(lock_file.as_path()));
+        drop(lock);
+        assert!(!lock_file.exists());
+
+        // A lock nobody released for a while doesn't hold anyone up.
+        let file = fs::File::create(&lock_file).unwrap();
+        let old = std::time::SystemTime::now() - 2 * StoreLock::STALE;
+        file.set_modified(old).unwrap();
+        let started = Instant::now();
+        assert!(StoreLock::take(&store).0.is_some());
+        assert!(started.elapsed() < StoreLock::WAIT);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -3,11 +3,13 @@ use nucleo::{
     Config, Matcher, Utf32Str,
 };
 
+use crate::frecency::Frecency;
 use crate::model::Session;
 
 pub struct FuzzyMatcher {
     matcher: Matcher,
     buf: Vec<char>,
+    frecency: Frecency,
 }
 
 pub struct MatchResult {
@@ -24,9 +26,17 @@ impl FuzzyMatcher {
         Self {
             matcher: Matcher::new(Config::DEFAULT),
             buf: Vec::new(),
+            frecency: Frecency::default(),
         }
     }
 
+    /// Break ties between equally good matches by `frecency`: the session
+    /// agf resumed or opened more often, and more lately, comes first.
+    pub fn with_frecency(mut self, frecency: Frecency) -> Self {
+        self.frecency = frecency;
+        self
+    }
+
     /// Filter a subset of `sessions` (addressed by `indices`) against `query`.
     ///
     /// `indices` is a list of indices into `sessions` identifying which sessions
@@ -79,7 +89,12 @@ impl FuzzyMatcher {
             })
             .collect();
 
-        results.sort_by_key(|r| std::cmp::Reverse(r.score));
+        let now = chrono::Utc::now().timestamp_millis();
+        results.sort_by_cached_key(|r| {
+            let visits = self.frecency.score(&sessions[indices[r.index]], now);
+            // Scores are non-negative, so their bit patterns order like them.
+            std::cmp::Reverse((r.score, visits.to_bits()))
+        });
         results
     }
 }
@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::model::Session;
+
+/// The working tree `path` is in: the nearest directory at or above it with
+/// a `.git` entry (a directory, or a file in linked worktrees).
+pub fn toplevel(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// The repository a directory belongs to, the same for every checkout of
+/// it: subdirectories, linked worktrees and other clones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repo {
+    /// Identity: the normalized `origin` URL (`github.com/owner/name`), or
+    /// the main working tree's path for a repository without remotes.
+    pub key: String,
+    /// Short name: the remote's last path segment, or the main working
+    /// tree's directory name.
+    pub name: String,
+    /// Working tree the directory is in.
+    pub toplevel: PathBuf,
+    /// Name of the linked worktree, when the directory isn't in the main one.
+    pub worktree: Option<String>,
+    /// Where the directory sits below `toplevel`, if not at its root.
+    pub subdir: Option<PathBuf>,
+}
+
+impl Repo {
+    /// Worktree and subdirectory, e.g. `feat-x/crates/core`, when the
+    /// directory isn't the repository's main root.
+    pub fn location(&self) -> Option<String> {
+        let subdir = self.subdir.as_deref().map(|p| p.to_string_lossy());
+        match (&self.worktree, subdir) {
+            (Some(wt), Some(sub)) => Some(format!("{wt}/{sub}")),
+            (Some(wt), None) => Some(wt.clone()),
+            (None, Some(sub)) => Some(sub.into_owned()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Resolve `path` to its repository. Results are remembered for the life
+/// of the process, since every session of a project asks again.
+pub fn repo(path: &str) -> Option<Repo> {
+    static RESOLVED: OnceLock<Mutex<HashMap<String, Option<Repo>>>> = OnceLock::new();
+    let resolved = RESOLVED.get_or_init(Mutex::default);
+    if let Some(repo) = resolved.lock().ok()?.get(path) {
+        return repo.clone();
+    }
+    let repo = resolve(Path::new(path));
+    if let Ok(mut resolved) = resolved.lock() {
+        resolved.insert(path.to_string(), repo.clone());
+    }
+    repo
+}
+
+/// Repository identity and display name of the project `session` ran in;
+/// the project path and name when it isn't in a repository (or is gone).
+pub fn identity(session: &Session) -> (String, String) {
+    match repo(&session.project_path) {
+        Some(repo) => (repo.key, repo.name),
+        None => (session.project_path.clone(), session.project_name.clone()),
+    }
+}
+
+fn resolve(path: &Path) -> Option<Repo> {
+    if path.as_os_str().is_empty() || !path.is_absolute() {
+        return None;
+    }
+    let toplevel = toplevel(path)?;
+    let mut subdir = path.strip_prefix(&toplevel).ok().map(Path::to_path_buf);
+    let mut worktree = None;
+    // A Claude Code worktree that has since been removed still names itself.
+    if let Some(rest) = subdir
+        .as_deref()
+        .and_then(|s| s.strip_prefix(".claude/worktrees").ok())
+    {
+        let mut parts = rest.components();
+        if let Some(Component::Normal(name)) = parts.next() {
+            worktree = Some(name.to_string_lossy().into_owned());
+            subdir = Some(parts.as_path().to_path_buf());
+        }
+    }
+    let subdir = subdir.filter(|s| !s.as_os_str().is_empty());
+
+    let (gitdir, common) = git_dirs(&toplevel)?;
+    if gitdir != common {
+        worktree = toplevel
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned());
+    }
+    let main = if common.file_name().is_some_and(|n| n == ".git") {
+        common.parent().map(Path::to_path_buf)
+    } else {
+        None
+    }
+    .unwrap_or_else(|| toplevel.clone());
+
+    let remote = fs::read_to_string(common.join("config"))
+        .ok()
+        .and_then(|config| remote_url(&config))
+        .map(|url| normalize_remote(&url));
+    let (key, name) = match remote {
+        Some(remote) => {
+            let name = remote.rsplit('/').next().unwrap_or(&remote).to_string();
+            (remote, name)
+        }
+        None => (
+            main.to_string_lossy().into_owned(),
+            main.file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned()),
+        ),
+    };
+    Some(Repo {
+        key,
+        name,
+        toplevel,
+        worktree,
+        subdir,
+    })
+}
+
+/// The git directory of the working tree at `toplevel` (its own `HEAD`)
+/// and the common directory shared by all its worktrees (refs, config).
+/// The same directory twice, except in linked worktrees.
+fn git_dirs(toplevel: &Path) -> Option<(PathBuf, PathBuf)> {
+    let dot_git = toplevel.join(".git");
+    if dot_git.is_dir() {
+        return Some((dot_git.clone(), dot_git));
+    }
+    // Linked worktree: `.git` is a file pointing at
+    // `<main>/.git/worktrees/<name>`, whose `commondir` leads back.
+    let gitdir = fs::read_to_string(&dot_git).ok()?;
+    let gitdir = toplevel.join(gitdir.strip_prefix("gitdir:")?.trim());
+    let common = match fs::read_to_string(gitdir.join("commondir")) {
+        Ok(common) => normalize(&gitdir.join(common.trim())),
+        Err(_) => gitdir.clone(),
+    };
+    Some((gitdir, common))
+}
+
+/// What a working tree has checked out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Head {
+    /// Branch name; `None` on a detached HEAD.
+    pub branch: Option<String>,
+    /// Commit id; `None` on a branch without commits yet.
+    pub commit: Option<String>,
+}
+
+/// Read HEAD of the working tree `path` is in, straight from the files:
+/// linked worktrees, packed refs and detached HEADs included.
+pub fn head(path: &str) -> Option<Head> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return None;
+    }
+    let (gitdir, common) = git_dirs(&toplevel(path)?)?;
+    let content = fs::read_to_string(gitdir.join("HEAD")).ok()?;
+    let content = content.trim();
+    match content.strip_prefix("ref:") {
+        Some(name) => {
+            let name = name.trim();
+            Some(Head {
+                branch: name
+                    .strip_prefix("refs/heads/")
+                    .filter(|b| !b.is_empty())
+                    .map(str::to_string),
+                commit: resolve_ref(&gitdir, &common, name),
+            })
+        }
+        None if is_object_id(content) => Some(Head {
+            branch: None,
+            commit: Some(content.to_string()),
+        }),
+        None => None,
+    }
+}
+
+/// Commit `name` (e.g. `refs/heads/main`) points at: a loose ref file,
+/// else its line in `packed-refs`. Symbolic refs are followed.
+fn resolve_ref(gitdir: &Path, common: &Path, name: &str) -> Option<String> {
+    let mut name = name.to_string();
+    // Bounded, in case of a symbolic ref cycle.
+    for _ in 0..5 {
+        // Per-worktree refs live in the worktree's own git directory.
+        let loose = [gitdir, common]
+            .iter()
+            .find_map(|dir| fs::read_to_string(dir.join(&name)).ok());
+        match loose {
+            Some(content) => match content.trim().strip_prefix("ref:") {
+                Some(target) => name = target.trim().to_string(),
+                None => return Some(content.trim().to_string()).filter(|id| is_object_id(id)),
+            },
+            None => return packed_ref(common, &name),
+        }
+    }
+    None
+}
+
+fn packed_ref(common: &Path, name: &str) -> Option<String> {
+    let packed = fs::read_to_string(common.join("packed-refs")).ok()?;
+    packed
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+        .filter_map(|line| line.split_once(' '))
+        .find(|(_, refname)| refname.trim() == name)
+        .map(|(id, _)| id.to_string())
+}
+
+/// A full SHA-1 or SHA-256 object id.
+fn is_object_id(s: &str) -> bool {
+    matches!(s.len(), 40 | 64) && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// What became of the branch a session worked on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchState {
+    /// Checked out in the session's working tree right now.
+    Current,
+    /// Still there, with commits the default branch doesn't have.
+    Exists,
+    /// Contained in the default branch, whether or not it still exists.
+    Merged,
+    /// Gone, with nothing showing it was merged.
+    Deleted,
+}
+
+impl BranchState {
+    pub fn label(self) -> &'static str {
+        match self {
+            BranchState::Current => "checked out",
+            BranchState::Exists => "not merged",
+            BranchState::Merged => "merged",
+            BranchState::Deleted => "deleted",
+        }
+    }
+}
+
+/// Whether `branch` of the repository at `path` still exists and whether
+/// the default branch contains it. A deleted branch counts as merged when
+/// `commit`, the HEAD the session recorded, is in the default branch.
+/// `None` when `path` is no longer a repository.
+///
+/// Refs are read from the files; only the ancestry check runs `git`.
+pub fn branch_state(path: &str, branch: &str, commit: Option<&str>) -> Option<BranchState> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return None;
+    }
+    let toplevel = toplevel(path)?;
+    let (gitdir, common) = git_dirs(&toplevel)?;
+    let checked_out = head(&toplevel.to_string_lossy()).and_then(|h| h.branch);
+    let tip = resolve_ref(&gitdir, &common, &format!("refs/heads/{branch}"));
+    let default = default_branch(&gitdir, &common);
+    let merged = |id: &str| {
+        default
+            .as_ref()
+            .is_some_and(|(name, target)| name != branch && is_ancestor(&toplevel, id, target))
+    };
+    Some(match tip {
+        Some(_) if checked_out.as_deref() == Some(branch) => BranchState::Current,
+        Some(tip) if merged(&tip) => BranchState::Merged,
+        Some(_) => BranchState::Exists,
+        None if commit.is_some_and(merged) => BranchState::Merged,
+        None => BranchState::Deleted,
+    })
+}
+
+/// The branch others are merged into: what `origin/HEAD` points at, else a
+/// local `main` or `master`. Returns its short name and the ref to
+/// compare against.
+fn default_branch(gitdir: &Path, common: &Path) -> Option<(String, String)> {
+    let remote = fs::read_to_string(common.join("refs/remotes/origin/HEAD"))
+        .ok()
+        .and_then(|c| {
+            let target = c.trim().strip_prefix("ref:")?.trim().to_string();
+            let name = target.strip_prefix("refs/remotes/origin/")?.to_string();
+            Some((name, target))
+        });
+    remote.or_else(|| {
+        ["main", "master"].into_iter().find_map(|name| {
+            let target = format!("refs/heads/{name}");
+            resolve_ref(gitdir, common, &target)?;
+            Some((name.to_string(), target))
+        })
+    })
+}
+
+/// `git merge-base --is-ancestor`: whether `commit` is reachable from
+/// `target`. False when git isn't installed or doesn't know the commit.
+fn is_ancestor(toplevel: &Path, commit: &str, target: &str) -> bool {
+    std::process::Command::new("git")
+        .arg("-C")
+        .arg(toplevel)
+        .args(["merge-base", "--is-ancestor", commit, target])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Resolve `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for part in path.components() {
+        match part {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// URL of `origin` in a git config file, else of the first remote.
+fn remote_url(config: &str) -> Option<String> {
+    let mut section = String::new();
+    let mut urls: Vec<(bool, String)> = Vec::new();
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line.to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "url" && section.starts_with("[remote ") {
+                urls.push((section == "[remote \"origin\"]", value.trim().to_string()));
+            }
+        }
+    }
+    let origin = urls.iter().position(|(origin, _)| *origin).unwrap_or(0);
+    (!urls.is_empty()).then(|| urls.swap_remove(origin).1)
+}
+
+/// `git@github.com:Owner/agf.git`, `https://user@github.com/Owner/agf` and
+/// `ssh://git@github.com:22/Owner/agf.git` all become `github.com/Owner/agf`.
+fn normalize_remote(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let (rest, scp) = match url.split_once("://") {
+        Some((_, rest)) => (rest, false),
+        None => (url, true),
+    };
+    let split = if scp {
+        rest.split_once(':')
+    } else {
+        rest.split_once('/')
+    };
+    let Some((host, path)) = split else {
+        return rest.to_string();
+    };
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    let host = host.split(':').next().unwrap_or(host).to_lowercase();
+    format!("{host}/{}", path.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worktrees_subdirectories_and_clones_share_a_repo() {
+        let tmp = std::env::temp_dir().join(format!("agf-git-{}", std::process::id()));
+        let main = tmp.join("agf");
+        fs::create_dir_all(main.join(".git/worktrees/feat-x")).unwrap();
+        fs::create_dir_all(main.join("src")).unwrap();
+        fs::write(
+            main.join(".git/config"),
+            "[core]\n\tbare = false\n[remote \"upstream\"]\n\turl = https://github.com/up/agf\n\
+             [remote \"origin\"]\n\turl = git@github.com:me/agf.git\n",
+        )
+        .unwrap();
+        fs::write(main.join(".git/worktrees/feat-x/commondir"), "../..\n").unwrap();
+        let linked = tmp.join("agf-feat-x");
+        fs::create_dir_all(linked.join("docs")).unwrap();
+        fs::write(
+            linked.join(".git"),
+            format!("gitdir: {}\n", main.join(".git/worktrees/feat-x").display()),
+        )
+        .unwrap();
+        let clone = tmp.join("elsewhere/agf");
+        fs::create_dir_all(clone.join(".git")).unwrap();
+        fs::write(
+            clone.join(".git/config"),
+            "[remote \"origin\"]\n\turl = ssh://git@GitHub.com:22/me/agf\n",
+        )
+        .unwrap();
+
+        let at = |p: &Path| resolve(p).unwrap();
+        let root = at(&main);
+        assert_eq!(root.key, "github.com/me/agf");
+        assert_eq!(root.name, "agf");
+        assert_eq!(root.location(), None);
+        assert_eq!(at(&main.join("src")).location().as_deref(), Some("src"));
+        let wt = at(&linked.join("docs"));
+        assert_eq!(wt.key, root.key);
+        assert_eq!(wt.location().as_deref(), Some("agf-feat-x/docs"));
+        assert_eq!(at(&clone).key, root.key);
+        let gone = at(&main.join(".claude/worktrees/fix-y"));
+        assert_eq!(gone.location().as_deref(), Some("fix-y"));
+        assert!(resolve(&tmp.join("not-a-repo")).is_none());
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn head_follows_worktrees_packed_refs_and_detached_heads() {
+        let tmp = std::env::temp_dir().join(format!("agf-head-{}", std::process::id()));
+        let (a, b, c) = ("a".repeat(40), "b".repeat(40), "c".repeat(40));
+        let main = tmp.join("agf");
+        let git = main.join(".git");
+        fs::create_dir_all(git.join("refs/heads/feat")).unwrap();
+        fs::create_dir_all(git.join("worktrees/fix")).unwrap();
+        fs::create_dir_all(main.join("src")).unwrap();
+        fs::write(git.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(git.join("refs/heads/feat/x"), format!("{b}\n")).unwrap();
+        fs::write(
+            git.join("packed-refs"),
+            format!("# pack-refs with: peeled\n{a} refs/heads/main\n{c} refs/tags/v1\n^{b}\n"),
+        )
+        .unwrap();
+        fs::write(git.join("worktrees/fix/HEAD"), format!("{c}\n")).unwrap();
+        fs::write(git.join("worktrees/fix/commondir"), "../..\n").unwrap();
+        let linked = tmp.join("agf-fix");
+        fs::create_dir_all(&linked).unwrap();
+        fs::write(
+            linked.join(".git"),
+            format!("gitdir: {}\n", git.join("worktrees/fix").display()),
+        )
+        .unwrap();
+
+        let at = |p: &Path| head(&p.to_string_lossy()).unwrap();
+        let root = at(&main.join("src"));
+        assert_eq!(root.branch.as_deref(), Some("main"));
+        assert_eq!(root.commit.as_deref(), Some(a.as_str()));
+        let detached = at(&linked);
+        assert_eq!(detached.branch, None);
+        assert_eq!(detached.commit.as_deref(), Some(c.as_str()));
+
+        let path = main.to_string_lossy();
+        let state = |branch: &str| branch_state(&path, branch, None);
+        assert_eq!(state("main"), Some(BranchState::Current));
+        assert_eq!(state("feat/x"), Some(BranchState::Exists));
+        assert_eq!(state("gone"), Some(BranchState::Deleted));
+        assert!(branch_state(&tmp.to_string_lossy(), "main", None).is_none());
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+}
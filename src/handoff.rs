@@ -0,0 +1,157 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::AgfError;
+use crate::model::Session;
+use crate::transcript::{self, Message, Role};
+
+/// Characters of conversation kept in a hand-off file. The oldest turns
+/// after the opening request are dropped first.
+const BUDGET: usize = 24_000;
+/// Longest single message kept verbatim.
+const MAX_MESSAGE: usize = 1_500;
+
+/// Write the condensed context of `session` to
+/// `<project>/.agf/handoff-<id>.md` and return its path.
+pub fn write_context(session: &Session) -> Result<PathBuf, AgfError> {
+    let messages: Vec<Message> = transcript::open(session)?.collect();
+    let dir = Path::new(&session.project_path).join(".agf");
+    fs::create_dir_all(&dir)?;
+    // Hand-off files are scratch; keep them out of the project's history.
+    let ignore = dir.join(".gitignore");
+    if !ignore.exists() {
+        fs::write(&ignore, "*\n")?;
+    }
+    let id: String = session.session_id.chars().take(8).collect();
+    let path = dir.join(format!("handoff-{id}.md"));
+    fs::write(&path, context(session, &messages))?;
+    Ok(path)
+}
+
+/// Initial prompt for the agent taking over.
+pub fn prompt(session: &Session, path: &Path) -> String {
+    format!(
+        "You are taking over a {} session on this project. Read {} for its recap and \
+         condensed transcript, then continue the work from where it stopped.",
+        session.agent,
+        path.display()
+    )
+}
+
+/// Markdown hand-off: the recap, then the conversation with tool output
+/// left out and long turns shortened, cut to [`BUDGET`].
+pub fn context(session: &Session, messages: &[Message]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Hand-off from {}", session.agent);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "- Project: {}", session.project_path);
+    let _ = writeln!(out, "- Session: {}", session.session_id);
+    if let Some(branch) = &session.git_branch {
+        let _ = writeln!(out, "- Branch: {branch}");
+    }
+    let _ = writeln!(out);
+
+    let recap = session
+        .recap
+        .as_deref()
+        .or(session.summaries.first().map(String::as_str));
+    if let Some(recap) = recap {
+        let _ = writeln!(out, "## Recap");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}", recap.trim());
+        let _ = writeln!(out);
+    }
+
+    let entries: Vec<String> = messages.iter().filter_map(condense).collect();
+    // Keep the opening request, then as many of the latest turns as fit.
+    let mut kept: Vec<&String> = Vec::new();
+    let mut used = entries.first().map_or(0, String::len);
+    for entry in entries.iter().skip(1).rev() {
+        if used + entry.len() > BUDGET {
+            break;
+        }
+        used += entry.len();
+        kept.push(entry);
+    }
+    kept.reverse();
+    let omitted = entries.len().saturating_sub(kept.len() + 1);
+
+    let _ = writeln!(out, "## Conversation (condensed)");
+    let _ = writeln!(out);
+    if let Some(first) = entries.first() {
+        out.push_str(first);
+    }
+    if omitted > 0 {
+        let _ = writeln!(out, "_… {omitted} earlier entries omitted …_");
+        let _ = writeln!(out);
+    }
+    for entry in kept {
+        out.push_str(entry);
+    }
+    out
+}
+
+fn condense(msg: &Message) -> Option<String> {
+    let text = msg.text.trim();
+    match msg.role {
+        Role::ToolResult => None,
+        Role::Tool => {
+            let name = msg.tool.as_deref().unwrap_or("tool");
+            let line = text.lines().next().unwrap_or("");
+            Some(format!("- _{name}_ {}\n\n", shorten(line, 200)))
+        }
+        Role::User | Role::Assistant if text.is_empty() => None,
+        Role::User => Some(format!("**User:**\n\n{}\n\n", shorten(text, MAX_MESSAGE))),
+        Role::Assistant => Some(format!(
+            "**Assistant:**\n\n{}\n\n",
+            shorten(text, MAX_MESSAGE)
+        )),
+    }
+}
+
+fn shorten(text: &str, max: usize) -> String {
+    if text.len() <= max {
+        return text.to_string();
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{} […]", &text[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Agent;
+
+    #[test]
+    fn context_keeps_opening_request_and_latest_turns() {
+        let session = Session {
+            project_name: "agf".to_string(),
+            project_path: "/src/agf".to_string(),
+            summaries: vec!["fix the tokenizer".to_string()],
+            git_branch: Some("main".to_string()),
+            ..Session::stub(Agent::ClaudeCode, "abc-123")
+        };
+        let mut messages = vec![Message::new(Role::User, "fix the tokenizer", None)];
+        for i in 0..100 {
+            messages.push(Message::new(
+                Role::Assistant,
+                format!("{i} {}", "x".repeat(900)),
+                None,
+            ));
+            messages.push(Message::new(Role::ToolResult, "y".repeat(5_000), None));
+        }
+        messages.push(Message::new(Role::User, "now run the tests", None));
+
+        let doc = context(&session, &messages);
+        assert!(doc.contains("## Recap\n\nfix the tokenizer"));
+        assert!(doc.contains("**User:**\n\nfix the tokenizer"));
+        assert!(doc.ends_with("**User:**\n\nnow run the tests\n\n"));
+        assert!(doc.contains("earlier entries omitted"));
+        assert!(!doc.contains("yyyy"));
+        assert!(doc.len() < BUDGET + 1_000);
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use crate::git::{self, Repo};
+use crate::model::Session;
+
+/// How close a session's project is to the directory agf runs in. Closer
+/// sorts first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Closeness {
+    /// Started in this very directory.
+    Exact,
+    /// Started in a directory above this one, inside the same repository.
+    Ancestor,
+    /// Started elsewhere in the same git repository, or another checkout
+    /// of it.
+    SameRepo,
+    Elsewhere,
+}
+
+/// The directory agf runs in and the repository around it.
+#[derive(Debug, Clone)]
+pub struct Here {
+    cwd: PathBuf,
+    repo: Option<Repo>,
+}
+
+impl Here {
+    pub fn new(cwd: impl Into<PathBuf>) -> Self {
+        let cwd = cwd.into();
+        let repo = git::repo(&cwd.to_string_lossy());
+        Self { cwd, repo }
+    }
+
+    /// The current directory, when there is one.
+    pub fn detect() -> Option<Self> {
+        std::env::current_dir().ok().map(Self::new)
+    }
+
+    pub fn closeness(&self, session: &Session) -> Closeness {
+        let path = Path::new(&session.project_path);
+        if path == self.cwd {
+            return Closeness::Exact;
+        }
+        match &self.repo {
+            Some(repo) if path.starts_with(&repo.toplevel) && self.cwd.starts_with(path) => {
+                Closeness::Ancestor
+            }
+            // Other worktrees and clones of the repository count too.
+            Some(repo) if git::repo(&session.project_path).is_some_and(|r| r.key == repo.key) => {
+                Closeness::SameRepo
+            }
+            // Outside a repository, a parent directory still counts, short
+            // of catch-alls like $HOME or /.
+            None if self.cwd.starts_with(path)
+                && path.parent().is_some()
+                && dirs::home_dir().as_deref() != Some(path) =>
+            {
+                Closeness::Ancestor
+            }
+            _ => Closeness::Elsewhere,
+        }
+    }
+
+    /// Whether `session` belongs to the project agf runs in.
+    pub fn contains(&self, session: &Session) -> bool {
+        self.closeness(session) != Closeness::Elsewhere
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Agent;
+
+    fn session(path: &str) -> Session {
+        Session {
+            project_name: "agf".to_string(),
+            project_path: path.to_string(),
+            ..Session::stub(Agent::ClaudeCode, path)
+        }
+    }
+
+    #[test]
+    fn sessions_rank_by_closeness_to_the_current_directory() {
+        let tmp = std::env::temp_dir().join(format!("agf-here-{}", std::process::id()));
+        let cwd = tmp.join("repo/crates/core");
+        std::fs::create_dir_all(&cwd).unwrap();
+        std::fs::create_dir_all(tmp.join("repo/.git")).unwrap();
+        let here = Here::new(&cwd);
+        let at = |p: &str| session(&tmp.join(p).to_string_lossy());
+
+        assert_eq!(here.closeness(&at("repo/crates/core")), Closeness::Exact);
+        assert_eq!(here.closeness(&at("repo/crates/core/")), Closeness::Exact);
+        assert_eq!(here.closeness(&at("repo")), Closeness::Ancestor);
+        assert_eq!(here.closeness(&at("repo/docs")), Closeness::SameRepo);
+        assert_eq!(here.closeness(&at("other")), Closeness::Elsewhere);
+        // Above the repository is not part of it.
+        assert!(!here.contains(&session(&tmp.to_string_lossy())));
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
@@ -0,0 +1,240 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Action, Session};
+use crate::shell::CommandShell;
+
+/// Where an action's command runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LaunchTarget {
+    /// The shell agf was started from (via the shell wrapper).
+    #[default]
+    Shell,
+    /// A new window in the current tmux session.
+    TmuxWindow,
+    /// A new pane split off the current tmux window.
+    TmuxPane,
+    /// A new detached tmux session, attached to later.
+    TmuxSession,
+    /// A new tab in the current zellij session.
+    ZellijTab,
+}
+
+/// `[launch]` in config.toml: a target per action, falling back to
+/// `default`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LaunchConfig {
+    #[serde(default)]
+    pub default: LaunchTarget,
+    pub resume: Option<LaunchTarget>,
+    pub new_session: Option<LaunchTarget>,
+    pub handoff: Option<LaunchTarget>,
+    pub open: Option<LaunchTarget>,
+}
+
+impl LaunchConfig {
+    pub fn target(&self, action: Action) -> LaunchTarget {
+        let specific = match action {
+            Action::Resume => self.resume,
+            Action::NewSession => self.new_session,
+            Action::Handoff => self.handoff,
+            Action::Open => self.open,
+            // `cd` only means something in the shell agf was started from.
+            _ => return LaunchTarget::Shell,
+        };
+        specific.unwrap_or(self.default)
+    }
+}
+
+fn config() -> &'static LaunchConfig {
+    static CONFIG: OnceLock<LaunchConfig> = OnceLock::new();
+    CONFIG.get_or_init(|| crate::settings::Settings::load().launch)
+}
+
+/// Run `cmd` (a full `cd … && agent …` command) for `action` on `session`
+/// in the configured target. Resuming a session that already has a window
+/// of its own switches to that window instead.
+pub fn place(action: Action, session: &Session, cmd: String) -> String {
+    place_in(config().target(action), action, session, cmd)
+}
+
+fn place_in(target: LaunchTarget, action: Action, session: &Session, cmd: String) -> String {
+    let shell = CommandShell::from_env();
+    if target == LaunchTarget::Shell {
+        return cmd;
+    }
+    if action == Action::Resume {
+        if let Some(switch) = existing(target, session) {
+            return switch;
+        }
+    }
+    let inside_tmux = std::env::var_os("TMUX").is_some();
+    let inside_zellij = std::env::var_os("ZELLIJ").is_some();
+    let raw = window_name(action, session);
+    let name = shell.quote(&raw);
+    let quoted = shell.quote(&cmd);
+    match target {
+        LaunchTarget::Shell => cmd,
+        LaunchTarget::TmuxWindow if inside_tmux => format!("tmux new-window -n {name} {quoted}"),
+        LaunchTarget::TmuxPane if inside_tmux => format!(
+            "tmux split-window {quoted} {} select-pane -T {name}",
+            shell.quote(";")
+        ),
+        LaunchTarget::TmuxSession => {
+            // Starting the same project twice would reuse its name, which
+            // tmux refuses.
+            let taken = output("tmux", &["list-sessions", "-F", "#{session_name}"]);
+            let raw = unique_name(&raw, taken.as_deref().unwrap_or_default());
+            format!(
+                "tmux new-session -d -s {} {quoted} && {}",
+                shell.quote(&raw),
+                shell.warn_and(
+                    &format!("started in tmux session {raw}; attach with `tmux attach -t {raw}`"),
+                    "true"
+                )
+            )
+        }
+        LaunchTarget::ZellijTab if inside_zellij => format!(
+            "zellij action new-tab --name {name} && zellij action write-chars {quoted} \
+             && zellij action write 13"
+        ),
+        LaunchTarget::TmuxWindow | LaunchTarget::TmuxPane | LaunchTarget::ZellijTab => shell
+            .warn_and(
+                &format!("not inside {}; running here", multiplexer(target)),
+                &cmd,
+            ),
+    }
+}
+
+fn multiplexer(target: LaunchTarget) -> &'static str {
+    match target {
+        LaunchTarget::ZellijTab => "zellij",
+        _ => "tmux",
+    }
+}
+
+/// Window, pane or session name for what `action` starts: the project and
+/// the session id for a resume, the project alone otherwise. Only the
+/// characters tmux accepts in session names are kept.
+pub fn window_name(action: Action, session: &Session) -> String {
+    let raw = if action == Action::Resume {
+        let id: String = session.session_id.chars().take(8).collect();
+        format!("{}-{id}", session.project_name)
+    } else {
+        session.project_name.clone()
+    };
+    raw.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// `name`, or `name-2`, `name-3`, … when it is already one of the `taken`
+/// lines.
+fn unique_name(name: &str, taken: &str) -> String {
+    let taken: Vec<&str> = taken.lines().collect();
+    std::iter::once(name.to_string())
+        .chain((2..).map(|n| format!("{name}-{n}")))
+        .find(|candidate| !taken.contains(&candidate.as_str()))
+        .unwrap_or_default()
+}
+
+/// Whether resuming `session` in the configured target would switch to a
+/// window that already runs it rather than start the agent again.
+pub fn resumes_existing(session: &Session) -> bool {
+    let target = config().target(Action::Resume);
+    target != LaunchTarget::Shell && existing(target, session).is_some()
+}
+
+/// Command that switches to the window, pane or tmux session a previous
+/// resume of `session` opened, when it is still there.
+fn existing(target: LaunchTarget, session: &Session) -> Option<String> {
+    let shell = CommandShell::from_env();
+    let name = window_name(Action::Resume, session);
+    if target == LaunchTarget::ZellijTab {
+        let tabs = output("zellij", &["action", "query-tab-names"])?;
+        return tabs
+            .lines()
+            .any(|t| t == name)
+            .then(|| format!("zellij action go-to-tab-name {}", shell.quote(&name)));
+    }
+    let panes = output(
+        "tmux",
+        &[
+            "list-panes",
+            "-a",
+            "-F",
+            "#{pane_id}\t#{session_name}\t#{window_name}\t#{pane_title}",
+        ],
+    )?;
+    let pane = panes.lines().find_map(|line| {
+        let mut fields = line.split('\t');
+        let pane = fields.next()?;
+        fields.any(|f| f == name).then_some(pane)
+    })?;
+    let verb = if std::env::var_os("TMUX").is_some() {
+        "switch-client"
+    } else {
+        "attach"
+    };
+    Some(format!("tmux {verb} -t {}", shell.quote(pane)))
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let out = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Agent;
+
+    #[test]
+    fn targets_fall_back_to_default_and_names_are_tmux_safe() {
+        let config: LaunchConfig = toml::from_str(
+            r#"
+            default = "tmux-window"
+            open = "shell"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.target(Action::Resume), LaunchTarget::TmuxWindow);
+        assert_eq!(config.target(Action::Open), LaunchTarget::Shell);
+        assert_eq!(config.target(Action::Cd), LaunchTarget::Shell);
+
+        let session = Session {
+            project_name: "my.app".to_string(),
+            project_path: "/src/my.app".to_string(),
+            ..Session::stub(Agent::ClaudeCode, "0199abcd-ef")
+        };
+        assert_eq!(window_name(Action::Resume, &session), "my-app-0199abcd");
+        let cmd = place_in(
+            LaunchTarget::TmuxSession,
+            Action::NewSession,
+            &session,
+            "cd '/src/my.app' && claude".to_string(),
+        );
+        assert!(
+            cmd.starts_with("tmux new-session -d -s 'my-app' 'cd '\\''/src/my.app'\\'' && claude'")
+        );
+    }
+
+    #[test]
+    fn taken_names_get_a_numbered_suffix() {
+        assert_eq!(unique_name("api", ""), "api");
+        assert_eq!(unique_name("api", "web\napi-2"), "api");
+        assert_eq!(unique_name("api", "api\napi-2\nweb"), "api-3");
+    }
+}
@@ -2,7 +2,9 @@ use std::io::{self, IsTerminal, Write};
 
 use unicode_width::UnicodeWidthStr;
 
+use crate::live::Live;
 use crate::model::Session;
+use crate::usage::PriceTable;
 
 pub enum OutputFormat {
     Table,
@@ -20,46 +22,46 @@ impl OutputFormat {
     }
 }
 
-pub fn list_sessions(sessions: &[Session], format: OutputFormat) {
+pub fn list_sessions(sessions: &[Session], live: &Live, format: OutputFormat) {
     match format {
-        OutputFormat::Table => print_table(sessions),
-        OutputFormat::Json => print_json(sessions),
+        OutputFormat::Table => print_table(sessions, live),
+        OutputFormat::Json => print_json(sessions, live),
         OutputFormat::Csv => print_csv(sessions),
     }
 }
 
-struct Ansi {
+pub(crate) struct Ansi {
     enabled: bool,
 }
 
 impl Ansi {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             enabled: io::stdout().is_terminal(),
         }
     }
-    fn rgb(&self, r: u8, g: u8, b: u8, text: &str) -> String {
+    pub(crate) fn rgb(&self, r: u8, g: u8, b: u8, text: &str) -> String {
         if self.enabled {
             format!("\x1b[38;2;{r};{g};{b}m{text}\x1b[0m")
         } else {
             text.to_string()
         }
     }
-    fn bold(&self, text: &str) -> String {
+    pub(crate) fn bold(&self, text: &str) -> String {
         if self.enabled {
             format!("\x1b[1m{text}\x1b[0m")
         } else {
             text.to_string()
         }
     }
-    fn dim(&self, text: &str) -> String {
+    pub(crate) fn dim(&self, text: &str) -> String {
         if self.enabled {
             format!("\x1b[2m{text}\x1b[0m")
         } else {
             text.to_string()
         }
     }
-    fn bold_rgb(&self, r: u8, g: u8, b: u8, text: &str) -> String {
+    pub(crate) fn bold_rgb(&self, r: u8, g: u8, b: u8, text: &str) -> String {
         if self.enabled {
             format!("\x1b[1;38;2;{r};{g};{b}m{text}\x1b[0m")
         } else {
@@ -68,7 +70,7 @@ impl Ansi {
     }
 }
 
-fn print_table(sessions: &[Session]) {
+fn print_table(sessions: &[Session], live: &Live) {
     if sessions.is_empty() {
         return;
     }
@@ -128,11 +130,18 @@ fn print_table(sessions: &[Session]) {
             truncate(s.git_branch.as_deref().unwrap_or("—"), 10)
         );
         let num = format!("{:>3}", i + 1);
+        // A running agent holds this session.
+        let status = if live.pid(s).is_some() {
+            a.rgb(52, 211, 153, "●")
+        } else {
+            " ".to_string()
+        };
 
         let _ = writeln!(
             out,
-            "   {}  {}  {}  {}  {}  {}",
+            "  {} {} {}  {}  {}  {}  {}",
             a.dim(&num),
+            status,
             a.bold(&project),
             a.bold_rgb(r, g, b_val, &agent),
             a.dim(&time),
@@ -143,10 +152,26 @@ fn print_table(sessions: &[Session]) {
     let _ = writeln!(out);
 }
 
-fn print_json(sessions: &[Session]) {
+fn print_json(sessions: &[Session], live: &Live) {
+    if let Ok(json) = serde_json::to_string_pretty(&sessions_json(sessions, live)) {
+        println!("{json}");
+    }
+}
+
+/// The `agf list --format json` array.
+pub fn sessions_json(sessions: &[Session], live: &Live) -> serde_json::Value {
+    let prices = PriceTable::load();
     let items: Vec<serde_json::Value> = sessions
         .iter()
         .map(|s| {
+            let tokens = s.token_totals();
+            let repo = crate::git::repo(&s.project_path).map(|r| {
+                serde_json::json!({
+                    "key": r.key,
+                    "name": r.name,
+                    "location": r.location(),
+                })
+            });
             serde_json::json!({
                 "agent": s.agent.to_string(),
                 "session_id": s.session_id,
@@ -155,27 +180,52 @@ fn print_json(sessions: &[Session]) {
                 "timestamp": s.timestamp,
                 "time": s.time_display(),
                 "git_branch": s.git_branch,
+                "branch_is_live": s.branch_is_live,
+                "commit": s.commit,
                 "worktree": s.worktree,
+                "repo": repo,
+                "data_root": s.data_root,
+                "pid": live.pid(s),
                 "summaries": s.summaries,
+                "tokens": {
+                    "input": tokens.input,
+                    "output": tokens.output,
+                    "cache_read": tokens.cache_read,
+                    "cache_write": tokens.cache_write,
+                },
+                "models": s.models(),
+                "cost_usd": prices.cost(&s.usage),
             })
         })
         .collect();
-    if let Ok(json) = serde_json::to_string_pretty(&items) {
-        println!("{json}");
-    }
+    serde_json::Value::Array(items)
 }
 
 fn print_csv(sessions: &[Session]) {
-    println!("project,agent,time,path,session_id,branch");
+    let prices = PriceTable::load();
+    println!(
+        "project,agent,time,path,session_id,branch,\
+         input_tokens,output_tokens,cache_read_tokens,cache_write_tokens,models,cost_usd"
+    );
     for s in sessions {
+        let tokens = s.token_totals();
         println!(
-            "{},{},{},{},{},{}",
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
             csv_escape(&s.project_name),
             s.agent,
             s.time_display(),
             csv_escape(&s.project_path),
             s.session_id,
             s.git_branch.as_deref().unwrap_or(""),
+            tokens.input,
+            tokens.output,
+            tokens.cache_read,
+            tokens.cache_write,
+            csv_escape(&s.models().join(";")),
+            prices
+                .cost(&s.usage)
+                .map(|c| format!("{c:.4}"))
+                .unwrap_or_default(),
         );
     }
 }
@@ -197,19 +247,3 @@ fn truncate(s: &str, max: usize) -> String {
         format!("{prefix}…")
     }
 }
-
-pub fn filter_by_agent(sessions: Vec<Session>, agent_name: &str) -> Vec<Session> {
-    let agent_lower = agent_name.to_lowercase();
-    sessions
-        .into_iter()
-        .filter(|s| {
-            s.agent.cli_name().to_lowercase() == agent_lower
-                || s.agent.to_string().to_lowercase() == agent_lower
-                || s.agent
-                    .to_string()
-                    .to_lowercase()
-                    .replace(' ', "")
-                    .contains(&agent_lower)
-        })
-        .collect()
-}
@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::model::{Agent, Session};
+
+/// An agent CLI process found on this machine.
+#[derive(Debug, Clone)]
+pub struct Process {
+    pub pid: u32,
+    pub agent: Agent,
+    /// Working directory; `None` when it can't be read (another user's
+    /// process).
+    pub cwd: Option<PathBuf>,
+    pub target: Target,
+    /// Start time in Unix ms.
+    pub started: i64,
+}
+
+/// Which session a process was started on, judging by its argv.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A named session: `--resume <id>`, `codex resume <id>`, `pi --session <file>`.
+    Id(String),
+    /// The directory's latest session: `claude --continue`,
+    /// `codex resume --last`, `kiro-cli chat --resume`.
+    Latest,
+    /// A fresh session, or one picked interactively.
+    New,
+}
+
+/// Agents that are running and the sessions they hold.
+#[derive(Debug, Default)]
+pub struct Live {
+    held: HashMap<(Agent, String), u32>,
+    agents: Vec<Agent>,
+    procs: Vec<Process>,
+}
+
+/// Agent processes running right now, not yet matched to sessions. Listing
+/// them is the slow half of [`Live::detect`] (`pgrep` per agent outside
+/// Linux), so the TUI does it on a worker thread.
+pub struct Processes {
+    procs: Vec<Process>,
+    /// Running agents where processes can't be listed one by one.
+    agents: Option<Vec<Agent>>,
+}
+
+impl Processes {
+    pub fn list() -> Self {
+        Self {
+            procs: processes(),
+            agents: cfg!(not(target_os = "linux")).then(running_agents),
+        }
+    }
+
+    /// Map each process to the session it is in.
+    pub fn resolve(&self, sessions: &[Session]) -> Live {
+        let mut live = Live::resolve(&self.procs, sessions);
+        if let Some(agents) = &self.agents {
+            live.agents = agents.clone();
+        }
+        live
+    }
+}
+
+impl Live {
+    /// Look for running agents and map each to the session it is in.
+    pub fn detect(sessions: &[Session]) -> Self {
+        Processes::list().resolve(sessions)
+    }
+
+    fn resolve(procs: &[Process], sessions: &[Session]) -> Self {
+        let mut held = HashMap::new();
+        let mut agents: Vec<Agent> = Vec::new();
+        for p in procs {
+            if !agents.contains(&p.agent) {
+                agents.push(p.agent);
+            }
+            let id = match &p.target {
+                Target::Id(id) => Some(id.clone()),
+                // A session started without an id is the newest one of the
+                // agent in its directory: any age for `--continue`, and
+                // written since the process started for a fresh one.
+                target => p.cwd.as_deref().and_then(|cwd| {
+                    sessions
+                        .iter()
+                        .filter(|s| {
+                            s.agent == p.agent
+                                && Path::new(&s.project_path) == cwd
+                                && (*target == Target::Latest || s.timestamp >= p.started)
+                        })
+                        .max_by_key(|s| s.timestamp)
+                        .map(|s| s.session_id.clone())
+                }),
+            };
+            if let Some(id) = id {
+                held.insert((p.agent, id), p.pid);
+            }
+        }
+        Self {
+            held,
+            agents,
+            procs: procs.to_vec(),
+        }
+    }
+
+    /// Pid of the process running `session`, if any.
+    pub fn pid(&self, session: &Session) -> Option<u32> {
+        self.held
+            .get(&(session.agent, session.session_id.clone()))
+            .copied()
+    }
+
+    /// Pid of a process holding `session`: one started on it, or one with
+    /// its log file open (Codex keeps the rollout open while it runs).
+    pub fn holder(&self, session: &Session) -> Option<u32> {
+        if let Some(pid) = self.pid(session) {
+            return Some(pid);
+        }
+        // `/proc` links are canonical paths.
+        let file = fs::canonicalize(session_file(session)?).ok()?;
+        self.procs
+            .iter()
+            .filter(|p| p.agent == session.agent)
+            .find(|p| open_files(p.pid).any(|f| f == file))
+            .map(|p| p.pid)
+    }
+
+    /// Agents with at least one running process.
+    pub fn agents(&self) -> &[Agent] {
+        &self.agents
+    }
+}
+
+/// Every agent process visible in `/proc`.
+#[cfg(target_os = "linux")]
+pub fn processes() -> Vec<Process> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let boot = boot_time();
+    let me = std::process::id();
+    entries
+        .filter_map(|e| e.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|&pid| pid != me)
+        .filter_map(|pid| process(pid, boot))
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn processes() -> Vec<Process> {
+    Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+fn process(pid: u32, boot: i64) -> Option<Process> {
+    let dir = PathBuf::from(format!("/proc/{pid}"));
+    let argv: Vec<String> = fs::read(dir.join("cmdline"))
+        .ok()?
+        .split(|&b| b == 0)
+        .filter(|a| !a.is_empty())
+        .map(|a| String::from_utf8_lossy(a).into_owned())
+        .collect();
+    let comm = fs::read_to_string(dir.join("comm")).unwrap_or_default();
+    let (agent, args) = identify(comm.trim(), &argv)?;
+    let stat = Stat::read(pid);
+    Some(Process {
+        pid,
+        agent,
+        cwd: fs::read_link(dir.join("cwd")).ok(),
+        target: target(agent, args),
+        started: stat.map_or(0, |s| boot + s.start_ticks as i64 * 1000 / CLOCK_TICKS),
+    })
+}
+
+/// Files `pid` has open, from its `/proc/<pid>/fd` links.
+fn open_files(pid: u32) -> impl Iterator<Item = PathBuf> {
+    fs::read_dir(format!("/proc/{pid}/fd"))
+        .into_iter()
+        .flatten()
+        .filter_map(|fd| fs::read_link(fd.ok()?.path()).ok())
+}
+
+/// The log an agent appends to while `session` runs, for the agents that
+/// keep one file per session.
+fn session_file(session: &Session) -> Option<PathBuf> {
+    use crate::scanner;
+
+    if let Some(file) = &session.session_file {
+        return Some(file.clone());
+    }
+    let root = crate::config::session_root(session).ok()?;
+    let id = &session.session_id;
+    match session.agent {
+        Agent::ClaudeCode => scanner::claude::session_file(&root, id),
+        Agent::Codex => scanner::codex::rollout_file(&root, id),
+        Agent::Gemini => scanner::gemini::session_file(&root, id),
+        Agent::CursorAgent => scanner::cursor_agent::transcript_file(&root, id),
+        Agent::Pi => scanner::pi::session_file(&root.join("sessions"), id),
+        Agent::OpenCode | Agent::Kiro | Agent::Custom(_) => None,
+    }
+}
+
+/// `USER_HZ`: the kernel reports process times in 1/100 s on every
+/// architecture Linux supports.
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS: i64 = 100;
+
+/// Boot time in Unix ms, from the `btime` line of `/proc/stat`.
+#[cfg(target_os = "linux")]
+fn boot_time() -> i64 {
+    fs::read_to_string("/proc/stat")
+        .ok()
+        .and_then(|s| {
+            s.lines()
+                .find_map(|l| l.strip_prefix("btime ")?.trim().parse::<i64>().ok())
+        })
+        .map_or(0, |secs| secs * 1000)
+}
+
+/// The fields agf needs from `/proc/<pid>/stat`.
+struct Stat {
+    ppid: u32,
+    start_ticks: u64,
+}
+
+impl Stat {
+    fn read(pid: u32) -> Option<Self> {
+        let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // The command name in parentheses may contain spaces; the fields
+        // after it start at `state` (field 3).
+        let fields: Vec<&str> = stat
+            .get(stat.rfind(')')? + 1..)?
+            .split_whitespace()
+            .collect();
+        Some(Self {
+            ppid: fields.get(1)?.parse().ok()?,
+            start_ticks: fields.get(19)?.parse().ok()?,
+        })
+    }
+}
+
+/// The agent a command line runs and the arguments after the executable.
+/// Agents run either as their own binary or as a script under an
+/// interpreter (`node …/bin/codex.js`); `comm` catches the ones that
+/// rename themselves.
+fn identify<'a>(comm: &str, argv: &'a [String]) -> Option<(Agent, &'a [String])> {
+    let stem = |arg: &String| {
+        let name = Path::new(arg).file_name()?.to_str()?;
+        Some(
+            [".js", ".mjs", ".cjs"]
+                .iter()
+                .find_map(|ext| name.strip_suffix(ext))
+                .unwrap_or(name)
+                .to_string(),
+        )
+    };
+    let agent_named = |name: &str| {
+        Agent::all()
+            .iter()
+            .copied()
+            .find(|a| !a.cli_name().is_empty() && a.cli_name() == name)
+    };
+    let exe = argv.first().and_then(stem).unwrap_or_default();
+    if let Some(agent) = agent_named(&exe) {
+        return Some((agent, argv.get(1..).unwrap_or_default()));
+    }
+    if ["node", "bun", "deno", "python", "python3"].contains(&exe.as_str()) {
+        if let Some(agent) = argv.get(1).and_then(stem).and_then(|s| agent_named(&s)) {
+            return Some((agent, argv.get(2..).unwrap_or_default()));
+        }
+    }
+    agent_named(comm).map(|agent| (agent, argv.get(1..).unwrap_or_default()))
+}
+
+/// Read the session target from an agent's arguments.
+fn target(agent: Agent, args: &[String]) -> Target {
+    let value = |flags: &[&str]| {
+        args.iter().enumerate().find_map(|(i, arg)| {
+            flags.iter().find_map(|flag| {
+                if arg == flag {
+                    args.get(i + 1).filter(|v| !v.starts_with('-')).cloned()
+                } else {
+                    arg.strip_prefix(flag)?
+                        .strip_prefix('=')
+                        .map(str::to_string)
+                }
+            })
+        })
+    };
+    let has = |flags: &[&str]| args.iter().any(|a| flags.contains(&a.as_str()));
+
+    let id = match agent {
+        Agent::ClaudeCode => value(&["--resume", "-r", "--session-id"]),
+        Agent::Codex => value(&["resume"]),
+        Agent::OpenCode => value(&["--session", "-s"]),
+        Agent::CursorAgent => value(&["--resume"]),
+        // `--resume latest` and `--resume 3` pick by recency, not by id.
+        Agent::Gemini => value(&["--resume", "-r"])
+            .filter(|v| v != "latest" && !v.bytes().all(|b| b.is_ascii_digit())),
+        Agent::Pi => value(&["--session"])
+            .and_then(|file| crate::scanner::pi::session_id_of(Path::new(&file))),
+        Agent::Kiro | Agent::Custom(_) => None,
+    };
+    if let Some(id) = id {
+        return Target::Id(id);
+    }
+    let latest = match agent {
+        Agent::ClaudeCode | Agent::Pi => has(&["--continue", "-c"]),
+        Agent::Codex => has(&["resume"]) && has(&["--last"]),
+        Agent::Gemini => has(&["--resume", "-r"]),
+        Agent::Kiro => has(&["--resume", "-r"]),
+        _ => false,
+    };
+    if latest {
+        Target::Latest
+    } else {
+        Target::New
+    }
+}
+
+/// Agents running anywhere, by executable name. Used where `/proc` isn't
+/// available.
+fn running_agents() -> Vec<Agent> {
+    Agent::all()
+        .iter()
+        .copied()
+        .filter(|agent| {
+            std::process::Command::new("pgrep")
+                .args(["-x", agent.cli_name()])
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Command that brings the terminal running `pid` to the front: the tmux
+/// pane it lives in, when agf itself runs inside tmux.
+pub fn focus_cmd(pid: u32) -> Option<String> {
+    std::env::var_os("TMUX")?;
+    let out = std::process::Command::new("tmux")
+        .args(["list-panes", "-a", "-F", "#{pane_pid} #{pane_id}"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let panes: HashMap<u32, String> = String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|l| {
+            let (pid, pane) = l.split_once(' ')?;
+            Some((pid.parse().ok()?, pane.to_string()))
+        })
+        .collect();
+    // The pane's pid is the shell the agent was started from.
+    let mut current = pid;
+    for _ in 0..32 {
+        if let Some(pane) = panes.get(&current) {
+            return Some(format!("tmux switch-client -t '{pane}'"));
+        }
+        current = Stat::read(current)?.ppid;
+        if current <= 1 {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn session(agent: Agent, id: &str, path: &str, timestamp: i64) -> Session {
+        Session {
+            project_name: "p".to_string(),
+            project_path: path.to_string(),
+            timestamp,
+            ..Session::stub(agent, id)
+        }
+    }
+
+    #[test]
+    fn processes_map_to_the_sessions_they_run() {
+        let resumed = args("/usr/bin/node /opt/bin/codex.js resume 0199-abc --full-auto");
+        let (agent, rest) = identify("node", &resumed).unwrap();
+        assert_eq!(agent, Agent::Codex);
+        assert_eq!(target(agent, rest), Target::Id("0199-abc".to_string()));
+        let continued = args("claude -c --permission-mode plan");
+        let (agent, rest) = identify("claude", &continued).unwrap();
+        assert_eq!(target(agent, rest), Target::Latest);
+        assert_eq!(
+            target(Agent::ClaudeCode, &args("--resume=abc")),
+            Target::Id("abc".to_string())
+        );
+        assert!(identify("bash", &args("/bin/bash -l")).is_none());
+
+        let sessions = vec![
+            session(Agent::ClaudeCode, "old", "/src/a", 1_000),
+            session(Agent::ClaudeCode, "new", "/src/a", 5_000),
+            session(Agent::ClaudeCode, "other", "/src/b", 9_000),
+        ];
+        let proc = |pid, target, cwd: &str, started| Process {
+            pid,
+            agent: Agent::ClaudeCode,
+            cwd: Some(PathBuf::from(cwd)),
+            target,
+            started,
+        };
+        let live = Live::resolve(
+            &[
+                proc(10, Target::Latest, "/src/a", 2_000),
+                // Started after the last session in /src/b was written: it
+                // hasn't written its own yet.
+                proc(11, Target::New, "/src/b", 10_000),
+            ],
+            &sessions,
+        );
+        assert_eq!(live.pid(&sessions[1]), Some(10));
+        assert_eq!(live.pid(&sessions[0]), None);
+        assert_eq!(live.pid(&sessions[2]), None);
+        assert_eq!(live.agents(), [Agent::ClaudeCode]);
+    }
+}
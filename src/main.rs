@@ -3,15 +3,30 @@ mod cache;
 mod config;
 mod delete;
 mod error;
+mod export;
+mod frecency;
 mod fuzzy;
+mod git;
+mod handoff;
+mod here;
+mod launch;
 mod list;
+mod live;
+mod mcp;
 mod model;
 mod plugin;
+mod prune;
+mod query;
 mod scanner;
+mod search;
+mod serve;
 mod settings;
 mod shell;
 mod stats;
+mod transcript;
+mod trash;
 mod tui;
+mod usage;
 mod watch;
 
 use std::io::IsTerminal;
@@ -43,7 +58,8 @@ enum Commands {
     Setup,
     /// Fuzzy-match a session and resume it directly (no TUI)
     Resume {
-        /// Fuzzy query to match a session (project name, path, summary)
+        /// Query to match a session: fuzzy text plus qualifiers such as
+        /// agent:codex, branch:feat, since:3d, -wip
         query: Vec<String>,
         /// Filter by agent name (e.g. claude, codex, gemini)
         #[arg(long)]
@@ -54,9 +70,19 @@ enum Commands {
         /// Permission/approval mode (e.g. acceptEdits, yolo, full-auto)
         #[arg(long)]
         mode: Option<String>,
+        /// If the session is already open in a running agent, continue in a
+        /// fork of it (Claude Code)
+        #[arg(long, conflicts_with = "force")]
+        fork: bool,
+        /// Resume even if the session is already open in a running agent
+        #[arg(long)]
+        force: bool,
     },
     /// List sessions as plain text (for scripting)
     List {
+        /// Optional query: fuzzy text plus qualifiers such as agent:codex,
+        /// branch:feat, path:api, since:3d, before:2026-01-01, has:recap, -wip
+        query: Vec<String>,
         /// Filter by agent name (e.g. claude, codex, gemini)
         #[arg(long)]
         agent: Option<String>,
@@ -67,6 +93,36 @@ enum Commands {
         #[arg(long, default_value = "table")]
         format: String,
     },
+    /// Search the full transcripts of all sessions
+    Grep {
+        /// Text to search for (case-insensitive)
+        #[arg(required = true)]
+        pattern: Vec<String>,
+        /// Filter by agent name (e.g. claude, codex, gemini)
+        #[arg(long)]
+        agent: Option<String>,
+        /// Maximum number of sessions to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        /// Matching lines shown per session
+        #[arg(long, default_value = "3")]
+        hits: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export a session transcript as Markdown, HTML or JSON
+    Export {
+        /// Session id (or unique prefix), or a query picking the best match
+        #[arg(required = true)]
+        target: Vec<String>,
+        /// Output format: md, html, json
+        #[arg(long, default_value = "md")]
+        format: String,
+        /// Write to this file (or into this directory) instead of stdout
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
     /// Show session statistics
     Stats {
         /// Output as JSON
@@ -79,6 +135,300 @@ enum Commands {
         #[arg(long, default_value = "5")]
         interval: u64,
     },
+    /// Serve the session index as JSON-RPC / HTTP over a Unix socket or a
+    /// local port
+    Serve {
+        /// Unix socket to listen on [default: $XDG_RUNTIME_DIR/agf.sock]
+        #[arg(long)]
+        socket: Option<std::path::PathBuf>,
+        /// Listen on 127.0.0.1:<PORT> instead of a Unix socket (read-only:
+        /// no `delete`)
+        #[arg(long, conflicts_with = "socket")]
+        port: Option<u16>,
+        /// Refresh interval in seconds
+        #[arg(long, default_value = "5")]
+        interval: u64,
+    },
+    /// Run a Model Context Protocol server on stdio, so agents can search
+    /// each other's past sessions
+    Mcp,
+    /// Delete sessions by id, query or filter (their data goes to the trash)
+    Delete {
+        /// Session ids (or unique prefixes); with --query, a query
+        targets: Vec<String>,
+        /// Treat the targets as a query and delete every session it
+        /// matches: qualifiers plus words the name, path, branch or
+        /// summaries contain
+        #[arg(long)]
+        query: bool,
+        /// Only sessions of this agent, by its full name (e.g. claude, codex,
+        /// kiro-cli)
+        #[arg(long)]
+        agent: Option<String>,
+        /// Only sessions last active before this (e.g. 30d, 2026-01-01)
+        #[arg(long)]
+        before: Option<String>,
+        /// Show what would be removed on disk and stop
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't ask for confirmation
+        #[arg(long, short)]
+        yes: bool,
+        /// Print what was removed as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete sessions by retention policy (they go to the trash)
+    Prune {
+        /// Sessions last active longer ago than this (e.g. 90d, 2026-01-01)
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Keep the newest N sessions of each project, prune the rest
+        #[arg(long, value_name = "N", alias = "keep-last-per-project")]
+        keep_last: Option<usize>,
+        /// Sessions whose project directory no longer exists
+        #[arg(long)]
+        project_gone: bool,
+        /// Sessions taking more than this on disk (e.g. 50M, 1G)
+        #[arg(long, value_name = "SIZE")]
+        larger_than: Option<String>,
+        /// Only sessions of this agent, by its full name (e.g. claude, codex,
+        /// kiro-cli)
+        #[arg(long)]
+        agent: Option<String>,
+        /// Report what would be removed, per agent, and stop
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't ask for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// List, restore or purge deleted sessions
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// Show trashed deletions, newest first
+    List,
+    /// Put a deletion back (default: the most recent one)
+    Restore {
+        /// Batch id or unique prefix, as shown by `agf trash list`
+        id: Option<String>,
+    },
+    /// Permanently delete trashed sessions
+    Purge {
+        /// Batch ids or unique prefixes
+        ids: Vec<String>,
+        /// Purge batches deleted longer ago than this (e.g. 7d, 2w)
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Empty the whole trash
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+/// Sessions named by `targets`: each an id or unique id prefix, or with
+/// `query` all together a query whose every match is taken (substrings and
+/// qualifiers, never fuzzy). No targets means all of `sessions`. Fails on
+/// the first id that names no session, or more than one.
+fn select_sessions<'a>(
+    sessions: &'a [model::Session],
+    targets: &[String],
+    query: bool,
+) -> Result<Vec<&'a model::Session>, String> {
+    if targets.is_empty() {
+        return Ok(sessions.iter().collect());
+    }
+    if query {
+        let parsed = parse_query_or_exit(&targets.join(" "));
+        let pinned = settings::Settings::load().pinned_sessions;
+        return Ok(parsed
+            .select(sessions, &pinned)
+            .into_iter()
+            .map(|i| &sessions[i])
+            .collect());
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut chosen = Vec::new();
+    for t in targets {
+        let session = match sessions.iter().find(|s| s.session_id == *t) {
+            Some(s) => s,
+            None => {
+                let mut prefixed = sessions
+                    .iter()
+                    .filter(|s| s.session_id.starts_with(t.as_str()));
+                match (prefixed.next(), prefixed.next()) {
+                    (Some(s), None) => s,
+                    (None, _) => return Err(format!("no session '{t}'")),
+                    (Some(_), Some(_)) => return Err(format!("'{t}' matches several sessions")),
+                }
+            }
+        };
+        if seen.insert(std::ptr::from_ref(session)) {
+            chosen.push(session);
+        }
+    }
+    Ok(chosen)
+}
+
+/// Delete `sessions` as one trash batch after showing what goes. Returns the
+/// exit code: non-zero when any session failed or the user declined.
+fn run_delete(sessions: &[&model::Session], dry_run: bool, yes: bool, json: bool) -> i32 {
+    let plans = delete::plan_sessions(sessions);
+    if !json {
+        for s in sessions {
+            println!("{} {} ({})", s.agent, s.session_id, s.project_name);
+        }
+        print_impact(&delete::Impact::of(&plans));
+    }
+    if dry_run {
+        if json {
+            print_delete_json(sessions, &plans, &[], None, true);
+        }
+        return 0;
+    }
+    let prompt = format!("Move {} session(s) to the trash?", sessions.len());
+    if !yes && !prune::confirm(&prompt) {
+        return 1;
+    }
+
+    let (batch, failed) = match delete::delete_sessions(sessions) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("agf: delete failed: {e}");
+            return 1;
+        }
+    };
+    let failed: Vec<(&model::Session, String)> = failed
+        .into_iter()
+        .map(|(s, e)| (s, e.to_string()))
+        .collect();
+    let is_failed = |s: &model::Session| {
+        failed
+            .iter()
+            .any(|(f, _)| f.agent == s.agent && f.session_id == s.session_id)
+    };
+    let deleted: Vec<&model::Session> =
+        sessions.iter().copied().filter(|s| !is_failed(s)).collect();
+    let removed = delete::moved(&deleted, batch.as_ref());
+
+    if json {
+        print_delete_json(
+            &deleted,
+            &removed,
+            &failed,
+            batch.as_ref().map(|b| b.id.as_str()),
+            false,
+        );
+    } else {
+        for (s, e) in &failed {
+            eprintln!("agf: could not delete {}: {e}", s.session_id);
+        }
+        eprintln!("Deleted {} session(s).", deleted.len());
+        if let Some(batch) = &batch {
+            eprintln!("Undo with `agf trash restore {}`.", batch.id);
+        }
+    }
+    i32::from(!failed.is_empty())
+}
+
+/// `agf delete --json`: per session, what was (or would be) removed.
+fn print_delete_json(
+    sessions: &[&model::Session],
+    plans: &[trash::TrashedSession],
+    failed: &[(&model::Session, String)],
+    batch: Option<&str>,
+    dry_run: bool,
+) {
+    let out = delete::report_json(sessions, plans, failed, batch, dry_run);
+    if let Ok(json) = serde_json::to_string_pretty(&out) {
+        println!("{json}");
+    }
+}
+
+/// Print a deletion plan: what is touched on disk and the space freed.
+fn print_impact(impact: &delete::Impact) {
+    let lines = impact.lines();
+    if lines.is_empty() {
+        println!("  nothing on disk to remove");
+        return;
+    }
+    for line in lines {
+        println!("  {line}");
+    }
+    println!(
+        "{} session(s), frees {}",
+        impact.sessions,
+        prune::format_bytes(impact.bytes)
+    );
+}
+
+fn run_trash(action: TrashAction) -> anyhow::Result<()> {
+    match action {
+        TrashAction::List => trash::print_list(&trash::list()),
+        TrashAction::Restore { id } => {
+            let id = match id {
+                Some(id) => id,
+                None => match trash::list().first() {
+                    Some(latest) => latest.id.clone(),
+                    None => {
+                        eprintln!("Trash is empty.");
+                        std::process::exit(1);
+                    }
+                },
+            };
+            let restored = trash::restore(&id)?;
+            for s in &restored.sessions {
+                eprintln!(
+                    "Restored {} {} ({})",
+                    s.agent_name(),
+                    s.session_id,
+                    s.project_name
+                );
+            }
+        }
+        TrashAction::Purge {
+            ids,
+            older_than,
+            all,
+        } => {
+            let mut targets = ids;
+            if all || older_than.is_some() {
+                let cutoff = match older_than.as_deref() {
+                    Some(age) => match query::parse_time(age) {
+                        Some(t) => t,
+                        None => {
+                            eprintln!("agf: invalid age '{age}' (e.g. 7d, 2w, 2026-01-01)");
+                            std::process::exit(2);
+                        }
+                    },
+                    None => i64::MAX,
+                };
+                targets.extend(
+                    trash::list()
+                        .into_iter()
+                        .filter(|m| m.created < cutoff)
+                        .map(|m| m.id),
+                );
+            } else if targets.is_empty() {
+                eprintln!("agf: name batches to purge, or pass --older-than or --all");
+                std::process::exit(2);
+            }
+            targets.sort();
+            targets.dedup();
+            for id in &targets {
+                trash::purge(id)?;
+            }
+            eprintln!("Purged {} batch(es).", targets.len());
+        }
+    }
+    Ok(())
 }
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -107,18 +457,28 @@ fn main() -> anyhow::Result<()> {
             agent,
             list: list_count,
             mode,
+            fork,
+            force,
         }) => {
             let query = query.join(" ");
-            let mut sessions = scanner::scan_all();
-            if let Some(ref agent_name) = agent {
-                sessions = list::filter_by_agent(sessions, agent_name);
-            }
-            let mut fuzzy = fuzzy::FuzzyMatcher::new();
+            let parsed = parse_query_or_exit(&query);
+            let sessions = cache::load_sessions(&cache::SessionFilter::for_agent(agent.as_deref()));
+            let pinned = settings::Settings::load().pinned_sessions;
             let all_indices: Vec<usize> = (0..sessions.len()).collect();
-            let results = fuzzy.filter(&sessions, &all_indices, &query, 5, false);
+            let mut all_indices = parsed.filter_indices(&sessions, &all_indices, &pinned);
+            // No query: the latest session of the project agf runs in.
+            let here = query.trim().is_empty().then(here::Here::detect).flatten();
+            if let Some(here) = &here {
+                all_indices.retain(|&i| here.contains(&sessions[i]));
+            }
+            let mut fuzzy = fuzzy::FuzzyMatcher::new().with_frecency(frecency::Frecency::load());
+            let results = fuzzy.filter(&sessions, &all_indices, &parsed.text, 5, false);
 
             if results.is_empty() {
-                eprintln!("No session matching '{query}'");
+                match &here {
+                    Some(_) => eprintln!("No session for this directory or its repository"),
+                    None => eprintln!("No session matching '{query}'"),
+                }
                 std::process::exit(1);
             }
 
@@ -148,38 +508,101 @@ fn main() -> anyhow::Result<()> {
             // Build resume command with optional mode flags
             let flags = mode
                 .as_deref()
-                .and_then(|m| {
-                    chosen
-                        .agent
-                        .resume_mode_options()
-                        .iter()
-                        .find(|(label, _)| label.to_lowercase().contains(&m.to_lowercase()))
-                        .map(|(_, f)| *f)
-                })
+                .and_then(|m| chosen.agent.mode_flags(m))
                 .unwrap_or("");
 
-            let cmd = action::resume_with_flags(chosen, flags);
-            return deliver_command(&cmd);
+            let mut cmd = action::resume_with_flags(chosen, flags);
+            if !force {
+                let live = live::Live::detect(&sessions);
+                if let Some(held) = action::resume_guard(chosen, flags, &live) {
+                    match resolve_held(chosen, held, fork) {
+                        Some(other) => cmd = other,
+                        None => std::process::exit(1),
+                    }
+                }
+            }
+            frecency::record(chosen);
+            return deliver_command(&action::prepare_resume(chosen, cmd));
         }
         Some(Commands::List {
+            query,
             agent,
             limit,
             format,
         }) => {
-            let mut sessions = scanner::scan_all();
-            if let Some(ref agent_name) = agent {
-                sessions = list::filter_by_agent(sessions, agent_name);
+            let parsed = parse_query_or_exit(&query.join(" "));
+            let mut filter = cache::SessionFilter::for_agent(agent.as_deref());
+            if !parsed.has_filters() && parsed.text.is_empty() {
+                filter.limit = Some(limit);
+            }
+            let mut sessions = cache::load_sessions(&filter);
+            let live = live::Live::detect(&sessions);
+            if parsed.has_filters() || !parsed.text.is_empty() {
+                let pinned = settings::Settings::load().pinned_sessions;
+                sessions = parsed
+                    .rank(&sessions, &pinned)
+                    .into_iter()
+                    .map(|i| sessions[i].clone())
+                    .collect();
             }
             sessions.truncate(limit);
             if sessions.is_empty() {
                 eprintln!("No sessions found.");
                 std::process::exit(1);
             }
-            list::list_sessions(&sessions, list::OutputFormat::parse(&format));
+            list::list_sessions(&sessions, &live, list::OutputFormat::parse(&format));
+            return Ok(());
+        }
+        Some(Commands::Grep {
+            pattern,
+            agent,
+            limit,
+            hits,
+            json,
+        }) => {
+            let pattern = pattern.join(" ");
+            let sessions = cache::load_sessions(&cache::SessionFilter::for_agent(agent.as_deref()));
+            let mut matches = search::search(&sessions, &pattern, hits);
+            matches.truncate(limit);
+            if matches.is_empty() {
+                eprintln!("No session mentions '{pattern}'");
+                std::process::exit(1);
+            }
+            search::print_matches(&sessions, &matches, json);
+            return Ok(());
+        }
+        Some(Commands::Export {
+            target,
+            format,
+            output,
+        }) => {
+            let Some(format) = export::ExportFormat::parse(&format) else {
+                eprintln!("agf: unknown export format '{format}' (expected md, html or json)");
+                std::process::exit(2);
+            };
+            let target = target.join(" ");
+            let sessions = cache::load_sessions(&cache::SessionFilter::default());
+            let Some(session) = resolve_session(&sessions, &target) else {
+                eprintln!("No session matching '{target}'");
+                std::process::exit(1);
+            };
+            let doc = export::export(session, format)?;
+            match output {
+                Some(path) => {
+                    let path = if path.is_dir() {
+                        export::write_new(&path, session, format, &doc)?
+                    } else {
+                        std::fs::write(&path, doc)?;
+                        path
+                    };
+                    eprintln!("Exported {} to {}", session.session_id, path.display());
+                }
+                None => print!("{doc}"),
+            }
             return Ok(());
         }
         Some(Commands::Stats { json }) => {
-            let sessions = scanner::scan_all();
+            let sessions = cache::load_sessions(&cache::SessionFilter::default());
             stats::print_stats(&sessions, json);
             return Ok(());
         }
@@ -187,6 +610,97 @@ fn main() -> anyhow::Result<()> {
             watch::run_watch(interval)?;
             return Ok(());
         }
+        Some(Commands::Serve {
+            socket,
+            port,
+            interval,
+        }) => {
+            let endpoint = match port {
+                Some(port) => serve::Endpoint::Port(port),
+                None => serve::Endpoint::Socket(socket.unwrap_or_else(serve::default_socket)),
+            };
+            serve::run(endpoint, interval)?;
+            return Ok(());
+        }
+        Some(Commands::Mcp) => {
+            mcp::run()?;
+            return Ok(());
+        }
+        Some(Commands::Delete {
+            targets,
+            query,
+            agent,
+            before,
+            dry_run,
+            yes,
+            json,
+        }) => {
+            if targets.is_empty() && agent.is_none() && before.is_none() {
+                eprintln!("agf: name sessions to delete, or pass a query, --agent or --before");
+                std::process::exit(2);
+            }
+            let before = before.as_deref().map(|age| {
+                query::parse_time(age).unwrap_or_else(|| {
+                    eprintln!("agf: invalid time '{age}' (e.g. 30d, 2w, 2026-01-01)");
+                    std::process::exit(2);
+                })
+            });
+            let filter =
+                cache::SessionFilter::for_agent_named(agent.as_deref()).unwrap_or_else(|e| {
+                    eprintln!("agf: {e}");
+                    std::process::exit(2);
+                });
+            let sessions = cache::load_sessions(&filter);
+            let mut chosen = select_sessions(&sessions, &targets, query).unwrap_or_else(|e| {
+                eprintln!("agf: {e}");
+                std::process::exit(1);
+            });
+            if let Some(before) = before {
+                chosen.retain(|s| s.timestamp < before);
+            }
+            if chosen.is_empty() {
+                eprintln!("No sessions to delete.");
+                std::process::exit(1);
+            }
+            std::process::exit(run_delete(&chosen, dry_run, yes, json));
+        }
+        Some(Commands::Prune {
+            older_than,
+            keep_last,
+            project_gone,
+            larger_than,
+            agent,
+            dry_run,
+            yes,
+        }) => {
+            let policy = prune::Policy {
+                older_than: older_than.as_deref().map(|age| {
+                    query::parse_time(age).unwrap_or_else(|| {
+                        eprintln!("agf: invalid age '{age}' (e.g. 90d, 2w, 2026-01-01)");
+                        std::process::exit(2);
+                    })
+                }),
+                keep_last,
+                project_gone,
+                larger_than: larger_than.as_deref().map(|size| {
+                    prune::parse_size(size).unwrap_or_else(|| {
+                        eprintln!("agf: invalid size '{size}' (e.g. 500K, 50M, 1G)");
+                        std::process::exit(2);
+                    })
+                }),
+            };
+            if policy.is_empty() {
+                eprintln!(
+                    "agf: give at least one of --older-than, --keep-last, --project-gone, --larger-than"
+                );
+                std::process::exit(2);
+            }
+            std::process::exit(prune::run(&policy, agent.as_deref(), dry_run, yes));
+        }
+        Some(Commands::Trash { action }) => {
+            run_trash(action)?;
+            return Ok(());
+        }
         None => {}
     }
 
@@ -249,17 +763,14 @@ fn main() -> anyhow::Result<()> {
             app.sort_mode = match sort_by.as_str() {
                 "name" => model::SortMode::Name,
                 "agent" => model::SortMode::Agent,
+                "frecency" => model::SortMode::Frecency,
                 _ => model::SortMode::Time,
             };
             app.apply_sort();
         }
-        let result = app.run()?;
-        // Persist whatever sessions accumulated during the TUI lifetime so
-        // the next launch reflects all scans that completed before exit.
-        // Agents still scanning at exit keep their prior cache entry so we
-        // don't accidentally persist "empty + fresh-mtime" for them.
-        cache::write_cache(&app.sessions, &app.scanning_agents);
-        result
+        // Background scans store their own results as they finish, so
+        // there is nothing left to persist here.
+        app.run()?
     };
 
     if let Some(cmd) = cmd_opt {
@@ -269,6 +780,34 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Pick the session a CLI argument refers to: an exact session id, then a
+/// unique id prefix, then the best match for it as a query.
+fn resolve_session<'a>(sessions: &'a [model::Session], target: &str) -> Option<&'a model::Session> {
+    if let Some(s) = sessions.iter().find(|s| s.session_id == target) {
+        return Some(s);
+    }
+    let mut prefixed = sessions.iter().filter(|s| s.session_id.starts_with(target));
+    if let (Some(s), None) = (prefixed.next(), prefixed.next()) {
+        return Some(s);
+    }
+    let parsed = parse_query_or_exit(target);
+    let pinned = settings::Settings::load().pinned_sessions;
+    parsed
+        .rank(sessions, &pinned)
+        .first()
+        .map(|&i| &sessions[i])
+}
+
+/// Parse a CLI query, exiting with an error message if a qualifier is invalid.
+fn parse_query_or_exit(input: &str) -> query::Query {
+    let parsed = query::Query::parse(input);
+    if let Some(err) = parsed.error() {
+        eprintln!("agf: {err}");
+        std::process::exit(2);
+    }
+    parsed
+}
+
 /// RAII guard that enters the alternate screen and hides the cursor on
 /// construction, and restores both on drop. Uses raw ANSI escape codes to
 /// avoid taking a direct dependency on crossterm.
@@ -300,6 +839,36 @@ impl Drop for AltScreenGuard {
     }
 }
 
+/// Decide what to do about resuming a session a running agent holds:
+/// fork it when asked (or chosen at the prompt), or give up. Returns the
+/// command to run instead.
+fn resolve_held(session: &model::Session, held: action::Held, fork: bool) -> Option<String> {
+    eprintln!(
+        "agf: {} session {} is already running (pid {}); resuming it again would interleave writes",
+        session.agent, session.session_id, held.pid
+    );
+    let Some(fork_cmd) = held.fork else {
+        if fork {
+            eprintln!("agf: {} can't fork a session", session.agent);
+        } else {
+            eprintln!("agf: pass --force to resume it anyway");
+        }
+        return None;
+    };
+    if fork {
+        return Some(fork_cmd);
+    }
+    if !std::io::stdin().is_terminal() {
+        eprintln!("agf: pass --fork to continue in a fork, or --force to resume it anyway");
+        return None;
+    }
+    eprint!("Fork it into a new session instead? [y/N] ");
+    let mut input = String::new();
+    let yes =
+        std::io::stdin().read_line(&mut input).is_ok() && input.trim().eq_ignore_ascii_case("y");
+    yes.then_some(fork_cmd)
+}
+
 /// Deliver a generated shell command to the parent context.
 ///
 /// Priority:
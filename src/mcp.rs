@@ -0,0 +1,272 @@
+use std::io::{self, BufRead, Write};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::serve::{self, CallError, Index};
+use crate::transcript::{self, Message};
+
+/// Protocol revision answered when the client doesn't ask for one.
+const PROTOCOL_VERSION: &str = "2025-06-18";
+/// How often the index is rescanned while an agent keeps the server open.
+const REFRESH: Duration = Duration::from_secs(30);
+
+/// Run a Model Context Protocol server on stdin/stdout until the client
+/// closes stdin. Only JSON-RPC goes to stdout; diagnostics go to stderr.
+pub fn run() -> anyhow::Result<()> {
+    let index = serve::start(REFRESH);
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = handle(&index, &line) {
+            writeln!(stdout, "{reply}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Answer one message; notifications get no reply.
+fn handle(index: &RwLock<Index>, line: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(serve::rpc_error(
+                Value::Null,
+                &CallError::new(serve::PARSE_ERROR, e.to_string()),
+            ))
+        }
+    };
+    let id = request.get("id").cloned()?;
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let result = match request.get("method").and_then(Value::as_str) {
+        Some("initialize") => Ok(json!({
+            "protocolVersion": params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "agf", "version": env!("CARGO_PKG_VERSION") },
+            "instructions": "Local history of every coding agent on this machine \
+                (Claude Code, Codex, Gemini, …). Search it before redoing work \
+                that may already have been tried.",
+        })),
+        Some("ping") => Ok(json!({})),
+        Some("tools/list") => Ok(json!({ "tools": tools() })),
+        Some("tools/call") => call_tool(index, &params),
+        Some(method) => Err(CallError::new(
+            serve::METHOD_NOT_FOUND,
+            format!("unknown method '{method}'"),
+        )),
+        None => Err(CallError::new(serve::INVALID_REQUEST, "missing method")),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => serve::rpc_error(id, &e),
+    })
+}
+
+fn tools() -> Value {
+    let session_id = json!({
+        "type": "string",
+        "description": "Session id, or a prefix only one session id starts with",
+    });
+    let project = json!({
+        "type": "string",
+        "description": "Project directory (sessions in it or below it) or project name",
+    });
+    let agent = json!({
+        "type": "string",
+        "description": "Only this agent's sessions, e.g. claude, codex, gemini",
+    });
+    json!([
+        {
+            "name": "search_sessions",
+            "description": "Full-text search over the transcripts of past agent sessions. \
+                Returns matching sessions, newest first, with snippets around each hit.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Text to look for (case-insensitive)" },
+                    "project": project,
+                    "agent": agent,
+                    "limit": { "type": "integer", "description": "Most sessions returned (default 10)" },
+                },
+                "required": ["query"],
+            },
+        },
+        {
+            "name": "list_recent_sessions",
+            "description": "Most recent agent sessions, newest first, with their first \
+                prompts, branch and token usage.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": project,
+                    "agent": agent,
+                    "limit": { "type": "integer", "description": "Most sessions returned (default 10)" },
+                },
+            },
+        },
+        {
+            "name": "get_session_transcript",
+            "description": "Conversation of one session as Markdown. Condensed by default: \
+                tool output dropped, long turns shortened, oldest turns cut first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": session_id,
+                    "full": { "type": "boolean", "description": "Whole transcript, tool calls included" },
+                },
+                "required": ["session_id"],
+            },
+        },
+        {
+            "name": "get_recap",
+            "description": "What a session was about: its recap where the agent wrote one, \
+                and its prompts.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "session_id": session_id },
+                "required": ["session_id"],
+            },
+        },
+    ])
+}
+
+/// `tools/call`. Failures of the tool itself come back as an `isError`
+/// result the model can read, not as a protocol error.
+fn call_tool(index: &RwLock<Index>, params: &Value) -> Result<Value, CallError> {
+    let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+    let args = params.get("arguments").cloned().unwrap_or(Value::Null);
+    let limit = args.get("limit").cloned().unwrap_or(json!(10));
+    let output = match name {
+        "search_sessions" => {
+            let query = args.get("query").cloned().unwrap_or(Value::Null);
+            let params = json!({
+                "pattern": query,
+                "project": args.get("project"),
+                "agent": args.get("agent"),
+                "limit": limit,
+            });
+            serve::call(index, "search", &params).map(|v| pretty(&v))
+        }
+        "list_recent_sessions" => {
+            let params = json!({
+                "project": args.get("project"),
+                "agent": args.get("agent"),
+                "limit": limit,
+            });
+            serve::call(index, "list", &params).map(|v| pretty(&v))
+        }
+        "get_session_transcript" => session_transcript(&serve::read(index), &args),
+        "get_recap" => recap(&serve::read(index), &args),
+        _ => {
+            return Err(CallError::new(
+                serve::INVALID_PARAMS,
+                format!("unknown tool '{name}'"),
+            ))
+        }
+    };
+    let (text, is_error) = match output {
+        Ok(text) => (text, false),
+        Err(e) => (e.message, true),
+    };
+    Ok(json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    }))
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+fn session_transcript(index: &Index, args: &Value) -> Result<String, CallError> {
+    let session = serve::session(index, args)?;
+    let messages: Vec<Message> = transcript::open(session)
+        .map_err(|e| CallError::new(serve::FAILED, e.to_string()))?
+        .collect();
+    let full = args.get("full").and_then(Value::as_bool).unwrap_or(false);
+    Ok(if full {
+        crate::export::render(session, &messages, crate::export::ExportFormat::Markdown)
+    } else {
+        crate::handoff::context(session, &messages)
+    })
+}
+
+fn recap(index: &Index, args: &Value) -> Result<String, CallError> {
+    let session = serve::session(index, args)?;
+    Ok(pretty(&json!({
+        "agent": session.agent.to_string(),
+        "session_id": session.session_id,
+        "project_path": session.project_path,
+        "timestamp": session.timestamp,
+        "time": session.time_display(),
+        "git_branch": session.git_branch,
+        "commit": session.commit,
+        "recap": session.recap,
+        "prompts": session.summaries,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Agent, Session};
+
+    #[test]
+    fn tools_answer_from_the_index() {
+        let index = RwLock::new(Index {
+            sessions: vec![Session {
+                project_name: "api".to_string(),
+                project_path: "/src/api".to_string(),
+                summaries: vec!["retry the flaky upload test".to_string()],
+                ..Session::stub(Agent::Codex, "0199-abcd")
+            }],
+            ..Index::default()
+        });
+
+        let init = r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#;
+        let reply = handle(&index, init).unwrap();
+        assert_eq!(reply["result"]["protocolVersion"], "2025-03-26");
+        assert!(handle(
+            &index,
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#
+        )
+        .is_none());
+
+        let listed = handle(&index, r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).unwrap();
+        assert_eq!(listed["result"]["tools"].as_array().unwrap().len(), 4);
+
+        let call = |name: &str, args: Value| {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": args },
+            });
+            handle(&index, &request.to_string()).unwrap()["result"].clone()
+        };
+        let recent = call("list_recent_sessions", json!({ "project": "/src/api/" }));
+        assert_eq!(recent["isError"], false);
+        assert!(recent["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("0199-abcd"));
+        let elsewhere = call("list_recent_sessions", json!({ "project": "/src/web" }));
+        assert_eq!(elsewhere["content"][0]["text"], "[]");
+
+        let recap = call("get_recap", json!({ "session_id": "0199" }));
+        assert!(recap["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("retry the flaky upload test"));
+        let missing = call("get_recap", json!({ "session_id": "zzz" }));
+        assert_eq!(missing["isError"], true);
+    }
+}
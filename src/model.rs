@@ -1,4 +1,9 @@
 use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::plugin::{self, CustomAgent};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[allow(clippy::enum_variant_names)]
@@ -10,6 +15,8 @@ pub enum Agent {
     Kiro,
     CursorAgent,
     Gemini,
+    /// An agent declared in config.toml (see `plugin::custom_agents`).
+    Custom(&'static CustomAgent),
 }
 
 impl fmt::Display for Agent {
@@ -22,6 +29,7 @@ impl fmt::Display for Agent {
             Agent::Kiro => write!(f, "Kiro"),
             Agent::CursorAgent => write!(f, "Cursor CLI"),
             Agent::Gemini => write!(f, "Gemini"),
+            Agent::Custom(c) => write!(f, "{}", c.name),
         }
     }
 }
@@ -36,10 +44,12 @@ impl Agent {
             Agent::Kiro => (136, 69, 244),       // #8845F4 deep purple (AWS Kiro)
             Agent::CursorAgent => (245, 184, 65), // #F5B841 Cursor brand yellow
             Agent::Gemini => (66, 133, 244),     // #4285F4 Google blue
+            Agent::Custom(c) => c.color,
         }
     }
 
-    pub fn all() -> &'static [Agent] {
+    /// Agents agf knows natively.
+    pub fn builtin() -> &'static [Agent] {
         &[
             Agent::ClaudeCode,
             Agent::Codex,
@@ -51,6 +61,16 @@ impl Agent {
         ]
     }
 
+    /// Built-in agents followed by the custom ones from config.toml.
+    pub fn all() -> &'static [Agent] {
+        static ALL: std::sync::OnceLock<Vec<Agent>> = std::sync::OnceLock::new();
+        ALL.get_or_init(|| {
+            let mut all = Agent::builtin().to_vec();
+            all.extend(plugin::custom_agents().iter().map(Agent::Custom));
+            all
+        })
+    }
+
     /// CLI executable name used for launching and detection.
     pub fn cli_name(&self) -> &'static str {
         match self {
@@ -61,22 +81,64 @@ impl Agent {
             Agent::Kiro => "kiro-cli",
             Agent::CursorAgent => "cursor-agent",
             Agent::Gemini => "gemini",
+            Agent::Custom(c) => &c.cli,
         }
     }
 
+    /// Whether a user-typed agent name (`claude`, `Claude Code`, `cursor`, …)
+    /// refers to this agent. Loose on purpose, for searching; an empty name
+    /// matches nothing. Commands that delete use [`Agent::named`].
+    pub fn matches_name(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        if name.is_empty() {
+            return false;
+        }
+        let display = self.to_string().to_lowercase();
+        self.cli_name() == name || display == name || display.replace(' ', "").contains(&name)
+    }
+
+    /// The agent whose CLI name or display name (any case, spaces optional)
+    /// is exactly `name`. The error lists the valid names.
+    pub fn named(name: &str) -> Result<Agent, String> {
+        let wanted = name.to_lowercase().replace(' ', "");
+        Agent::all()
+            .iter()
+            .copied()
+            .find(|a| {
+                a.cli_name() == wanted || a.to_string().to_lowercase().replace(' ', "") == wanted
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = Agent::all().iter().map(|a| a.cli_name()).collect();
+                format!("unknown agent '{name}' (one of: {})", names.join(", "))
+            })
+    }
+
     /// Shell command to resume the most recent session.
     pub fn resume_cmd(&self, session_id: &str) -> String {
         match self {
             Agent::ClaudeCode => format!("claude --resume '{session_id}'"),
             Agent::Codex => format!("codex resume '{session_id}'"),
             Agent::OpenCode => format!("opencode -s '{session_id}'"),
+            // Neither takes an id: pi opens its session picker and Kiro resumes
+            // the directory's latest conversation. `action` resumes exactly.
             Agent::Pi => "pi --resume".to_string(),
             Agent::Kiro => "kiro-cli chat --resume".to_string(),
             Agent::CursorAgent => format!("cursor-agent --resume '{session_id}'"),
             Agent::Gemini => format!("gemini --resume '{session_id}'"),
+            Agent::Custom(c) => c.resume_cmd(session_id),
         }
     }
 
+    /// Flags of the first mode option whose label contains `mode`
+    /// (case-insensitive), e.g. "full-auto" or "yolo".
+    pub fn mode_flags(&self, mode: &str) -> Option<&'static str> {
+        let mode = mode.to_lowercase();
+        self.resume_mode_options()
+            .iter()
+            .find(|(label, _)| label.to_lowercase().contains(&mode))
+            .map(|(_, flags)| *flags)
+    }
+
     /// Permission/approval mode options for resuming a session with extra flags.
     pub fn resume_mode_options(&self) -> &'static [(&'static str, &'static str)] {
         match self {
@@ -106,6 +168,29 @@ impl Agent {
         }
     }
 
+    /// Flag that makes a resume continue in a copy of the session, leaving
+    /// the original untouched.
+    pub fn fork_flag(&self) -> Option<&'static str> {
+        match self {
+            Agent::ClaudeCode => Some(" --fork-session"),
+            _ => None,
+        }
+    }
+
+    /// How the CLI takes an initial prompt when starting a session: the
+    /// option to put before it (`""` for a positional argument), or `None`
+    /// when agf doesn't know.
+    pub fn prompt_arg(&self) -> Option<&'static str> {
+        match self {
+            Agent::ClaudeCode | Agent::Codex | Agent::Pi | Agent::Kiro | Agent::CursorAgent => {
+                Some("")
+            }
+            Agent::OpenCode => Some("--prompt "),
+            Agent::Gemini => Some("-i "),
+            Agent::Custom(_) => None,
+        }
+    }
+
     /// Shell command to start a new session (base, without flags).
     pub fn new_session_cmd(&self) -> &'static str {
         match self {
@@ -116,6 +201,7 @@ impl Agent {
             Agent::Kiro => "kiro-cli chat",
             Agent::CursorAgent => "cursor-agent",
             Agent::Gemini => "gemini",
+            Agent::Custom(c) => &c.new_session,
         }
     }
 }
@@ -125,6 +211,9 @@ pub enum SortMode {
     Time,
     Name,
     Agent,
+    /// Sessions agf resumed or opened often and lately first, blended with
+    /// recency (see `frecency`).
+    Frecency,
 }
 
 impl SortMode {
@@ -132,7 +221,8 @@ impl SortMode {
         match self {
             SortMode::Time => SortMode::Name,
             SortMode::Name => SortMode::Agent,
-            SortMode::Agent => SortMode::Time,
+            SortMode::Agent => SortMode::Frecency,
+            SortMode::Frecency => SortMode::Time,
         }
     }
 
@@ -141,6 +231,7 @@ impl SortMode {
             SortMode::Time => "time",
             SortMode::Name => "name",
             SortMode::Agent => "agent",
+            SortMode::Frecency => "frecency",
         }
     }
 }
@@ -154,8 +245,60 @@ pub struct Session {
     pub summaries: Vec<String>,
     pub timestamp: i64, // Unix ms
     pub git_branch: Option<String>,
+    pub commit: Option<String>, // HEAD when the session ran, where the agent recorded it
+    pub branch_is_live: bool, // git_branch is what the project has checked out now, not a recorded one
     pub worktree: Option<String>,
     pub recap: Option<String>, // Claude Code away_summary, optionally prefixed with aiTitle
+    pub usage: Vec<ModelUsage>, // token totals per model; empty when the agent doesn't record them
+    pub data_root: Option<PathBuf>, // agent data directory the session was found in (config::data_roots)
+    pub session_file: Option<PathBuf>, // file the transcript is read from, as found by the scan
+}
+
+/// Token counts as billed: `input` excludes cached prompt tokens, which are
+/// split into `cache_read` and `cache_write`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenCounts {
+    #[serde(default)]
+    pub input: u64,
+    #[serde(default)]
+    pub output: u64,
+    #[serde(default)]
+    pub cache_read: u64,
+    #[serde(default)]
+    pub cache_write: u64,
+}
+
+impl TokenCounts {
+    pub fn add(&mut self, other: &TokenCounts) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_read += other.cache_read;
+        self.cache_write += other.cache_write;
+    }
+
+    /// Take back counts added before; saturates at zero.
+    pub fn subtract(&mut self, other: &TokenCounts) {
+        self.input = self.input.saturating_sub(other.input);
+        self.output = self.output.saturating_sub(other.output);
+        self.cache_read = self.cache_read.saturating_sub(other.cache_read);
+        self.cache_write = self.cache_write.saturating_sub(other.cache_write);
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.cache_read + self.cache_write
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+/// Token totals for one model within a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub model: String,
+    #[serde(flatten)]
+    pub tokens: TokenCounts,
 }
 
 impl Session {
@@ -206,6 +349,19 @@ impl Session {
         self.project_path.clone()
     }
 
+    /// Token totals across every model used in the session.
+    pub fn token_totals(&self) -> TokenCounts {
+        let mut total = TokenCounts::default();
+        for u in &self.usage {
+            total.add(&u.tokens);
+        }
+        total
+    }
+
+    pub fn models(&self) -> Vec<&str> {
+        self.usage.iter().map(|u| u.model.as_str()).collect()
+    }
+
     pub fn search_text(&self, max_summaries: usize, include_summaries: bool) -> String {
         let mut text = format!("{} {}", self.project_name, self.project_path);
         if include_summaries {
@@ -226,20 +382,24 @@ impl Session {
 pub enum Action {
     Resume,
     NewSession,
+    Handoff,
     Open,
     Cd,
     Pin,
+    Export,
     Delete,
     Back,
 }
 
 impl Action {
-    pub const MENU: [Action; 6] = [
+    pub const MENU: [Action; 8] = [
         Action::Resume,
         Action::NewSession,
+        Action::Handoff,
         Action::Open,
         Action::Cd,
         Action::Pin,
+        Action::Export,
         Action::Delete,
     ];
 }
@@ -249,11 +409,55 @@ impl fmt::Display for Action {
         match self {
             Action::Resume => write!(f, "Resume Session"),
             Action::NewSession => write!(f, "New Session"),
+            Action::Handoff => write!(f, "Hand Off to…"),
             Action::Open => write!(f, "Open in Editor"),
             Action::Cd => write!(f, "Go to Directory"),
             Action::Pin => write!(f, "Pin Session"),
+            Action::Export => write!(f, "Export Transcript"),
             Action::Delete => write!(f, "Delete Session"),
             Action::Back => write!(f, "← Back"),
         }
     }
 }
+
+#[cfg(test)]
+impl Session {
+    /// A session with only its agent and id set, for tests to fill in with
+    /// `Session { project_path, ..Session::stub(agent, id) }`.
+    pub fn stub(agent: Agent, session_id: &str) -> Self {
+        Session {
+            agent,
+            session_id: session_id.to_string(),
+            project_name: String::new(),
+            project_path: String::new(),
+            summaries: Vec::new(),
+            timestamp: 0,
+            git_branch: None,
+            commit: None,
+            branch_is_live: false,
+            worktree: None,
+            recap: None,
+            usage: Vec::new(),
+            data_root: None,
+            session_file: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_takes_whole_names_only() {
+        assert_eq!(Agent::named("Claude Code"), Ok(Agent::ClaudeCode));
+        assert_eq!(Agent::named("claudecode"), Ok(Agent::ClaudeCode));
+        assert_eq!(Agent::named("kiro-cli"), Ok(Agent::Kiro));
+        for partial in ["c", "", "cursor"] {
+            let err = Agent::named(partial).unwrap_err();
+            assert!(err.contains("claude, codex"), "{err}");
+        }
+        assert!(Agent::CursorAgent.matches_name("cursor"));
+        assert!(!Agent::CursorAgent.matches_name(""));
+    }
+}
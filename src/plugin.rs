@@ -1,7 +1,16 @@
-use std::io;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read};
 use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
-use crate::model::{Agent, Session};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AgfError;
+use crate::model::{Agent, ModelUsage, Session};
+use crate::scanner::declarative::{self, JsonlSpec};
+use crate::transcript::{parse_timestamp, Message, MessageStream, Role};
 
 /// Trait for agent plugins. Each AI agent scanner implements this.
 /// Some methods are reserved for future use (e.g., direct dispatch instead of match on Agent).
@@ -18,13 +27,21 @@ pub trait AgentPlugin: Send + Sync {
     fn resume_mode_options(&self) -> &[(&str, &str)] {
         &[("default", "")]
     }
-    /// Paths to check for mtime-based cache invalidation.
-    fn data_sources(&self) -> Vec<PathBuf>;
+    /// Data roots, each with the paths checked for mtime-based cache
+    /// invalidation. Roots are scanned and cached independently.
+    fn data_sources(&self) -> Vec<DataRoot>;
+}
+
+/// One data directory of an agent and the paths whose mtime marks it stale.
+pub struct DataRoot {
+    /// `None` for custom agents, which locate their own sessions.
+    pub root: Option<PathBuf>,
+    pub sources: Vec<PathBuf>,
 }
 
 /// Return all registered agent plugins.
 pub fn all_plugins() -> Vec<Box<dyn AgentPlugin>> {
-    vec![
+    let mut plugins: Vec<Box<dyn AgentPlugin>> = vec![
         Box::new(PluginAdapter(Agent::ClaudeCode)),
         Box::new(PluginAdapter(Agent::Codex)),
         Box::new(PluginAdapter(Agent::OpenCode)),
@@ -32,7 +49,11 @@ pub fn all_plugins() -> Vec<Box<dyn AgentPlugin>> {
         Box::new(PluginAdapter(Agent::Kiro)),
         Box::new(PluginAdapter(Agent::CursorAgent)),
         Box::new(PluginAdapter(Agent::Gemini)),
-    ]
+    ];
+    for custom in custom_agents() {
+        plugins.push(Box::new(PluginAdapter(Agent::Custom(custom))));
+    }
+    plugins
 }
 
 /// Adapter that bridges the existing Agent enum methods to the AgentPlugin trait.
@@ -53,6 +74,7 @@ impl AgentPlugin for PluginAdapter {
             Agent::Kiro => "Kiro",
             Agent::CursorAgent => "Cursor CLI",
             Agent::Gemini => "Gemini",
+            Agent::Custom(c) => &c.name,
         }
     }
 
@@ -65,20 +87,14 @@ impl AgentPlugin for PluginAdapter {
     }
 
     fn scan(&self) -> Vec<Session> {
-        use crate::scanner;
-        match self.0 {
-            Agent::ClaudeCode => scanner::claude::scan().unwrap_or_default(),
-            Agent::Codex => scanner::codex::scan().unwrap_or_default(),
-            Agent::OpenCode => scanner::opencode::scan().unwrap_or_default(),
-            Agent::Pi => scanner::pi::scan().unwrap_or_default(),
-            Agent::Kiro => scanner::kiro::scan().unwrap_or_default(),
-            Agent::CursorAgent => scanner::cursor_agent::scan().unwrap_or_default(),
-            Agent::Gemini => scanner::gemini::scan().unwrap_or_default(),
-        }
+        self.data_sources()
+            .iter()
+            .flat_map(|d| crate::scanner::scan_root(self.0, d.root.as_deref()).unwrap_or_default())
+            .collect()
     }
 
     fn delete(&self, session: &Session) -> Result<(), io::Error> {
-        crate::delete::delete_session(session)
+        crate::delete::delete_session(session).map(|_| ())
     }
 
     fn resume_cmd(&self, session_id: &str) -> String {
@@ -93,28 +109,530 @@ impl AgentPlugin for PluginAdapter {
         self.0.resume_mode_options()
     }
 
-    fn data_sources(&self) -> Vec<PathBuf> {
-        use crate::config;
-        match self.0 {
-            Agent::ClaudeCode => config::claude_dir()
-                .map(|d| vec![d.join("history.jsonl")])
-                .unwrap_or_default(),
-            Agent::Codex => config::codex_dir().map(|d| vec![d]).unwrap_or_default(),
-            Agent::OpenCode => config::opencode_data_dir()
-                .map(|d| vec![d.join("opencode.db")])
-                .unwrap_or_default(),
-            Agent::Pi => config::pi_sessions_dir()
-                .map(|d| vec![d])
-                .unwrap_or_default(),
-            Agent::Kiro => config::kiro_data_dir()
-                .map(|d| vec![d.join("data.sqlite3")])
-                .unwrap_or_default(),
-            Agent::CursorAgent => config::cursor_dir()
-                .map(|d| vec![d.join("chats"), d.join("projects")])
-                .unwrap_or_default(),
-            Agent::Gemini => config::gemini_dir()
-                .map(|d| vec![d.join("tmp")])
-                .unwrap_or_default(),
+    fn data_sources(&self) -> Vec<DataRoot> {
+        if let Agent::Custom(c) = self.0 {
+            return vec![DataRoot {
+                root: None,
+                sources: c.data_sources.clone(),
+            }];
+        }
+        crate::config::data_roots(self.0)
+            .into_iter()
+            .map(|root| DataRoot {
+                sources: match self.0 {
+                    Agent::ClaudeCode => vec![root.join("history.jsonl")],
+                    Agent::OpenCode => vec![root.join("opencode.db")],
+                    Agent::Pi => vec![root.join("sessions")],
+                    Agent::Kiro => vec![root.join("data.sqlite3")],
+                    Agent::CursorAgent => vec![root.join("chats"), root.join("projects")],
+                    Agent::Gemini => vec![root.join("tmp")],
+                    Agent::Codex | Agent::Custom(_) => vec![root.clone()],
+                },
+                root: Some(root),
+            })
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// External plugins
+// ---------------------------------------------------------------------------
+
+/// A `[[plugins]]` entry in config.toml:
+///
+/// ```toml
+/// [[plugins]]
+/// name = "Acme"                       # shown in the UI; `--agent acme`
+/// command = "agf-acme"                # executable speaking the protocol below
+/// color = "#22c55e"                   # optional
+/// new_session = "acme chat"           # optional; offered under New Session
+/// data_sources = ["~/.acme/sessions"] # optional; rescan only when these change
+/// ```
+///
+/// The executable is called as:
+///
+/// * `<command> scan` — print one JSON object per line for each session
+///   (`session_id`, `project_path`, `timestamp` in Unix ms, and optionally
+///   `project_name`, `summaries`, `git_branch`, `commit`, `worktree`, `usage`);
+/// * `<command> delete <session_id>` — remove the session, exit non-zero on
+///   failure (stderr is shown to the user);
+/// * `<command> resume-cmd <session_id>` — print the shell command that
+///   resumes the session;
+/// * `<command> transcript <session_id>` — optional; print one JSON object
+///   per line with `role` (`user`, `assistant`, `tool`, `tool_result`),
+///   `text`, and optionally `tool` and `timestamp`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PluginConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub new_session: Option<String>,
+    #[serde(default)]
+    pub data_sources: Vec<String>,
+}
+
+/// A configured agent outside the built-in set. Lives for the whole process
+/// (see `custom_agents`) so `Agent::Custom` can borrow it and stay `Copy`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct CustomAgent {
+    pub name: String,
+    /// Stable id used in the session store and search index.
+    pub key: String,
+    pub color: (u8, u8, u8),
+    /// Command to start a new session; empty when not configured.
+    pub new_session: String,
+    pub data_sources: Vec<PathBuf>,
+    /// Executable used for installed detection and `Agent::cli_name`.
+    pub cli: String,
+    pub backend: Backend,
+}
+
+/// Where a custom agent's sessions come from.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// `[[plugins]]`: an executable speaking the subprocess protocol.
+    Command(String),
+    /// `[[agents]]`: JSONL session files described in config.toml.
+    Jsonl(JsonlSpec),
+}
+
+const DEFAULT_CUSTOM_COLOR: (u8, u8, u8) = (156, 163, 175); // gray-400
+
+/// Every custom agent declared in config.toml, loaded once per process:
+/// `[[plugins]]` first, then `[[agents]]`. Entries whose name collides with
+/// a built-in agent or an earlier entry are skipped.
+pub fn custom_agents() -> &'static [CustomAgent] {
+    static AGENTS: OnceLock<Vec<CustomAgent>> = OnceLock::new();
+    AGENTS.get_or_init(|| {
+        let settings = crate::settings::Settings::load();
+        let mut agents: Vec<CustomAgent> = Vec::new();
+        let taken = |agents: &[CustomAgent], name: &str| {
+            let taken = name.is_empty()
+                || Agent::builtin().iter().any(|a| {
+                    a.to_string().eq_ignore_ascii_case(name) || a.cli_name() == name.to_lowercase()
+                })
+                || agents.iter().any(|a| a.name.eq_ignore_ascii_case(name));
+            if taken {
+                eprintln!("[agf] agent '{name}' ignored: name is empty or already in use");
+            }
+            taken
+        };
+        for p in settings.plugins {
+            let name = p.name.trim().to_string();
+            if taken(&agents, &name) {
+                continue;
+            }
+            agents.push(CustomAgent {
+                key: format!("custom:{}", name.to_lowercase()),
+                color: p
+                    .color
+                    .as_deref()
+                    .and_then(parse_hex_color)
+                    .unwrap_or(DEFAULT_CUSTOM_COLOR),
+                new_session: p.new_session.unwrap_or_default(),
+                data_sources: p.data_sources.iter().map(|d| expand_home(d)).collect(),
+                cli: p.command.clone(),
+                backend: Backend::Command(p.command),
+                name,
+            });
         }
+        for a in settings.agents {
+            let name = a.name.trim().to_string();
+            if taken(&agents, &name) {
+                continue;
+            }
+            let spec = JsonlSpec::new(&a);
+            let new_session = a.new_session.unwrap_or_default();
+            let cli = a.cli.unwrap_or_else(|| {
+                let cmd = if new_session.is_empty() {
+                    &a.resume
+                } else {
+                    &new_session
+                };
+                cmd.split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            });
+            agents.push(CustomAgent {
+                key: format!("custom:{}", name.to_lowercase()),
+                color: a
+                    .color
+                    .as_deref()
+                    .and_then(parse_hex_color)
+                    .unwrap_or(DEFAULT_CUSTOM_COLOR),
+                new_session,
+                data_sources: vec![spec.root.clone()],
+                cli,
+                backend: Backend::Jsonl(spec),
+                name,
+            });
+        }
+        agents
+    })
+}
+
+fn parse_hex_color(s: &str) -> Option<(u8, u8, u8)> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// One line of `<command> scan` output.
+#[derive(Deserialize)]
+struct PluginSession {
+    session_id: String,
+    project_path: String,
+    #[serde(default)]
+    project_name: Option<String>,
+    #[serde(default)]
+    timestamp: i64,
+    #[serde(default)]
+    summaries: Vec<String>,
+    #[serde(default)]
+    git_branch: Option<String>,
+    #[serde(default)]
+    commit: Option<String>,
+    #[serde(default)]
+    worktree: Option<String>,
+    #[serde(default)]
+    usage: Vec<ModelUsage>,
+}
+
+/// How long a plugin gets to answer one subcommand before it is killed.
+const PLUGIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Resume commands printed by plugins, by (agent key, session id).
+fn resume_memo() -> &'static Mutex<HashMap<(String, String), String>> {
+    static MEMO: OnceLock<Mutex<HashMap<(String, String), String>>> = OnceLock::new();
+    MEMO.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl CustomAgent {
+    /// Whether the agent's executable can be found.
+    pub fn is_installed(&self) -> bool {
+        let path = expand_home(&self.cli);
+        if path.components().count() > 1 {
+            path.is_file()
+        } else {
+            crate::config::is_executable_on_path(&self.cli)
+        }
+    }
+
+    fn run(&self, args: &[&str]) -> Result<std::process::Output, AgfError> {
+        self.run_within(args, PLUGIN_TIMEOUT)
+    }
+
+    fn run_within(
+        &self,
+        args: &[&str],
+        timeout: Duration,
+    ) -> Result<std::process::Output, AgfError> {
+        let command = match &self.backend {
+            Backend::Command(command) => command,
+            Backend::Jsonl(_) => {
+                return Err(AgfError::Plugin(format!("{} has no command", self.name)))
+            }
+        };
+        let mut child = Command::new(expand_home(command))
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        // Drain both pipes while waiting, so a chatty plugin can't block on
+        // a full pipe and run into the deadline.
+        let drain = |pipe: Option<Box<dyn Read + Send>>| {
+            std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_end(&mut buf);
+                }
+                buf
+            })
+        };
+        let stdout = drain(child.stdout.take().map(|p| Box::new(p) as _));
+        let stderr = drain(child.stderr.take().map(|p| Box::new(p) as _));
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(AgfError::Plugin(format!(
+                    "{} {}: no answer after {}s",
+                    command,
+                    args.first().unwrap_or(&""),
+                    timeout.as_secs()
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        let output = std::process::Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        };
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(AgfError::Plugin(format!(
+                "{} {}: {}",
+                command,
+                args.first().unwrap_or(&""),
+                stderr.trim()
+            )));
+        }
+        Ok(output)
+    }
+
+    pub fn scan(&'static self) -> Result<Vec<Session>, AgfError> {
+        if let Backend::Jsonl(spec) = &self.backend {
+            return declarative::scan(self, spec);
+        }
+        let output = self.run(&["scan"])?;
+        Ok(self.parse_scan(&output.stdout))
+    }
+
+    /// Sessions in `scan` output, newest first. Lines that don't parse or
+    /// lack an id or project are skipped.
+    fn parse_scan(&'static self, stdout: &[u8]) -> Vec<Session> {
+        let mut sessions = Vec::new();
+        for line in stdout.lines().map_while(Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let s: PluginSession = match serde_json::from_str(&line) {
+                Ok(s) => s,
+                Err(e) => {
+                    if std::env::var("AGF_DEBUG").is_ok() {
+                        eprintln!("[agf] {}: bad scan line: {e}", self.name);
+                    }
+                    continue;
+                }
+            };
+            if s.session_id.is_empty() || s.project_path.is_empty() {
+                continue;
+            }
+            let project_name = s.project_name.unwrap_or_else(|| {
+                std::path::Path::new(&s.project_path)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown")
+                    .to_string()
+            });
+            sessions.push(Session {
+                agent: Agent::Custom(self),
+                session_id: s.session_id,
+                project_name,
+                project_path: s.project_path,
+                summaries: s.summaries,
+                timestamp: s.timestamp,
+                git_branch: s.git_branch,
+                commit: s.commit,
+                branch_is_live: false,
+                worktree: s.worktree,
+                recap: None,
+                usage: s.usage,
+                data_root: None,
+                session_file: None,
+            });
+        }
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+        sessions
+    }
+
+    /// Declarative sessions go to the trash; plugins delete their own data
+    /// for good.
+    pub fn delete(
+        &self,
+        session_id: &str,
+        batch: &mut crate::trash::Batch,
+    ) -> Result<(), io::Error> {
+        if let Backend::Jsonl(spec) = &self.backend {
+            return declarative::delete(spec, session_id, batch);
+        }
+        if batch.is_plan() {
+            return Ok(());
+        }
+        self.run(&["delete", session_id])
+            .map(|_| ())
+            .map_err(|e| io::Error::other(e.to_string()))
+    }
+
+    /// Shell command that resumes `session_id`: the configured template, or
+    /// what the plugin prints. Plugin output is memoized, so a session's
+    /// command is asked for once per process.
+    pub fn resume_cmd(&self, session_id: &str) -> String {
+        if let Some(cmd) = self.known_resume_cmd(session_id) {
+            return cmd;
+        }
+        let key = (self.key.clone(), session_id.to_string());
+        let cmd = match self.run(&["resume-cmd", session_id]) {
+            Ok(out) => String::from_utf8_lossy(&out.stdout).trim().to_string(),
+            Err(_) => String::new(),
+        };
+        let cmd = if cmd.is_empty() {
+            format!(
+                "echo 'agf: {} plugin gave no resume command' >&2; false",
+                self.name.replace('\'', "")
+            )
+        } else {
+            cmd
+        };
+        if let Ok(mut m) = resume_memo().lock() {
+            m.insert(key, cmd.clone());
+        }
+        cmd
+    }
+
+    /// The resume command when it is known without running the plugin,
+    /// which can take up to `PLUGIN_TIMEOUT`: for previews that must not
+    /// block.
+    pub fn known_resume_cmd(&self, session_id: &str) -> Option<String> {
+        if let Backend::Jsonl(spec) = &self.backend {
+            return Some(spec.resume_cmd(session_id));
+        }
+        let key = (self.key.clone(), session_id.to_string());
+        resume_memo().lock().ok()?.get(&key).cloned()
+    }
+
+    pub fn transcript(&self, session_id: &str) -> Result<MessageStream, AgfError> {
+        if let Backend::Jsonl(spec) = &self.backend {
+            return declarative::transcript(spec, session_id);
+        }
+        let output = self.run(&["transcript", session_id])?;
+        let messages: Vec<Message> = output
+            .stdout
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+            .filter_map(|v| {
+                let role = match v.get("role")?.as_str()? {
+                    "user" => Role::User,
+                    "assistant" => Role::Assistant,
+                    "tool" => Role::Tool,
+                    "tool_result" => Role::ToolResult,
+                    _ => return None,
+                };
+                Some(Message {
+                    role,
+                    text: v.get("text")?.as_str()?.to_string(),
+                    tool: v.get("tool").and_then(|t| t.as_str()).map(str::to_string),
+                    timestamp: parse_timestamp(v.get("timestamp")),
+                })
+            })
+            .collect();
+        Ok(Box::new(messages.into_iter()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_color_accepts_optional_hash() {
+        assert_eq!(parse_hex_color("#22c55e"), Some((0x22, 0xc5, 0x5e)));
+        assert_eq!(parse_hex_color("FFFFFF"), Some((255, 255, 255)));
+        assert_eq!(parse_hex_color("#fff"), None);
+        assert_eq!(parse_hex_color("#zzzzzz"), None);
+    }
+
+    fn command_agent(name: &str, command: &str) -> &'static CustomAgent {
+        Box::leak(Box::new(CustomAgent {
+            name: name.to_string(),
+            key: format!("custom:{name}"),
+            color: DEFAULT_CUSTOM_COLOR,
+            new_session: String::new(),
+            data_sources: Vec::new(),
+            cli: command.to_string(),
+            backend: Backend::Command(command.to_string()),
+        }))
+    }
+
+    /// A plugin named `name` backed by a shell script in `dir`.
+    #[cfg(unix)]
+    fn plugin(dir: &std::path::Path, name: &str, script: &str) -> &'static CustomAgent {
+        use std::os::unix::fs::PermissionsExt;
+
+        let command = dir.join(name);
+        std::fs::write(&command, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&command, std::fs::Permissions::from_mode(0o755)).unwrap();
+        command_agent(name, &command.to_string_lossy())
+    }
+
+    #[test]
+    fn scan_skips_lines_without_an_id_or_project() {
+        let agent = command_agent("acme", "agf-acme");
+        let out = [
+            r#"{"session_id":"old","project_path":"/src/api","timestamp":5}"#,
+            "not json",
+            "",
+            r#"{"session_id":"","project_path":"/src/api"}"#,
+            r#"{"session_id":"new","project_path":"/src/web","project_name":"Web","timestamp":9,"git_branch":"main"}"#,
+        ]
+        .join("\n");
+        let sessions = agent.parse_scan(out.as_bytes());
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["new", "old"]);
+        assert_eq!(sessions[0].project_name, "Web");
+        assert_eq!(sessions[0].git_branch.as_deref(), Some("main"));
+        assert_eq!(sessions[1].project_name, "api");
+        assert_eq!(sessions[1].agent, Agent::Custom(agent));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_plugins_are_killed_and_deletes_report_failure() {
+        let dir = std::env::temp_dir().join(format!("agf-plugin-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let slow = plugin(&dir, "slow", "sleep 5");
+        let started = Instant::now();
+        let err = slow
+            .run_within(&["resume-cmd", "s1"], Duration::from_millis(200))
+            .unwrap_err();
+        assert!(err.to_string().contains("no answer"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let log = dir.join("deleted");
+        let deleter = plugin(
+            &dir,
+            "deleter",
+            &format!(
+                "echo \"$1 $2\" >> '{}'\n[ \"$2\" = locked ] && echo \"$2 is open\" >&2 && exit 1\nexit 0",
+                log.display()
+            ),
+        );
+        // A dry run leaves the plugin alone.
+        deleter
+            .delete("s1", &mut crate::trash::Batch::plan())
+            .unwrap();
+        assert!(!log.exists());
+        deleter
+            .delete("s1", &mut crate::trash::Batch::new())
+            .unwrap();
+        let err = deleter
+            .delete("locked", &mut crate::trash::Batch::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("locked is open"), "{err}");
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            "delete s1\ndelete locked\n"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+
+use crate::model::Session;
+use crate::trash::TrashedSession;
+
+/// Retention rules for `agf prune`. A session is pruned when it matches
+/// every rule given; pinned sessions are never pruned.
+#[derive(Debug, Default)]
+pub struct Policy {
+    /// Last active before this Unix ms.
+    pub older_than: Option<i64>,
+    /// Not among the newest N sessions of its project.
+    pub keep_last: Option<usize>,
+    /// Project directory no longer exists.
+    pub project_gone: bool,
+    /// Takes more than this many bytes on disk.
+    pub larger_than: Option<u64>,
+}
+
+impl Policy {
+    pub fn is_empty(&self) -> bool {
+        self.older_than.is_none()
+            && self.keep_last.is_none()
+            && !self.project_gone
+            && self.larger_than.is_none()
+    }
+}
+
+/// Indices of the sessions matching the rules that need no disk access
+/// beyond a stat; `larger_than` is applied to the deletion plan afterwards.
+fn select(sessions: &[Session], policy: &Policy, pinned: &[String]) -> Vec<usize> {
+    let mut newer_in_project: HashMap<&str, usize> = HashMap::new();
+    let mut order: Vec<usize> = (0..sessions.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sessions[i].timestamp));
+
+    let mut picked: Vec<usize> = order
+        .into_iter()
+        .filter(|&i| {
+            let s = &sessions[i];
+            let rank = newer_in_project.entry(&s.project_path).or_insert(0);
+            let kept = policy.keep_last.is_some_and(|n| *rank < n);
+            *rank += 1;
+            !kept
+                && !pinned.contains(&s.session_id)
+                && policy.older_than.is_none_or(|t| s.timestamp < t)
+                && (!policy.project_gone || !Path::new(&s.project_path).is_dir())
+        })
+        .collect();
+    picked.sort_unstable();
+    picked
+}
+
+/// Sessions to prune with what deleting each would remove.
+pub fn candidates<'a>(
+    sessions: &'a [Session],
+    policy: &Policy,
+    pinned: &[String],
+) -> Vec<(&'a Session, TrashedSession)> {
+    let picked: Vec<&Session> = select(sessions, policy, pinned)
+        .into_iter()
+        .map(|i| &sessions[i])
+        .collect();
+    let plans = crate::delete::plan_sessions(&picked);
+    picked
+        .into_iter()
+        .zip(plans)
+        .filter(|(_, plan)| policy.larger_than.is_none_or(|n| plan.bytes > n))
+        .collect()
+}
+
+/// Print what pruning `targets` removes: one line per session, then the
+/// sessions and bytes reclaimed per agent.
+pub fn print_report(targets: &[(&Session, TrashedSession)]) {
+    let mut out = io::stdout().lock();
+    let mut per_agent: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for (session, plan) in targets {
+        let _ = writeln!(
+            out,
+            "  {:<12} {:<10} {:>9}  {}  {}",
+            session.agent.to_string(),
+            session.date_str(),
+            format_bytes(plan.bytes),
+            session.session_id,
+            session.project_name,
+        );
+        let entry = per_agent.entry(session.agent.to_string()).or_default();
+        entry.0 += 1;
+        entry.1 += plan.bytes;
+    }
+    let _ = writeln!(out);
+    for (agent, (count, bytes)) in &per_agent {
+        let _ = writeln!(
+            out,
+            "  {agent:<12} {count:>5} sessions {:>10}",
+            format_bytes(*bytes)
+        );
+    }
+    let total: u64 = per_agent.values().map(|(_, b)| b).sum();
+    let _ = writeln!(
+        out,
+        "  {:<12} {:>5} sessions {:>10}",
+        "total",
+        targets.len(),
+        format_bytes(total)
+    );
+}
+
+/// Ask on the terminal before deleting. Without a terminal, only `--yes`
+/// lets a destructive command through.
+pub fn confirm(prompt: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        eprintln!("agf: refusing to delete without a terminal; pass --yes");
+        return false;
+    }
+    eprint!("{prompt} [y/N] ");
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).is_ok() && input.trim().eq_ignore_ascii_case("y")
+}
+
+/// Run `agf prune`. Returns the process exit code.
+pub fn run(policy: &Policy, agent: Option<&str>, dry_run: bool, yes: bool) -> i32 {
+    let filter = match crate::cache::SessionFilter::for_agent_named(agent) {
+        Ok(filter) => filter,
+        Err(e) => {
+            eprintln!("agf: {e}");
+            return 2;
+        }
+    };
+    let sessions = crate::cache::load_sessions(&filter);
+    let pinned = crate::settings::Settings::load().pinned_sessions;
+    let targets = candidates(&sessions, policy, &pinned);
+    if targets.is_empty() {
+        eprintln!("Nothing to prune.");
+        return 0;
+    }
+    print_report(&targets);
+    if dry_run {
+        eprintln!("Dry run: nothing was deleted.");
+        return 0;
+    }
+    if !yes && !confirm(&format!("Move {} sessions to the trash?", targets.len())) {
+        return 1;
+    }
+
+    let doomed: Vec<&Session> = targets.iter().map(|(s, _)| *s).collect();
+    match crate::delete::delete_sessions(&doomed) {
+        Ok((batch, failed)) => {
+            for (session, e) in &failed {
+                eprintln!("agf: could not delete {}: {e}", session.session_id);
+            }
+            eprintln!("Pruned {} sessions.", doomed.len() - failed.len());
+            if let Some(batch) = batch {
+                eprintln!("Undo with `agf trash restore {}`.", batch.id);
+            }
+            i32::from(!failed.is_empty())
+        }
+        Err(e) => {
+            eprintln!("agf: prune failed: {e}");
+            1
+        }
+    }
+}
+
+/// Parse a size such as `500K`, `20MB`, `1.5G` or plain bytes.
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (num, unit) = value.split_at(split);
+    let n: f64 = num.parse().ok()?;
+    let scale: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return None,
+    };
+    Some((n * scale as f64) as u64)
+}
+
+/// `1.2 GB`-style size for reports.
+pub fn format_bytes(n: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if n < 1024 {
+        return format!("{n} B");
+    }
+    let mut size = n as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Agent;
+
+    fn session(id: &str, project: &str, age_days: i64) -> Session {
+        Session {
+            project_name: project.to_string(),
+            project_path: std::env::temp_dir()
+                .join(project)
+                .to_string_lossy()
+                .into_owned(),
+            timestamp: chrono::Utc::now().timestamp_millis() - age_days * 86_400_000,
+            ..Session::stub(Agent::Codex, id)
+        }
+    }
+
+    #[test]
+    fn select_keeps_newest_per_project_and_pinned() {
+        let sessions = vec![
+            session("a1", "a", 1),
+            session("a2", "a", 100),
+            session("a3", "a", 200),
+            session("b1", "b", 150),
+            session("a4", "a", 300),
+        ];
+        let policy = Policy {
+            older_than: Some(chrono::Utc::now().timestamp_millis() - 90 * 86_400_000),
+            keep_last: Some(2),
+            ..Default::default()
+        };
+        // a1/a2 are the newest of project a; b1 is the only one of b.
+        let pinned = vec!["a4".to_string()];
+        let picked: Vec<&str> = select(&sessions, &policy, &pinned)
+            .into_iter()
+            .map(|i| sessions[i].session_id.as_str())
+            .collect();
+        assert_eq!(picked, ["a3"]);
+
+        assert_eq!(parse_size("1.5G"), Some(1_610_612_736));
+        assert_eq!(parse_size("20mb"), Some(20 << 20));
+        assert_eq!(format_bytes(1536), "1.5 KB");
+    }
+}
@@ -0,0 +1,326 @@
+use chrono::{Local, NaiveDate, TimeZone};
+
+use crate::model::Session;
+
+/// A search-box query split into structured qualifiers and the free text that
+/// is left for the fuzzy matcher.
+///
+/// ```text
+/// agent:codex branch:feat/x repo:agf since:3d -wip migration
+/// ```
+///
+/// Every qualifier must hold for a session to match; a leading `-` negates
+/// it. Bare `-word` excludes sessions whose name, path, branch or summaries
+/// contain `word`. Unknown `key:value` tokens are kept as free text so paths
+/// and URLs still search normally.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    /// Remaining free text, fed to the fuzzy matcher.
+    pub text: String,
+    filters: Vec<(Filter, bool)>, // (filter, negated)
+    /// Qualifiers whose value could not be parsed (e.g. `since:soon`). They
+    /// are ignored when matching; CLI commands report them as errors.
+    pub invalid: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Agent(String),
+    Branch(String),
+    Path(String),
+    Repo(String),
+    Worktree(String),
+    Since(i64),  // Unix ms, inclusive
+    Before(i64), // Unix ms, exclusive
+    Pinned(bool),
+    Has(Field),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Recap,
+    Branch,
+    Worktree,
+    Summary,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Query::default();
+        let mut text: Vec<String> = Vec::new();
+        for token in split_tokens(input) {
+            let (negated, body) = match token.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest),
+                _ => (false, token.as_str()),
+            };
+            let Some((key, value)) = body.split_once(':') else {
+                if negated {
+                    query
+                        .filters
+                        .push((Filter::Text(body.to_lowercase()), true));
+                } else {
+                    text.push(token);
+                }
+                continue;
+            };
+            let value = value.trim_matches('"');
+            let filter = match key.to_lowercase().as_str() {
+                "agent" => Some(Filter::Agent(value.to_string())),
+                "branch" => Some(Filter::Branch(value.to_lowercase())),
+                "path" => Some(Filter::Path(value.to_lowercase())),
+                "repo" => Some(Filter::Repo(value.to_lowercase())),
+                "worktree" => Some(Filter::Worktree(value.to_lowercase())),
+                "since" => parse_time(value).map(Filter::Since),
+                "before" => parse_time(value).map(Filter::Before),
+                "pinned" => match value.to_lowercase().as_str() {
+                    "" | "yes" | "true" | "1" => Some(Filter::Pinned(true)),
+                    "no" | "false" | "0" => Some(Filter::Pinned(false)),
+                    _ => None,
+                },
+                "has" => match value.to_lowercase().as_str() {
+                    "recap" => Some(Filter::Has(Field::Recap)),
+                    "branch" => Some(Filter::Has(Field::Branch)),
+                    "worktree" => Some(Filter::Has(Field::Worktree)),
+                    "summary" | "summaries" => Some(Filter::Has(Field::Summary)),
+                    _ => None,
+                },
+                _ => {
+                    // Not a qualifier (e.g. `http://…`, `c:\…`): plain text.
+                    if negated {
+                        query
+                            .filters
+                            .push((Filter::Text(body.to_lowercase()), true));
+                    } else {
+                        text.push(token);
+                    }
+                    continue;
+                }
+            };
+            match filter {
+                Some(f) => query.filters.push((f, negated)),
+                None => query.invalid.push(token),
+            }
+        }
+        query.text = text.join(" ");
+        query
+    }
+
+    /// True when the query has qualifiers beyond plain fuzzy text.
+    pub fn has_filters(&self) -> bool {
+        !self.filters.is_empty()
+    }
+
+    /// Whether `session` satisfies every qualifier. The free text is not
+    /// checked here; that is the fuzzy matcher's job.
+    pub fn matches(&self, session: &Session, pinned: &[String]) -> bool {
+        self.filters
+            .iter()
+            .all(|(filter, negated)| filter_matches(filter, session, pinned) != *negated)
+    }
+
+    /// Indices of `sessions` (from `indices`) that satisfy every qualifier.
+    pub fn filter_indices(
+        &self,
+        sessions: &[Session],
+        indices: &[usize],
+        pinned: &[String],
+    ) -> Vec<usize> {
+        indices
+            .iter()
+            .copied()
+            .filter(|&i| self.matches(&sessions[i], pinned))
+            .collect()
+    }
+
+    /// Indices of the sessions matching the qualifiers, best fuzzy match of
+    /// the free text first (ties go to the session agf resumed or opened
+    /// most): how the CLI picks sessions for a query.
+    pub fn rank(&self, sessions: &[Session], pinned: &[String]) -> Vec<usize> {
+        let all: Vec<usize> = (0..sessions.len()).collect();
+        let filtered = self.filter_indices(sessions, &all, pinned);
+        crate::fuzzy::FuzzyMatcher::new()
+            .with_frecency(crate::frecency::Frecency::load())
+            .filter(sessions, &filtered, &self.text, 5, false)
+            .iter()
+            .map(|r| filtered[r.index])
+            .collect()
+    }
+
+    /// Indices of `sessions` that satisfy every qualifier and contain every
+    /// word of the free text (case-insensitive substring). Unlike `rank`
+    /// there is no fuzzy matching, for commands that act on every match.
+    pub fn select(&self, sessions: &[Session], pinned: &[String]) -> Vec<usize> {
+        let words: Vec<Filter> = self
+            .text
+            .split_whitespace()
+            .map(|w| Filter::Text(w.to_lowercase()))
+            .collect();
+        (0..sessions.len())
+            .filter(|&i| {
+                let session = &sessions[i];
+                self.matches(session, pinned)
+                    && words.iter().all(|w| filter_matches(w, session, pinned))
+            })
+            .collect()
+    }
+
+    /// Human-readable error for CLI commands, if any qualifier was invalid.
+    pub fn error(&self) -> Option<String> {
+        if self.invalid.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "invalid qualifier {} (since:/before: take 30m, 3d, 2w, 1mo, 1y or YYYY-MM-DD; \
+             pinned: takes yes/no; has: takes recap, branch, worktree or summary)",
+            self.invalid.join(", ")
+        ))
+    }
+}
+
+fn filter_matches(filter: &Filter, session: &Session, pinned: &[String]) -> bool {
+    let contains = |field: Option<&str>, needle: &str| match field {
+        Some(v) => needle.is_empty() || v.to_lowercase().contains(needle),
+        None => false,
+    };
+    match filter {
+        Filter::Agent(name) => session.agent.matches_name(name),
+        Filter::Branch(b) => contains(session.git_branch.as_deref(), b),
+        Filter::Path(p) => {
+            contains(Some(&session.project_path), p) || contains(Some(&session.display_path()), p)
+        }
+        Filter::Repo(r) => {
+            let (repo, name) = crate::git::identity(session);
+            contains(Some(&repo), r) || contains(Some(&name), r)
+        }
+        Filter::Worktree(w) => contains(session.worktree.as_deref(), w),
+        Filter::Since(ms) => session.timestamp >= *ms,
+        Filter::Before(ms) => session.timestamp < *ms,
+        Filter::Pinned(want) => pinned.contains(&session.session_id) == *want,
+        Filter::Has(Field::Recap) => session.recap.is_some(),
+        Filter::Has(Field::Branch) => session.git_branch.is_some(),
+        Filter::Has(Field::Worktree) => session.worktree.is_some(),
+        Filter::Has(Field::Summary) => !session.summaries.is_empty(),
+        Filter::Text(t) => {
+            contains(Some(&session.project_name), t)
+                || contains(Some(&session.project_path), t)
+                || contains(session.git_branch.as_deref(), t)
+                || session.summaries.iter().any(|s| contains(Some(s), t))
+        }
+    }
+}
+
+/// Split on whitespace, keeping `"double quoted"` runs together so values
+/// like `path:"My Project"` survive.
+fn split_tokens(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for ch in input.chars() {
+        match ch {
+            '"' => {
+                quoted = !quoted;
+                current.push(ch);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Parse a relative age (`30m`, `12h`, `3d`, `2w`, `1mo`, `1y`) into "that
+/// long before now", or an absolute `YYYY-MM-DD` into local midnight, as Unix
+/// milliseconds.
+pub(crate) fn parse_time(value: &str) -> Option<i64> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0)?;
+        return Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|dt| dt.timestamp_millis());
+    }
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (num, unit) = value.split_at(split);
+    let n: i64 = num.parse().ok()?;
+    let secs = match unit {
+        "m" | "min" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 604_800,
+        "mo" => 2_629_800,
+        "y" => 31_557_600,
+        _ => return None,
+    };
+    // `None` rather than overflow for ages like `300000000y`.
+    let ms = n.checked_mul(secs)?.checked_mul(1000)?;
+    chrono::Utc::now().timestamp_millis().checked_sub(ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Agent;
+
+    fn session(agent: Agent, branch: Option<&str>, age_days: i64) -> Session {
+        Session {
+            project_name: "agf".to_string(),
+            project_path: "/src/agf".to_string(),
+            summaries: vec!["add migration for cache".to_string()],
+            timestamp: chrono::Utc::now().timestamp_millis() - age_days * 86_400_000,
+            git_branch: branch.map(str::to_string),
+            ..Session::stub(agent, &format!("{agent:?}-{age_days}"))
+        }
+    }
+
+    #[test]
+    fn parse_splits_qualifiers_from_text() {
+        let q = Query::parse("agent:codex branch:feat/x since:3d migration -wip http://x");
+        assert_eq!(q.text, "migration http://x");
+        assert_eq!(q.filters.len(), 4);
+        assert!(q.invalid.is_empty());
+
+        let q = Query::parse("since:soon has:nothing");
+        assert_eq!(q.invalid, ["since:soon", "has:nothing"]);
+        assert!(parse_time("300000000y").is_none());
+        assert!(parse_time("9999999999999999999d").is_none());
+        assert!(q.error().is_some());
+    }
+
+    #[test]
+    fn matches_applies_every_qualifier_and_negation() {
+        let recent = session(Agent::Codex, Some("feat/x"), 1);
+        let old = session(Agent::Codex, Some("feat/x"), 10);
+        let claude = session(Agent::ClaudeCode, None, 1);
+
+        let q = Query::parse("agent:codex branch:FEAT since:3d");
+        assert!(q.matches(&recent, &[]));
+        assert!(!q.matches(&old, &[]));
+        assert!(!q.matches(&claude, &[]));
+
+        let q = Query::parse("-agent:codex -migration");
+        assert!(!q.matches(&claude, &[]));
+        let q = Query::parse("-agent:codex pinned:");
+        assert!(q.matches(&claude, std::slice::from_ref(&claude.session_id)));
+        assert!(!q.matches(&claude, &[]));
+        assert!(Query::parse("has:branch").matches(&recent, &[]));
+        // Outside a repository, the project stands in for it.
+        assert!(Query::parse("repo:agf").matches(&recent, &[]));
+        assert!(!Query::parse("has:recap").matches(&recent, &[]));
+
+        // `select` wants whole substrings, not fuzzy subsequences.
+        let all = [recent, old, claude];
+        assert_eq!(
+            Query::parse("agent:codex MIGRATION").select(&all, &[]),
+            [0, 1]
+        );
+        assert!(Query::parse("mgrtn").select(&all, &[]).is_empty());
+    }
+}
@@ -1,15 +1,19 @@
 use std::collections::HashMap;
 use std::fs;
 use std::io::BufRead;
+use std::path::PathBuf;
 
-use rayon::prelude::*;
-
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::AgfError;
-use crate::model::{Agent, Session};
-use crate::scanner::read_head_tail;
+use crate::model::{Agent, ModelUsage, Session, TokenCounts};
+use crate::scanner::file_cache::{FileCache, FileStamp};
+use crate::scanner::{jsonl_values, read_head_tail};
+use crate::transcript::{
+    content_text, parse_timestamp, tool_input_preview, Message, MessageStream, Role,
+};
+use crate::usage;
 
 /// Per-file I/O cap for `scan_session_metadata`. Files larger than the sum
 /// fall back to head + tail reads; smaller files are read in full. Sized so
@@ -39,16 +43,33 @@ struct SessionData {
 }
 
 /// Metadata extracted from per-session JSONL files.
+#[derive(Clone, Default, Serialize, Deserialize)]
 struct SessionMeta {
     worktree: Option<String>,
+    #[serde(default)]
+    git_branch: Option<String>, // gitBranch of the latest entry that records one
     recap: Option<String>, // most recent away_summary, optionally prefixed with aiTitle
+    #[serde(skip)]
+    usage: Vec<ModelUsage>,
+    #[serde(skip)]
+    file: Option<PathBuf>,
 }
 
+/// Format version of the per-file metadata cache; bump when
+/// `parse_session_file` or `SessionMeta` changes.
+const META_CACHE_VERSION: u32 = 3;
+/// Format version of the per-file usage cache; bump when `read_usage` or
+/// `UsageScan` changes.
+const USAGE_CACHE_VERSION: u32 = 1;
+
 /// Scan ~/.claude/projects/*/<sessionId>.jsonl to detect worktree sessions
-/// and extract recap (away_summary / aiTitle) metadata.
+/// and extract branch and recap (away_summary / aiTitle) metadata.
 ///
 /// `cwd` in the per-session JSONL is the actual working directory, which for
 /// worktree sessions looks like `<project>/.claude/worktrees/<name>`.
+///
+/// Results are memoized per file (see `FileCache`), so only session logs
+/// that changed since the last scan are read again.
 fn scan_session_metadata(claude_dir: &std::path::Path) -> HashMap<String, SessionMeta> {
     let projects_dir = claude_dir.join("projects");
 
@@ -57,7 +78,7 @@ fn scan_session_metadata(claude_dir: &std::path::Path) -> HashMap<String, Sessio
     };
 
     // Collect all JSONL file paths first, then process in parallel.
-    let mut file_paths: Vec<(String, std::path::PathBuf)> = Vec::new();
+    let mut file_paths: Vec<PathBuf> = Vec::new();
     for proj_entry in proj_entries.flatten() {
         let proj_path = proj_entry.path();
         if !proj_path.is_dir() {
@@ -68,71 +89,216 @@ fn scan_session_metadata(claude_dir: &std::path::Path) -> HashMap<String, Sessio
         };
         for session_file in session_files.flatten() {
             let file_path = session_file.path();
-            if file_path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
-                continue;
-            }
-            if let Some(session_id) = file_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .map(|s| s.to_string())
-            {
-                file_paths.push((session_id, file_path));
+            if file_path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
+                file_paths.push(file_path);
             }
         }
     }
 
-    file_paths
-        .into_par_iter()
-        .filter_map(|(session_id, file_path)| {
-            let ht = read_head_tail(&file_path, HEAD_BYTES, TAIL_BYTES)?;
+    let mut cache = FileCache::load("claude", claude_dir, META_CACHE_VERSION);
+    let parsed = cache.resolve(file_paths.clone(), parse_session_file);
+    cache.save();
 
-            let mut worktree: Option<String> = None;
-            let mut ai_title: Option<String> = None;
+    // Usage needs every line, so it has a cache of its own that reads on
+    // from where the last scan stopped.
+    let mut usage_cache = FileCache::load("claude-usage", claude_dir, USAGE_CACHE_VERSION);
+    let usage = usage_cache.resolve_with(file_paths, read_usage);
+    usage_cache.save();
 
-            // Head slice: scan for worktree (cwd) + aiTitle. First-match
-            // semantics for both, matching the pre-cap behavior.
-            for line in ht.head.lines() {
-                if worktree.is_some() && ai_title.is_some() {
-                    break;
-                }
-                let Ok(val) = serde_json::from_str::<Value>(line) else {
-                    continue;
-                };
-                extract_worktree(&val, &mut worktree);
-                extract_ai_title(&val, &mut ai_title);
-            }
+    let mut metas: HashMap<String, SessionMeta> = parsed
+        .into_iter()
+        .filter_map(|(path, meta)| {
+            let session_id = path.file_stem()?.to_str()?.to_string();
+            // Every session file is kept, for its path, even with nothing
+            // else to tell.
+            let meta = SessionMeta {
+                file: Some(path),
+                ..meta.unwrap_or_default()
+            };
+            Some((session_id, meta))
+        })
+        .collect();
+    for (path, scan) in usage {
+        let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if let Some(meta) = metas.get_mut(session_id) {
+            meta.usage = scan.usage;
+        }
+    }
+    metas
+}
 
-            // Tail slice: scan for the latest away_summary. For small files
-            // (`!truncated`) the head already contains every line, so skip
-            // the redundant tail pass.
-            let mut latest_recap: Option<String> = None;
-            let mut latest_recap_ts: Option<String> = None;
-            let scan_tail = if ht.truncated { &ht.tail } else { &ht.head };
-            for line in scan_tail.lines() {
-                let Ok(val) = serde_json::from_str::<Value>(line) else {
-                    continue;
-                };
-                // Late aiTitle wins on small files; tail-late aiTitle on
-                // truncated files is rare but harmless to capture.
-                extract_ai_title(&val, &mut ai_title);
-                extract_recap(&val, &mut latest_recap, &mut latest_recap_ts);
-            }
+fn parse_session_file(file_path: &std::path::Path) -> Option<SessionMeta> {
+    let ht = read_head_tail(file_path, HEAD_BYTES, TAIL_BYTES)?;
 
-            // Build recap: prepend "recap: " and optionally aiTitle
-            let recap = match (ai_title, latest_recap) {
-                (Some(title), Some(summary)) => Some(format!("recap: {title} — {summary}")),
-                (None, Some(summary)) => Some(format!("recap: {summary}")),
-                (Some(title), None) => Some(title),
-                (None, None) => None,
-            };
+    let mut worktree: Option<String> = None;
+    let mut ai_title: Option<String> = None;
+    let mut git_branch: Option<String> = None;
 
-            if worktree.is_some() || recap.is_some() {
-                Some((session_id, SessionMeta { worktree, recap }))
-            } else {
-                None
-            }
+    // Head slice: scan for worktree (cwd) + aiTitle. First-match
+    // semantics for both, matching the pre-cap behavior.
+    for line in ht.head.lines() {
+        if worktree.is_some() && ai_title.is_some() {
+            break;
+        }
+        let Ok(val) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        extract_worktree(&val, &mut worktree);
+        extract_ai_title(&val, &mut ai_title);
+        extract_git_branch(&val, &mut git_branch);
+    }
+
+    // Tail slice: scan for the latest away_summary and branch. For small files
+    // (`!truncated`) the head already contains every line, so skip
+    // the redundant tail pass.
+    let mut latest_recap: Option<String> = None;
+    let mut latest_recap_ts: Option<String> = None;
+    let scan_tail = if ht.truncated { &ht.tail } else { &ht.head };
+    for line in scan_tail.lines() {
+        let Ok(val) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        // Late aiTitle wins on small files; tail-late aiTitle on
+        // truncated files is rare but harmless to capture.
+        extract_ai_title(&val, &mut ai_title);
+        extract_git_branch(&val, &mut git_branch);
+        extract_recap(&val, &mut latest_recap, &mut latest_recap_ts);
+    }
+
+    // Build recap: prepend "recap: " and optionally aiTitle
+    let recap = match (ai_title, latest_recap) {
+        (Some(title), Some(summary)) => Some(format!("recap: {title} — {summary}")),
+        (None, Some(summary)) => Some(format!("recap: {summary}")),
+        (Some(title), None) => Some(title),
+        (None, None) => None,
+    };
+
+    if worktree.is_some() || git_branch.is_some() || recap.is_some() {
+        Some(SessionMeta {
+            worktree,
+            git_branch,
+            recap,
+            usage: Vec::new(),
+            file: None,
         })
-        .collect()
+    } else {
+        None
+    }
+}
+
+/// Token usage of one session log, as far as it has been read. Logs are
+/// append-only, so a rescan reads on from `offset` instead of from the
+/// start.
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct UsageScan {
+    inode: u64,
+    /// Bytes read so far; always the end of a complete line.
+    offset: u64,
+    usage: Vec<ModelUsage>,
+    /// The latest API messages counted, to replace their repeated lines.
+    recent: Vec<(String, String, TokenCounts)>, // (message id, model, tokens)
+}
+
+/// API messages remembered for deduplication; the repeats of one message
+/// are written back to back.
+const RECENT_MESSAGES: usize = 16;
+
+/// Sum the `message.usage` blocks of assistant entries per model. Claude
+/// Code writes one line per content block, each repeating the usage of the
+/// whole API message, so entries are deduplicated by `message.id` (the last
+/// one wins, as it carries the final output count).
+///
+/// Continues from `previous` when the file is the same one, grown.
+fn read_usage(path: &std::path::Path, previous: Option<&UsageScan>) -> UsageScan {
+    use std::io::{Seek, SeekFrom};
+
+    let Some(stamp) = FileStamp::of(path) else {
+        return UsageScan::default();
+    };
+    let mut scan = previous
+        .filter(|p| p.inode == stamp.inode && p.offset <= stamp.size)
+        .cloned()
+        .unwrap_or_else(|| UsageScan {
+            inode: stamp.inode,
+            ..UsageScan::default()
+        });
+    let Ok(mut file) = fs::File::open(path) else {
+        return scan;
+    };
+    if file.seek(SeekFrom::Start(scan.offset)).is_err() {
+        return scan;
+    }
+    let mut reader = std::io::BufReader::new(file);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let n = match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        // A line still being written is read again next time.
+        if line.last() != Some(&b'\n') {
+            break;
+        }
+        scan.offset += n as u64;
+        if let Some((id, model, tokens)) = line_usage(&line) {
+            scan.count(id, model, tokens);
+        }
+    }
+    scan.usage
+        .sort_by_key(|u| std::cmp::Reverse(u.tokens.total()));
+    scan
+}
+
+impl UsageScan {
+    fn count(&mut self, id: Option<String>, model: String, tokens: TokenCounts) {
+        let Some(id) = id else {
+            usage::accumulate(&mut self.usage, &model, &tokens);
+            return;
+        };
+        if let Some(pos) = self.recent.iter().position(|(seen, _, _)| *seen == id) {
+            let (_, model, tokens) = self.recent.remove(pos);
+            if let Some(u) = self.usage.iter_mut().find(|u| u.model == model) {
+                u.tokens.subtract(&tokens);
+            }
+        }
+        usage::accumulate(&mut self.usage, &model, &tokens);
+        self.usage.retain(|u| !u.tokens.is_empty());
+        self.recent.push((id, model, tokens));
+        if self.recent.len() > RECENT_MESSAGES {
+            self.recent.remove(0);
+        }
+    }
+}
+
+/// Message id, model and billed tokens of an assistant log line.
+fn line_usage(line: &[u8]) -> Option<(Option<String>, String, TokenCounts)> {
+    let line = std::str::from_utf8(line).ok()?;
+    if !line.contains("\"usage\"") {
+        return None;
+    }
+    let val = serde_json::from_str::<Value>(line).ok()?;
+    if val.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+        return None;
+    }
+    let msg = val.get("message")?;
+    let model = msg.get("model").and_then(|m| m.as_str()).unwrap_or("");
+    // Locally generated messages (API errors, interrupts) are not billed.
+    if model.is_empty() || model == "<synthetic>" {
+        return None;
+    }
+    let usage = msg.get("usage")?;
+    let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    let tokens = TokenCounts {
+        input: count("input_tokens"),
+        output: count("output_tokens"),
+        cache_read: count("cache_read_input_tokens"),
+        cache_write: count("cache_creation_input_tokens"),
+    };
+    let id = msg.get("id").and_then(|i| i.as_str()).map(str::to_string);
+    Some((id, model.to_string(), tokens))
 }
 
 fn extract_worktree(val: &Value, worktree: &mut Option<String>) {
@@ -148,6 +314,16 @@ fn extract_worktree(val: &Value, worktree: &mut Option<String>) {
     }
 }
 
+/// Last one wins. Claude Code writes `"HEAD"` for a detached HEAD and an
+/// empty string outside a repository; neither names a branch.
+fn extract_git_branch(val: &Value, git_branch: &mut Option<String>) {
+    if let Some(branch) = val.get("gitBranch").and_then(|b| b.as_str()) {
+        if !branch.is_empty() && branch != "HEAD" {
+            *git_branch = Some(branch.to_string());
+        }
+    }
+}
+
 fn extract_ai_title(val: &Value, ai_title: &mut Option<String>) {
     if val.get("type").and_then(|t| t.as_str()) == Some("ai-title") {
         if let Some(title) = val.get("aiTitle").and_then(|t| t.as_str()) {
@@ -189,31 +365,13 @@ fn extract_recap(
     }
 }
 
-/// Read the current git branch from the project root's `.git/HEAD`.
-/// Returns `None` if the directory is not a git repo or is in detached HEAD state.
-fn read_git_branch(project_path: &str) -> Option<String> {
-    let head_path = std::path::Path::new(project_path).join(".git").join("HEAD");
-    // `.git/HEAD` is a small (~30 byte) plain text file; a direct read is
-    // fast enough that the earlier thread+channel 100 ms timeout was
-    // unnecessary paranoia.
-    let content = fs::read_to_string(&head_path).ok()?;
-    let branch = content.trim().strip_prefix("ref: refs/heads/")?.to_string();
-    if branch.is_empty() {
-        None
-    } else {
-        Some(branch)
-    }
-}
-
-pub fn scan() -> Result<Vec<Session>, AgfError> {
-    let claude_dir = crate::config::claude_dir()?;
+pub fn scan(claude_dir: &std::path::Path) -> Result<Vec<Session>, AgfError> {
     let path = claude_dir.join("history.jsonl");
     if !path.exists() {
         return Ok(Vec::new());
     }
 
-    let session_meta = scan_session_metadata(&claude_dir);
-    let mut branch_cache: HashMap<String, Option<String>> = HashMap::new();
+    let session_meta = scan_session_metadata(claude_dir);
     let mut sessions_map: HashMap<String, SessionData> = HashMap::new();
 
     let file = fs::File::open(&path)?;
@@ -277,16 +435,12 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
             let timestamp = data.timestamp as i64;
 
             // Worktree: detected from per-session JSONL cwd field.
-            // Branch: live current branch from .git/HEAD of the project root.
-            //   - For worktree sessions this shows the root project's branch (e.g. "main"),
-            //     which is displayed in the detail view alongside the worktree name.
-            //   - For regular sessions this shows the project's current branch.
+            // Branch: the gitBranch the session recorded. Sessions without
+            // one are left to `scanner::scan_root`, which falls back to the
+            // live branch of the project root.
             let meta = session_meta.get(&session_id);
             let worktree = meta.and_then(|m| m.worktree.clone());
-            let git_branch = branch_cache
-                .entry(project_path.clone())
-                .or_insert_with(|| read_git_branch(&project_path))
-                .clone();
+            let git_branch = meta.and_then(|m| m.git_branch.clone());
 
             // Sort summaries newest-first
             data.summaries
@@ -294,6 +448,8 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
             let summaries: Vec<String> = data.summaries.into_iter().map(|(_, s)| s).collect();
 
             let recap = meta.and_then(|m| m.recap.clone());
+            let usage = meta.map(|m| m.usage.clone()).unwrap_or_default();
+            let session_file = meta.and_then(|m| m.file.clone());
 
             Some(Session {
                 agent: Agent::ClaudeCode,
@@ -303,8 +459,13 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
                 summaries,
                 timestamp,
                 git_branch,
+                commit: None,
+                branch_is_live: false,
                 worktree,
                 recap,
+                usage,
+                data_root: None,
+                session_file,
             })
         })
         .collect();
@@ -312,3 +473,168 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
     sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
     Ok(sessions)
 }
+
+/// Locate `~/.claude/projects/<encoded-project>/<sessionId>.jsonl`.
+pub(crate) fn session_file(claude_dir: &std::path::Path, session_id: &str) -> Option<PathBuf> {
+    let file_name = format!("{session_id}.jsonl");
+    fs::read_dir(claude_dir.join("projects"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|p| p.is_file())
+}
+
+/// Stream the conversation from the per-session JSONL log.
+pub fn transcript(session: &Session) -> Result<MessageStream, AgfError> {
+    let claude_dir = crate::config::session_root(session)?;
+    let path = session_file(&claude_dir, &session.session_id)
+        .ok_or_else(|| AgfError::SessionNotFound(session.session_id.clone()))?;
+    Ok(Box::new(
+        jsonl_values(&path)?.flat_map(|val| transcript_messages(&val)),
+    ))
+}
+
+/// Convert one Claude log line into transcript entries. A single assistant
+/// line can carry text plus several `tool_use` blocks; user lines carry
+/// either the prompt or `tool_result` blocks.
+fn transcript_messages(val: &Value) -> Vec<Message> {
+    let entry_type = val.get("type").and_then(|t| t.as_str()).unwrap_or("");
+    if !matches!(entry_type, "user" | "assistant")
+        || val.get("isMeta").and_then(|m| m.as_bool()) == Some(true)
+    {
+        return Vec::new();
+    }
+    let Some(content) = val.get("message").and_then(|m| m.get("content")) else {
+        return Vec::new();
+    };
+    let ts = parse_timestamp(val.get("timestamp"));
+    let role = if entry_type == "user" {
+        Role::User
+    } else {
+        Role::Assistant
+    };
+
+    let Some(blocks) = content.as_array() else {
+        let text = content_text(content);
+        return if text.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![Message::new(role, text, ts)]
+        };
+    };
+
+    let mut out = Vec::new();
+    for block in blocks {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") => {
+                let text = block.get("text").and_then(|t| t.as_str()).unwrap_or("");
+                if !text.trim().is_empty() {
+                    out.push(Message::new(role, text, ts));
+                }
+            }
+            Some("tool_use") => {
+                let name = block.get("name").and_then(|n| n.as_str()).unwrap_or("tool");
+                let input = block
+                    .get("input")
+                    .map(tool_input_preview)
+                    .unwrap_or_default();
+                out.push(Message::tool(name, input, ts));
+            }
+            Some("tool_result") => {
+                let text = block.get("content").map(content_text).unwrap_or_default();
+                out.push(Message::tool_result(text, ts));
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_usage_reads_on_from_the_last_scan() {
+        let line = |id: &str, output: u64| {
+            format!(
+                "{}\n",
+                serde_json::json!({
+                    "type": "assistant",
+                    "message": {
+                        "id": id,
+                        "model": "claude-sonnet-4",
+                        "usage": { "input_tokens": 10, "output_tokens": output },
+                    },
+                })
+            )
+        };
+        let path =
+            std::env::temp_dir().join(format!("agf-claude-usage-{}.jsonl", std::process::id()));
+        let mut log = line("m1", 5) + &line("m2", 1);
+        fs::write(&path, &log).unwrap();
+        let first = read_usage(&path, None);
+        assert_eq!(first.usage[0].tokens.output, 6);
+
+        // m2's final line and m3, plus a line still being written.
+        log += &(line("m2", 7) + &line("m3", 2));
+        fs::write(&path, format!("{log}{{\"type\":\"assis")).unwrap();
+        let next = read_usage(&path, Some(&first));
+        let whole = read_usage(&path, None);
+        assert_eq!(next.usage[0].tokens.output, 5 + 7 + 2);
+        assert_eq!(next.usage[0].tokens.input, 30);
+        assert_eq!(next.usage[0].tokens, whole.usage[0].tokens);
+        assert_eq!(next.offset, log.len() as u64);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn parse_session_file_keeps_the_latest_recorded_branch() {
+        let log = [
+            serde_json::json!({"type": "user", "gitBranch": "main"}),
+            serde_json::json!({"type": "user", "gitBranch": "feat-x"}),
+            serde_json::json!({"type": "user", "gitBranch": "HEAD"}),
+        ]
+        .map(|l| l.to_string() + "\n")
+        .concat();
+        let path =
+            std::env::temp_dir().join(format!("agf-claude-branch-{}.jsonl", std::process::id()));
+        fs::write(&path, log).unwrap();
+        let meta = parse_session_file(&path).unwrap();
+        assert_eq!(meta.git_branch.as_deref(), Some("feat-x"));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn transcript_messages_splits_text_and_tool_blocks() {
+        let line = serde_json::json!({
+            "type": "assistant",
+            "timestamp": "2026-04-21T12:00:00.000Z",
+            "message": {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "hmm"},
+                {"type": "text", "text": "Running tests"},
+                {"type": "tool_use", "name": "Bash", "input": {"command": "cargo test"}}
+            ]}
+        });
+        let msgs = transcript_messages(&line);
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(msgs[0].role, Role::Assistant);
+        assert_eq!(msgs[0].text, "Running tests");
+        assert_eq!(msgs[1].role, Role::Tool);
+        assert_eq!(msgs[1].tool.as_deref(), Some("Bash"));
+        assert_eq!(msgs[1].text, "cargo test");
+        assert!(msgs[0].timestamp.is_some());
+    }
+
+    #[test]
+    fn transcript_messages_skips_meta_and_other_types() {
+        let meta =
+            serde_json::json!({"type": "user", "isMeta": true, "message": {"content": "caveat"}});
+        assert!(transcript_messages(&meta).is_empty());
+        let summary = serde_json::json!({"type": "summary", "summary": "x"});
+        assert!(transcript_messages(&summary).is_empty());
+        let prompt = serde_json::json!({"type": "user", "message": {"content": "fix the bug"}});
+        assert_eq!(transcript_messages(&prompt)[0].text, "fix the bug");
+    }
+}
@@ -1,27 +1,34 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 
 use rusqlite::Connection;
+use serde_json::Value;
 
 use crate::error::AgfError;
-use crate::model::{Agent, Session};
-use crate::scanner::{first_line_truncated, read_first_line};
-
-pub fn scan() -> Result<Vec<Session>, AgfError> {
-    let codex_dir = crate::config::codex_dir()?;
-
+use crate::model::{Agent, ModelUsage, Session, TokenCounts};
+use crate::scanner::file_cache::FileCache;
+use crate::scanner::{first_line_truncated, jsonl_values, read_first_line, read_head_tail};
+use crate::transcript::{
+    content_text, parse_timestamp, tool_input_preview, Message, MessageStream, Role,
+};
+use crate::usage;
+
+pub fn scan(codex_dir: &Path) -> Result<Vec<Session>, AgfError> {
     // Collect summaries from history.jsonl (keyed by session_id, newest-first)
-    let summaries = read_history_summaries(&codex_dir);
+    let summaries = read_history_summaries(codex_dir);
 
     // Primary: read from SQLite (state_*.sqlite)
-    let mut sessions = scan_sqlite(&codex_dir, &summaries);
+    let mut sessions = scan_sqlite(codex_dir, &summaries);
 
     // Fallback: if SQLite found nothing, try JSONL walkdir
     if sessions.is_empty() {
-        sessions = scan_jsonl(&codex_dir, &summaries);
+        sessions = scan_jsonl(codex_dir, &summaries);
     }
 
+    attach_usage(codex_dir, &mut sessions);
+
     sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
     Ok(sessions)
 }
@@ -44,12 +51,19 @@ fn scan_sqlite(
             Err(_) => return Vec::new(),
         };
 
-    let mut stmt = match conn.prepare(
-        "SELECT id, cwd, title, updated_at, git_branch, first_user_message
-         FROM threads
-         WHERE archived = 0 AND cwd != ''
-         ORDER BY updated_at DESC",
-    ) {
+    // `git_sha` is missing from older databases.
+    let query = |sha: &str| {
+        format!(
+            "SELECT id, cwd, title, updated_at, git_branch, first_user_message, {sha}
+             FROM threads
+             WHERE archived = 0 AND cwd != ''
+             ORDER BY updated_at DESC"
+        )
+    };
+    let mut stmt = match conn
+        .prepare(&query("git_sha"))
+        .or_else(|_| conn.prepare(&query("NULL")))
+    {
         Ok(s) => s,
         Err(_) => return Vec::new(),
     };
@@ -62,6 +76,7 @@ fn scan_sqlite(
             row.get::<_, i64>(3).unwrap_or(0),
             row.get::<_, Option<String>>(4)?,
             row.get::<_, String>(5).unwrap_or_default(),
+            row.get::<_, Option<String>>(6).unwrap_or_default(),
         ))
     }) {
         Ok(r) => r,
@@ -70,7 +85,7 @@ fn scan_sqlite(
 
     let mut sessions = Vec::new();
     for row in rows.flatten() {
-        let (session_id, cwd, title, updated_at, git_branch, first_msg) = row;
+        let (session_id, cwd, title, updated_at, git_branch, first_msg, commit) = row;
 
         if session_id.is_empty() || cwd.is_empty() {
             continue;
@@ -106,8 +121,13 @@ fn scan_sqlite(
             summaries: session_summaries,
             timestamp,
             git_branch,
+            commit: commit.filter(|c| !c.is_empty()),
+            branch_is_live: false,
             worktree: None,
             recap: None,
+            usage: Vec::new(),
+            data_root: None,
+            session_file: None,
         });
     }
 
@@ -159,6 +179,7 @@ fn scan_jsonl(
     #[derive(Deserialize)]
     struct GitInfo {
         branch: Option<String>,
+        commit_hash: Option<String>,
     }
 
     let sessions_dir = codex_dir.join("sessions");
@@ -219,7 +240,10 @@ fn scan_jsonl(
             .map(|dt| dt.timestamp_millis())
             .unwrap_or(0);
 
-        let git_branch = payload.git.and_then(|g| g.branch);
+        let (git_branch, commit) = match payload.git {
+            Some(git) => (git.branch, git.commit_hash),
+            None => (None, None),
+        };
         let session_summaries = summaries.get(&session_id).cloned().unwrap_or_default();
 
         sessions.push(Session {
@@ -230,14 +254,114 @@ fn scan_jsonl(
             summaries: session_summaries,
             timestamp,
             git_branch,
+            commit,
+            branch_is_live: false,
             worktree: None,
             recap: None,
+            usage: Vec::new(),
+            data_root: None,
+            session_file: None,
         });
     }
 
     sessions
 }
 
+/// Per-file I/O cap for usage extraction. `token_count` events carry
+/// running totals, so only the latest one (in the tail) matters.
+const HEAD_BYTES: u64 = 16 * 1024;
+const TAIL_BYTES: u64 = 256 * 1024;
+
+/// Format version of the per-rollout usage cache.
+const USAGE_CACHE_VERSION: u32 = 1;
+
+/// Fill `Session::usage` and `Session::session_file` from each session's
+/// rollout file. Parsed usage is memoized per file, so only rollouts that
+/// grew since the last scan are read again.
+fn attach_usage(codex_dir: &Path, sessions: &mut [Session]) {
+    use walkdir::WalkDir;
+
+    let paths: Vec<PathBuf> = WalkDir::new(codex_dir.join("sessions"))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .collect();
+
+    let mut cache = FileCache::load("codex", codex_dir, USAGE_CACHE_VERSION);
+    let parsed = cache.resolve(paths, read_usage);
+    cache.save();
+
+    // Rollout names end in the session UUID (`rollout-<ts>-<id>.jsonl`).
+    let mut by_id: HashMap<String, (PathBuf, Vec<ModelUsage>)> = HashMap::new();
+    for (path, usage) in parsed {
+        let Some(stem) = path.file_stem().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if stem.len() > 36 && stem.is_char_boundary(stem.len() - 36) {
+            by_id.insert(stem[stem.len() - 36..].to_string(), (path, usage));
+        }
+    }
+    for session in sessions {
+        if let Some((path, usage)) = by_id.remove(&session.session_id) {
+            session.session_file = Some(path);
+            session.usage = usage;
+        }
+    }
+}
+
+/// Latest cumulative `token_count` event, attributed to the model named by
+/// the last `turn_context`. Codex reports cached prompt tokens as part of
+/// `input_tokens`; they are split out into `cache_read` here.
+fn read_usage(path: &Path) -> Vec<ModelUsage> {
+    let Some(ht) = read_head_tail(path, HEAD_BYTES, TAIL_BYTES) else {
+        return Vec::new();
+    };
+    let mut model: Option<String> = None;
+    let mut totals: Option<TokenCounts> = None;
+    for line in ht.head.lines().chain(ht.tail.lines()) {
+        if !line.contains("\"turn_context\"") && !line.contains("\"token_count\"") {
+            continue;
+        }
+        let Ok(val) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let Some(payload) = val.get("payload") else {
+            continue;
+        };
+        match val.get("type").and_then(|t| t.as_str()) {
+            Some("turn_context") => {
+                if let Some(m) = payload.get("model").and_then(|m| m.as_str()) {
+                    model = Some(m.to_string());
+                }
+            }
+            Some("event_msg")
+                if payload.get("type").and_then(|t| t.as_str()) == Some("token_count") =>
+            {
+                let Some(usage) = payload.pointer("/info/total_token_usage") else {
+                    continue;
+                };
+                let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+                let cached = count("cached_input_tokens");
+                totals = Some(TokenCounts {
+                    input: count("input_tokens").saturating_sub(cached),
+                    output: count("output_tokens"),
+                    cache_read: cached,
+                    cache_write: 0,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let mut usage = Vec::new();
+    if let Some(tokens) = totals {
+        let model = model.unwrap_or_else(|| "unknown".to_string());
+        usage::accumulate(&mut usage, &model, &tokens);
+    }
+    usage
+}
+
 #[derive(serde::Deserialize)]
 struct HistoryEntry {
     session_id: Option<String>,
@@ -287,3 +411,110 @@ fn read_history_summaries(codex_dir: &std::path::Path) -> HashMap<String, Vec<St
         })
         .collect()
 }
+
+/// Locate the rollout JSONL for `session_id` under `~/.codex/sessions/`.
+/// Rollout names end in the session UUID (`rollout-<ts>-<id>.jsonl`); older
+/// files that don't follow the convention are matched by `payload.id` on
+/// their first line.
+pub(crate) fn rollout_file(codex_dir: &Path, session_id: &str) -> Option<PathBuf> {
+    use walkdir::WalkDir;
+
+    let sessions_dir = codex_dir.join("sessions");
+    let suffix = format!("{session_id}.jsonl");
+    let mut fallback = Vec::new();
+    for entry in WalkDir::new(&sessions_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if name.ends_with(&suffix) {
+            return Some(path.to_path_buf());
+        }
+        if name.ends_with(".jsonl") {
+            fallback.push(path.to_path_buf());
+        }
+    }
+    fallback.into_iter().find(|path| {
+        read_first_line(path)
+            .and_then(|line| serde_json::from_str::<Value>(line.trim()).ok())
+            .and_then(|v| {
+                v.get("payload")
+                    .and_then(|p| p.get("id"))
+                    .and_then(|id| id.as_str())
+                    .map(|id| id == session_id)
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// Stream the conversation from the session's rollout file.
+pub fn transcript(session: &Session) -> Result<MessageStream, AgfError> {
+    let codex_dir = crate::config::session_root(session)?;
+    let path = rollout_file(&codex_dir, &session.session_id)
+        .ok_or_else(|| AgfError::SessionNotFound(session.session_id.clone()))?;
+    Ok(Box::new(
+        jsonl_values(&path)?.flat_map(|val| transcript_messages(&val)),
+    ))
+}
+
+/// Convert one rollout line into transcript entries. Current rollouts wrap
+/// items as `{"type":"response_item","payload":{...}}`; legacy ones write the
+/// item at the top level. `event_msg` lines duplicate the response items and
+/// are ignored.
+fn transcript_messages(val: &Value) -> Vec<Message> {
+    let ts = parse_timestamp(val.get("timestamp"));
+    let item = match val.get("type").and_then(|t| t.as_str()) {
+        Some("response_item") => match val.get("payload") {
+            Some(p) => p,
+            None => return Vec::new(),
+        },
+        Some("message" | "function_call" | "function_call_output") => val,
+        _ => return Vec::new(),
+    };
+
+    match item.get("type").and_then(|t| t.as_str()) {
+        Some("message") => {
+            let role = match item.get("role").and_then(|r| r.as_str()) {
+                Some("user") => Role::User,
+                Some("assistant") => Role::Assistant,
+                _ => return Vec::new(),
+            };
+            let text = item.get("content").map(content_text).unwrap_or_default();
+            // Codex injects AGENTS.md and environment context as user turns.
+            let trimmed = text.trim_start();
+            if trimmed.is_empty()
+                || trimmed.starts_with("<environment_context>")
+                || trimmed.starts_with("<user_instructions>")
+                || trimmed.starts_with("# AGENTS.md instructions")
+            {
+                return Vec::new();
+            }
+            vec![Message::new(role, text, ts)]
+        }
+        Some("function_call" | "custom_tool_call" | "local_shell_call") => {
+            let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("shell");
+            let input = item
+                .get("arguments")
+                .or_else(|| item.get("input"))
+                .or_else(|| item.get("action"))
+                .map(tool_input_preview)
+                .unwrap_or_default();
+            vec![Message::tool(name, input, ts)]
+        }
+        Some("function_call_output" | "custom_tool_call_output") => {
+            let output = match item.get("output") {
+                Some(Value::String(s)) => s.clone(),
+                Some(other) => other
+                    .get("content")
+                    .map(content_text)
+                    .unwrap_or_else(|| other.to_string()),
+                None => String::new(),
+            };
+            vec![Message::tool_result(output, ts)]
+        }
+        _ => Vec::new(),
+    }
+}
@@ -1,3 +1,4 @@
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
 use rusqlite::Connection;
@@ -5,11 +6,11 @@ use walkdir::WalkDir;
 
 use crate::error::AgfError;
 use crate::model::{Agent, Session};
+use crate::transcript::{Message, MessageStream, Role};
 
 use super::truncate;
 
-pub fn scan() -> Result<Vec<Session>, AgfError> {
-    let cursor_dir = crate::config::cursor_dir()?;
+pub fn scan(cursor_dir: &Path) -> Result<Vec<Session>, AgfError> {
     let projects_dir = cursor_dir.join("projects");
 
     if !projects_dir.exists() {
@@ -101,8 +102,13 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
             summaries: summary.into_iter().collect(),
             timestamp,
             git_branch: None,
+            commit: None,
+            branch_is_live: false,
             worktree: None,
             recap: None,
+            usage: Vec::new(),
+            data_root: None,
+            session_file: Some(path.to_path_buf()),
         });
     }
 
@@ -201,3 +207,115 @@ fn solve(parts: &[&str], idx: usize, current: &Path) -> Option<PathBuf> {
     }
     None
 }
+
+/// Locate `~/.cursor/projects/*/agent-transcripts/<session_id>.txt`.
+pub(crate) fn transcript_file(cursor_dir: &Path, session_id: &str) -> Option<PathBuf> {
+    let file_name = format!("{session_id}.txt");
+    std::fs::read_dir(cursor_dir.join("projects"))
+        .ok()?
+        .flatten()
+        .map(|e| e.path().join("agent-transcripts").join(&file_name))
+        .find(|p| p.is_file())
+}
+
+/// Stream the plain-text agent transcript. Turns start with a `user:` or
+/// `assistant:` line; tool activity is logged as `[Tool call] <name>` and
+/// `[Tool result] <name>` blocks.
+pub fn transcript(session: &Session) -> Result<MessageStream, AgfError> {
+    let cursor_dir = crate::config::session_root(session)?;
+    let path = transcript_file(&cursor_dir, &session.session_id)
+        .ok_or_else(|| AgfError::SessionNotFound(session.session_id.clone()))?;
+    let file = std::fs::File::open(&path)?;
+    Ok(Box::new(TranscriptReader {
+        lines: Box::new(BufReader::new(file).lines().map_while(Result::ok)),
+        pending: None,
+    }))
+}
+
+/// Line-driven parser over a Cursor transcript: accumulates body lines until
+/// the next turn header, then yields the finished message.
+struct TranscriptReader {
+    lines: Box<dyn Iterator<Item = String> + Send>,
+    pending: Option<Message>,
+}
+
+impl TranscriptReader {
+    fn header(line: &str) -> Option<Message> {
+        let trimmed = line.trim();
+        match trimmed {
+            "user:" => return Some(Message::new(Role::User, "", None)),
+            "assistant:" | "A:" => return Some(Message::new(Role::Assistant, "", None)),
+            _ => {}
+        }
+        if let Some(name) = trimmed.strip_prefix("[Tool call]") {
+            return Some(Message::tool(name.trim(), "", None));
+        }
+        if trimmed.starts_with("[Tool result]") {
+            return Some(Message::tool_result("", None));
+        }
+        None
+    }
+
+    fn finish(mut msg: Message) -> Option<Message> {
+        let text = msg
+            .text
+            .replace("<user_query>", "")
+            .replace("</user_query>", "");
+        msg.text = text.trim().to_string();
+        if msg.text.is_empty() && msg.role != Role::Tool {
+            None
+        } else {
+            Some(msg)
+        }
+    }
+}
+
+impl Iterator for TranscriptReader {
+    type Item = Message;
+
+    fn next(&mut self) -> Option<Message> {
+        loop {
+            let Some(line) = self.lines.next() else {
+                return self.pending.take().and_then(Self::finish);
+            };
+            if let Some(next) = Self::header(&line) {
+                if let Some(done) = self.pending.replace(next).and_then(Self::finish) {
+                    return Some(done);
+                }
+                continue;
+            }
+            if let Some(msg) = self.pending.as_mut() {
+                msg.text.push_str(&line);
+                msg.text.push('\n');
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcript_reader_splits_turns_and_tools() {
+        let text = "user:\n<user_query>\nfix it\n</user_query>\n\nassistant:\nOn it.\n[Tool call] Shell\n  command: ls\n[Tool result] Shell\nsrc\n";
+        let reader = TranscriptReader {
+            lines: Box::new(
+                text.lines()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
+            pending: None,
+        };
+        let msgs: Vec<Message> = reader.collect();
+        let roles: Vec<Role> = msgs.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![Role::User, Role::Assistant, Role::Tool, Role::ToolResult]
+        );
+        assert_eq!(msgs[0].text, "fix it");
+        assert_eq!(msgs[2].tool.as_deref(), Some("Shell"));
+        assert_eq!(msgs[3].text, "src");
+    }
+}
@@ -0,0 +1,405 @@
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use walkdir::WalkDir;
+
+use crate::error::AgfError;
+use crate::model::{Agent, Session};
+use crate::plugin::{expand_home, CustomAgent};
+use crate::scanner::file_cache::FileCache;
+use crate::scanner::{first_line_truncated, jsonl_values, read_head_tail};
+use crate::shell::CommandShell;
+use crate::transcript::{content_text, parse_timestamp, Message, MessageStream, Role};
+
+/// An `[[agents]]` entry in config.toml, for CLIs that keep one JSONL file
+/// per session:
+///
+/// ```toml
+/// [[agents]]
+/// name = "Toad"
+/// sessions = "~/.toad/sessions/**/*.jsonl" # glob: `*`, `?`, `**`
+/// cwd = "/cwd"                             # JSON pointer, required
+/// id = "/session_id"                       # optional; default: file stem
+/// timestamp = "/timestamp"                 # optional; default: file mtime
+/// title = "/message/content"               # optional; first match is the summary
+/// resume = "toad --resume {id}"            # `{id}` is the session id, quoted
+/// new_session = "toad"                     # optional
+/// color = "#f97316"                        # optional
+/// ```
+///
+/// Pointers are looked up in every line of the file's head (and, for
+/// `timestamp`, its tail): the first line that has `id`, `cwd` or `title`
+/// wins, the last line that has `timestamp` wins.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AgentConfig {
+    pub name: String,
+    pub sessions: String,
+    pub cwd: String,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub resume: String,
+    #[serde(default)]
+    pub new_session: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Executable used to detect whether the agent is installed. Defaults to
+    /// the first word of `new_session`, else of `resume`.
+    #[serde(default)]
+    pub cli: Option<String>,
+}
+
+/// Compiled form of an `AgentConfig`: the glob split into a literal root
+/// directory (also the agent's data source) and the wildcard components
+/// below it.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct JsonlSpec {
+    pub root: PathBuf,
+    pattern: Vec<String>,
+    id: Option<String>,
+    cwd: String,
+    timestamp: Option<String>,
+    title: Option<String>,
+    resume: String,
+}
+
+impl JsonlSpec {
+    pub fn new(config: &AgentConfig) -> Self {
+        let glob = expand_home(&config.sessions);
+        let mut root = PathBuf::new();
+        let mut pattern = Vec::new();
+        for component in glob.components() {
+            let part = component.as_os_str().to_string_lossy();
+            if pattern.is_empty() && !part.contains(['*', '?']) {
+                root.push(component);
+            } else {
+                pattern.push(part.into_owned());
+            }
+        }
+        Self {
+            root,
+            pattern,
+            id: config.id.clone(),
+            cwd: config.cwd.clone(),
+            timestamp: config.timestamp.clone(),
+            title: config.title.clone(),
+            resume: config.resume.clone(),
+        }
+    }
+
+    /// Session files under `root` whose relative path matches the glob. A
+    /// glob without wildcards names a single file, which walkdir yields as
+    /// its own root with an empty relative path.
+    fn files(&self) -> Vec<PathBuf> {
+        let pattern: Vec<&str> = self.pattern.iter().map(String::as_str).collect();
+        WalkDir::new(&self.root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|p| {
+                let rel: Vec<String> = p
+                    .strip_prefix(&self.root)
+                    .unwrap_or(p)
+                    .components()
+                    .filter_map(|c| match c {
+                        Component::Normal(s) => Some(s.to_string_lossy().into_owned()),
+                        _ => None,
+                    })
+                    .collect();
+                let rel: Vec<&str> = rel.iter().map(String::as_str).collect();
+                glob_match(&pattern, &rel)
+            })
+            .collect()
+    }
+
+    fn parse(&self, path: &Path) -> Option<SessionMeta> {
+        let ht = read_head_tail(path, HEAD_BYTES, TAIL_BYTES)?;
+        let head: Vec<Value> = json_lines(&ht.head).collect();
+        let first = |pointer: &str| head.iter().find_map(|v| v.pointer(pointer));
+
+        let cwd = first(&self.cwd)?.as_str()?.to_string();
+        let session_id = match &self.id {
+            Some(pointer) => value_to_string(first(pointer)?)?,
+            None => path.file_stem()?.to_str()?.to_string(),
+        };
+        // The id ends up in a shell command; anything beyond a plain token
+        // is not a session id agf can resume.
+        if !is_plain_id(&session_id) {
+            return None;
+        }
+        let timestamp = self
+            .timestamp
+            .as_deref()
+            .and_then(|pointer| {
+                let tail: Vec<Value> = json_lines(&ht.tail).collect();
+                tail.iter()
+                    .chain(head.iter())
+                    .rev()
+                    .find_map(|v| to_millis(v.pointer(pointer)))
+            })
+            .or_else(|| file_mtime_ms(path))
+            .unwrap_or(0);
+        let title = self.title.as_deref().and_then(|pointer| {
+            head.iter()
+                .filter_map(|v| v.pointer(pointer))
+                .find_map(|t| first_line_truncated(&content_text(t), 120))
+        });
+
+        Some(SessionMeta {
+            session_id,
+            cwd,
+            timestamp,
+            title,
+        })
+    }
+
+    /// agf already runs the command from the session's project directory.
+    /// The id is quoted for the active shell; quotes the template already
+    /// puts around `{id}` are dropped so it isn't quoted twice.
+    pub fn resume_cmd(&self, session_id: &str) -> String {
+        let quoted = CommandShell::from_env().quote(session_id);
+        self.resume
+            .replace("'{id}'", "{id}")
+            .replace("\"{id}\"", "{id}")
+            .replace("{id}", &quoted)
+    }
+
+    /// The session file whose id is `session_id`.
+    fn session_file(&self, session_id: &str) -> Option<PathBuf> {
+        self.files().into_iter().find(|p| match &self.id {
+            Some(_) => self.parse(p).is_some_and(|m| m.session_id == session_id),
+            None => p.file_stem().and_then(|s| s.to_str()) == Some(session_id),
+        })
+    }
+}
+
+/// `[A-Za-z0-9._-]+`.
+fn is_plain_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+}
+
+const HEAD_BYTES: u64 = 64 * 1024;
+const TAIL_BYTES: u64 = 16 * 1024;
+
+/// Bump when `SessionMeta` or the parse logic changes.
+const META_CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionMeta {
+    session_id: String,
+    cwd: String,
+    timestamp: i64,
+    title: Option<String>,
+}
+
+pub fn scan(agent: &'static CustomAgent, spec: &JsonlSpec) -> Result<Vec<Session>, AgfError> {
+    if !spec.root.exists() {
+        return Ok(Vec::new());
+    }
+    let cache_name: String = agent
+        .key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let mut cache: FileCache<Option<SessionMeta>> =
+        FileCache::load(&cache_name, &spec.root, META_CACHE_VERSION);
+    let metas = cache.resolve(spec.files(), |p| spec.parse(p));
+    cache.save();
+
+    let mut sessions: Vec<Session> = metas
+        .into_iter()
+        .filter_map(|(path, meta)| Some((path, meta?)))
+        .map(|(path, meta)| Session {
+            agent: Agent::Custom(agent),
+            session_id: meta.session_id,
+            project_name: Path::new(&meta.cwd)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            project_path: meta.cwd,
+            summaries: meta.title.into_iter().collect(),
+            timestamp: meta.timestamp,
+            git_branch: None,
+            commit: None,
+            branch_is_live: false,
+            worktree: None,
+            recap: None,
+            usage: Vec::new(),
+            data_root: None,
+            session_file: Some(path),
+        })
+        .collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+    Ok(sessions)
+}
+
+pub fn delete(
+    spec: &JsonlSpec,
+    session_id: &str,
+    batch: &mut crate::trash::Batch,
+) -> Result<(), std::io::Error> {
+    match spec.session_file(session_id) {
+        Some(path) => batch.remove_path(&path),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no session file for {session_id}"),
+        )),
+    }
+}
+
+/// Best-effort transcript: lines carrying a `user`/`assistant` role at
+/// `/role` or `/message/role`, with text from the matching `content`.
+pub fn transcript(spec: &JsonlSpec, session_id: &str) -> Result<MessageStream, AgfError> {
+    let path = spec
+        .session_file(session_id)
+        .ok_or_else(|| AgfError::SessionNotFound(session_id.to_string()))?;
+    Ok(Box::new(jsonl_values(&path)?.filter_map(|v| {
+        let msg = if v.get("role").is_some() {
+            &v
+        } else {
+            v.get("message")?
+        };
+        let role = match msg.get("role")?.as_str()? {
+            "user" => Role::User,
+            "assistant" => Role::Assistant,
+            _ => return None,
+        };
+        let text = content_text(msg.get("content").or_else(|| msg.get("text"))?);
+        if text.trim().is_empty() {
+            return None;
+        }
+        let ts = parse_timestamp(v.get("timestamp"));
+        Some(Message::new(role, text, ts))
+    })))
+}
+
+fn json_lines(s: &str) -> impl Iterator<Item = Value> + '_ {
+    s.lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str(l).ok())
+}
+
+fn value_to_string(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// RFC 3339 strings, or Unix timestamps in seconds or milliseconds.
+fn to_millis(v: Option<&Value>) -> Option<i64> {
+    let ms = parse_timestamp(v)?;
+    Some(if ms < 100_000_000_000 { ms * 1000 } else { ms })
+}
+
+fn file_mtime_ms(path: &Path) -> Option<i64> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+    Some(
+        modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64,
+    )
+}
+
+/// Match path components against glob components. `**` spans any number of
+/// directories; `*` and `?` match within one component.
+fn glob_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => {
+            glob_match(rest, path) || (!path.is_empty() && glob_match(pattern, &path[1..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((name, path_rest)) => wildcard_match(first, name) && glob_match(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ni));
+            pi += 1;
+        } else if let Some((sp, sn)) = star {
+            pi = sp + 1;
+            ni = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_pointers_from_head_and_tail() {
+        let dir = std::env::temp_dir().join(format!("agf-declarative-{}", std::process::id()));
+        let nested = dir.join("2025").join("01");
+        std::fs::create_dir_all(&nested).unwrap();
+        let file = nested.join("s1.jsonl");
+        std::fs::write(
+            &file,
+            concat!(
+                "{\"meta\":{\"id\":\"abc\",\"cwd\":\"/tmp/proj\"},\"ts\":1700000000}\n",
+                "{\"message\":{\"content\":[{\"text\":\"fix the build\"}]}}\n",
+                "{\"ts\":\"2025-01-02T03:04:05Z\"}\n",
+            ),
+        )
+        .unwrap();
+        std::fs::write(nested.join("notes.txt"), "").unwrap();
+
+        let spec = JsonlSpec::new(&AgentConfig {
+            name: "Toad".into(),
+            sessions: format!("{}/**/*.jsonl", dir.display()),
+            cwd: "/meta/cwd".into(),
+            id: Some("/meta/id".into()),
+            timestamp: Some("/ts".into()),
+            title: Some("/message/content".into()),
+            resume: "toad --resume '{id}'".into(),
+            new_session: None,
+            color: None,
+            cli: None,
+        });
+        assert_eq!(spec.root, dir);
+        assert_eq!(spec.files(), std::slice::from_ref(&file));
+
+        let meta = spec.parse(&file).unwrap();
+        assert_eq!(meta.session_id, "abc");
+        assert_eq!(meta.cwd, "/tmp/proj");
+        assert_eq!(meta.timestamp, 1_735_787_045_000);
+        assert_eq!(meta.title.as_deref(), Some("fix the build"));
+        assert_eq!(spec.resume_cmd("abc"), "toad --resume 'abc'");
+
+        std::fs::write(
+            nested.join("s2.jsonl"),
+            "{\"meta\":{\"id\":\"x; rm -rf ~\",\"cwd\":\"/tmp/proj\"}}\n",
+        )
+        .unwrap();
+        assert!(spec.parse(&nested.join("s2.jsonl")).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Identity of a source file at the time it was parsed. Any change to size,
+/// mtime or inode (rewrite-by-rename) invalidates the cached result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    pub mtime_ns: u64,
+    pub inode: u64,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        let mtime_ns = meta
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(&meta);
+        #[cfg(not(unix))]
+        let inode = 0;
+        Some(Self {
+            size: meta.len(),
+            mtime_ns,
+            inode,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    stamp: FileStamp,
+    value: T,
+}
+
+#[derive(Deserialize)]
+struct CacheFile<T> {
+    version: u32,
+    files: HashMap<PathBuf, Entry<T>>,
+}
+
+#[derive(Serialize)]
+struct CacheFileRef<'a, T> {
+    version: u32,
+    files: &'a HashMap<PathBuf, Entry<T>>,
+}
+
+/// Per-file parse results for one scanner and data root, persisted next to
+/// the session cache as `files-<name>-<root hash>.json`. A rescan re-parses
+/// only files whose stamp changed (or that are new) and drops entries for
+/// files that disappeared, so touching one session no longer means
+/// re-reading every log.
+///
+/// `version` is the scanner's own format version: bump it whenever the
+/// parser or `T` changes so stale results are discarded.
+pub struct FileCache<T> {
+    path: PathBuf,
+    version: u32,
+    files: HashMap<PathBuf, Entry<T>>,
+}
+
+impl<T> FileCache<T>
+where
+    T: Serialize + DeserializeOwned + Clone + Send + Sync,
+{
+    pub fn load(name: &str, root: &Path, version: u32) -> Self {
+        // One file per root: `resolve` forgets files it wasn't given, so
+        // roots sharing a cache would evict each other on every scan.
+        let digest = Sha256::digest(root.to_string_lossy().as_bytes());
+        let hash: String = digest[..4].iter().map(|b| format!("{b:02x}")).collect();
+        let path = crate::cache::cache_path().with_file_name(format!("files-{name}-{hash}.json"));
+        // Pre-root layout; superseded by the per-root files.
+        let _ = fs::remove_file(path.with_file_name(format!("files-{name}.json")));
+        let files = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<CacheFile<T>>(&c).ok())
+            .filter(|c| c.version == version)
+            .map(|c| c.files)
+            .unwrap_or_default();
+        Self {
+            path,
+            version,
+            files,
+        }
+    }
+
+    /// Parse every file in `paths`, reusing cached results for unchanged
+    /// files and running `parse` in parallel for the rest. Entries for files
+    /// not in `paths` are forgotten. Results come back in `paths` order;
+    /// files that can no longer be stat'ed are skipped.
+    pub fn resolve<F>(&mut self, paths: Vec<PathBuf>, parse: F) -> Vec<(PathBuf, T)>
+    where
+        F: Fn(&Path) -> T + Sync,
+    {
+        self.resolve_with(paths, |path, _| parse(path))
+    }
+
+    /// `resolve`, handing `parse` the file's previous result too (if any),
+    /// so append-only logs can be read on from where the last parse stopped.
+    pub fn resolve_with<F>(&mut self, paths: Vec<PathBuf>, parse: F) -> Vec<(PathBuf, T)>
+    where
+        F: Fn(&Path, Option<&T>) -> T + Sync,
+    {
+        let stamped: Vec<(PathBuf, FileStamp)> = paths
+            .into_par_iter()
+            .filter_map(|p| FileStamp::of(&p).map(|s| (p, s)))
+            .collect();
+
+        let fresh: Vec<(usize, T)> = stamped
+            .par_iter()
+            .enumerate()
+            .filter(|(_, (path, stamp))| self.files.get(path).is_none_or(|e| e.stamp != *stamp))
+            .map(|(i, (path, _))| (i, parse(path, self.files.get(path).map(|e| &e.value))))
+            .collect();
+
+        if std::env::var("AGF_DEBUG").is_ok() {
+            eprintln!(
+                "[agf] {}: {} files, {} reparsed",
+                self.path.display(),
+                stamped.len(),
+                fresh.len()
+            );
+        }
+
+        let live: HashSet<&PathBuf> = stamped.iter().map(|(p, _)| p).collect();
+        self.files.retain(|p, _| live.contains(p));
+        for (i, value) in fresh {
+            let (path, stamp) = &stamped[i];
+            self.files.insert(
+                path.clone(),
+                Entry {
+                    stamp: *stamp,
+                    value,
+                },
+            );
+        }
+
+        stamped
+            .into_iter()
+            .filter_map(|(path, _)| {
+                let value = self.files.get(&path)?.value.clone();
+                Some((path, value))
+            })
+            .collect()
+    }
+
+    /// Persist atomically (`cache::write_atomic`); the last save wins.
+    pub fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let file = CacheFileRef {
+            version: self.version,
+            files: &self.files,
+        };
+        if let Ok(json) = serde_json::to_string(&file) {
+            let _ = crate::cache::write_atomic(&self.path, &json);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn resolve_reparses_only_changed_files() {
+        let dir = std::env::temp_dir().join(format!("agf-file-cache-{}", std::process::id()));
+        let _ = fs::create_dir_all(&dir);
+        let a = dir.join("a.jsonl");
+        let b = dir.join("b.jsonl");
+        fs::write(&a, "one").unwrap();
+        fs::write(&b, "two").unwrap();
+
+        let mut cache: FileCache<usize> = FileCache {
+            path: dir.join("files-test.json"),
+            version: 1,
+            files: HashMap::new(),
+        };
+        let calls = AtomicUsize::new(0);
+        let parse = |p: &Path| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            fs::read_to_string(p).unwrap().len()
+        };
+
+        let first = cache.resolve(vec![a.clone(), b.clone()], parse);
+        assert_eq!(first, [(a.clone(), 3), (b.clone(), 3)]);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        fs::write(&b, "three").unwrap();
+        let second = cache.resolve(vec![a.clone(), b.clone()], parse);
+        assert_eq!(second, [(a.clone(), 3), (b.clone(), 5)]);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Files that vanish from the listing are forgotten.
+        cache.resolve(vec![a.clone()], parse);
+        assert!(!cache.files.contains_key(&b));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
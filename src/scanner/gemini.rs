@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 use std::fs;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use sha2::{Digest, Sha256};
 
 use crate::error::AgfError;
-use crate::model::{Agent, Session};
+use crate::model::{Agent, ModelUsage, Session, TokenCounts};
+use crate::scanner::file_cache::FileCache;
+use crate::transcript::{
+    content_text, parse_timestamp, tool_input_preview, Message, MessageStream, Role,
+};
+use crate::usage;
 
 use super::truncate;
 
@@ -16,25 +21,22 @@ use super::truncate;
 /// hundred bytes; the first user message almost always lands in the first 64 KB.
 const MAX_FILE_BYTES: usize = 64 * 1024;
 
-pub fn scan() -> Result<Vec<Session>, AgfError> {
-    let gemini_dir = crate::config::gemini_dir()?;
+pub fn scan(gemini_dir: &Path) -> Result<Vec<Session>, AgfError> {
     let tmp_dir = gemini_dir.join("tmp");
 
     if !tmp_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let path_map = build_path_map(&gemini_dir);
-
-    // Dedup by sessionId: keep the entry with the latest `lastUpdated`.
-    // The same session can appear in both a hash dir (old) and a named dir
-    // (new) when Gemini CLI migrates a project to projects.json.
-    let mut by_id: HashMap<String, Session> = HashMap::new();
+    let path_map = build_path_map(gemini_dir);
 
     let Ok(entries) = fs::read_dir(&tmp_dir) else {
         return Ok(Vec::new());
     };
 
+    // chat file → (project_path, project_name)
+    let mut projects: HashMap<PathBuf, (String, String)> = HashMap::new();
+
     for entry in entries.filter_map(|e| e.ok()) {
         let dir_name = entry.file_name().to_string_lossy().to_string();
         let chats_dir = entry.path().join("chats");
@@ -59,12 +61,45 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
                 continue;
             }
 
-            if let Some(session) = parse_session(&chat_entry.path(), &project_path, &project_name) {
-                let existing = by_id.get(&session.session_id);
-                if existing.is_none_or(|e| session.timestamp > e.timestamp) {
-                    by_id.insert(session.session_id.clone(), session);
-                }
-            }
+            projects.insert(
+                chat_entry.path(),
+                (project_path.clone(), project_name.clone()),
+            );
+        }
+    }
+
+    // Only chat files that changed since the last scan are parsed again.
+    let mut cache = FileCache::load("gemini", gemini_dir, CHAT_CACHE_VERSION);
+    let parsed = cache.resolve(projects.keys().cloned().collect(), parse_chat);
+    cache.save();
+
+    // Dedup by sessionId: keep the entry with the latest `lastUpdated`.
+    // The same session can appear in both a hash dir (old) and a named dir
+    // (new) when Gemini CLI migrates a project to projects.json.
+    let mut by_id: HashMap<String, Session> = HashMap::new();
+    for (path, chat) in parsed {
+        let (Some(chat), Some((project_path, project_name))) = (chat, projects.get(&path)) else {
+            continue;
+        };
+        let session = Session {
+            agent: Agent::Gemini,
+            session_id: chat.session_id,
+            project_name: project_name.clone(),
+            project_path: project_path.clone(),
+            summaries: chat.summary.into_iter().collect(),
+            timestamp: chat.timestamp,
+            git_branch: None,
+            commit: None,
+            branch_is_live: false,
+            worktree: None,
+            recap: None,
+            usage: chat.usage,
+            data_root: None,
+            session_file: Some(path),
+        };
+        let existing = by_id.get(&session.session_id);
+        if existing.is_none_or(|e| session.timestamp > e.timestamp) {
+            by_id.insert(session.session_id.clone(), session);
         }
     }
 
@@ -117,11 +152,24 @@ fn resolve_project(dir_name: &str, path_map: &HashMap<String, String>) -> (Strin
     (String::new(), short)
 }
 
-/// Parse a Gemini session JSON file into a Session.
+/// Format version of the per-file chat cache; bump when `parse_chat` or
+/// `ChatMeta` changes.
+const CHAT_CACHE_VERSION: u32 = 1;
+
+/// The project-independent part of a session, as read from one chat file.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct ChatMeta {
+    session_id: String,
+    timestamp: i64,
+    summary: Option<String>,
+    usage: Vec<ModelUsage>,
+}
+
+/// Parse a Gemini session JSON file.
 ///
 /// For large files (> 64 KB) we read a capped slice and fall back to
 /// field extraction if the JSON is truncated.
-fn parse_session(path: &Path, project_path: &str, project_name: &str) -> Option<Session> {
+fn parse_chat(path: &Path) -> Option<ChatMeta> {
     let content = read_capped(path)?;
 
     // Try full JSON parse first (works for files ≤ 64 KB)
@@ -133,17 +181,13 @@ fn parse_session(path: &Path, project_path: &str, project_name: &str) -> Option<
             .and_then(|v| v.as_str())?;
         let timestamp = parse_iso8601_ms(timestamp_str)?;
         let summary = extract_summary(&json);
+        let usage = read_usage(content.as_bytes());
 
-        return Some(Session {
-            agent: Agent::Gemini,
+        return Some(ChatMeta {
             session_id,
-            project_name: project_name.to_string(),
-            project_path: project_path.to_string(),
-            summaries: summary.into_iter().collect(),
             timestamp,
-            git_branch: None,
-            worktree: None,
-            recap: None,
+            summary,
+            usage,
         });
     }
 
@@ -155,20 +199,69 @@ fn parse_session(path: &Path, project_path: &str, project_name: &str) -> Option<
         .or_else(|| extract_str_field(&content, "startTime"))?;
     let timestamp = parse_iso8601_ms(&timestamp_str)?;
     let summary = extract_summary_partial(&content);
+    // Token counts are spread over the whole file; stream it through a
+    // typed parser that skips everything else.
+    let usage = fs::File::open(path)
+        .map(|f| read_usage(std::io::BufReader::new(f)))
+        .unwrap_or_default();
 
-    Some(Session {
-        agent: Agent::Gemini,
+    Some(ChatMeta {
         session_id,
-        project_name: project_name.to_string(),
-        project_path: project_path.to_string(),
-        summaries: summary.into_iter().collect(),
         timestamp,
-        git_branch: None,
-        worktree: None,
-        recap: None,
+        summary,
+        usage,
     })
 }
 
+#[derive(serde::Deserialize)]
+struct UsageFile {
+    #[serde(default)]
+    messages: Vec<UsageMessage>,
+}
+
+#[derive(serde::Deserialize)]
+struct UsageMessage {
+    model: Option<String>,
+    tokens: Option<UsageTokens>,
+}
+
+#[derive(serde::Deserialize)]
+struct UsageTokens {
+    #[serde(default)]
+    input: u64,
+    #[serde(default)]
+    output: u64,
+    #[serde(default)]
+    cached: u64,
+    #[serde(default)]
+    thoughts: u64,
+    #[serde(default)]
+    tool: u64,
+}
+
+/// Sum the per-message `tokens` blocks by model. Gemini counts cached
+/// prompt tokens inside `input`; thoughts are billed as output.
+fn read_usage<R: Read>(reader: R) -> Vec<ModelUsage> {
+    let Ok(file) = serde_json::from_reader::<_, UsageFile>(reader) else {
+        return Vec::new();
+    };
+    let mut usage = Vec::new();
+    for msg in file.messages {
+        let Some(t) = msg.tokens else {
+            continue;
+        };
+        let tokens = TokenCounts {
+            input: t.input.saturating_sub(t.cached) + t.tool,
+            output: t.output + t.thoughts,
+            cache_read: t.cached,
+            cache_write: 0,
+        };
+        let model = msg.model.unwrap_or_else(|| "gemini".to_string());
+        usage::accumulate(&mut usage, &model, &tokens);
+    }
+    usage
+}
+
 /// Read up to MAX_FILE_BYTES from a file.
 fn read_capped(path: &Path) -> Option<String> {
     let mut file = fs::File::open(path).ok()?;
@@ -274,3 +367,92 @@ fn sha256_hex(data: &[u8]) -> String {
     hasher.update(data);
     format!("{:x}", hasher.finalize())
 }
+
+/// Locate the chat file for `session_id`. File names embed the first eight
+/// characters of the id, which narrows the candidates before the header is
+/// checked. When the session was migrated between a hash dir and a named dir
+/// the most recently modified copy wins, matching `scan`'s dedup.
+pub(crate) fn session_file(gemini_dir: &Path, session_id: &str) -> Option<PathBuf> {
+    let short_id = crate::scanner::char_prefix(session_id, 8);
+    let mut best: Option<(std::time::SystemTime, PathBuf)> = None;
+    for entry in fs::read_dir(gemini_dir.join("tmp")).ok()?.flatten() {
+        let Ok(chats) = fs::read_dir(entry.path().join("chats")) else {
+            continue;
+        };
+        for chat in chats.flatten() {
+            let fname = chat.file_name().to_string_lossy().to_string();
+            if !fname.starts_with("session-") || !fname.ends_with(".json") {
+                continue;
+            }
+            if !fname.contains(&short_id) {
+                continue;
+            }
+            let path = chat.path();
+            let matches = read_capped(&path)
+                .and_then(|c| extract_str_field(&c, "sessionId"))
+                .is_some_and(|id| id == session_id);
+            if !matches {
+                continue;
+            }
+            let mtime = chat
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            if best.as_ref().is_none_or(|(t, _)| mtime > *t) {
+                best = Some((mtime, path));
+            }
+        }
+    }
+    best.map(|(_, p)| p)
+}
+
+/// Load the conversation from the session JSON. Gemini writes one JSON
+/// document per session rather than JSONL, so the file is decoded in a single
+/// buffered pass; message conversion is still deferred to the consumer.
+pub fn transcript(session: &Session) -> Result<MessageStream, AgfError> {
+    #[derive(serde::Deserialize)]
+    struct ChatFile {
+        #[serde(default)]
+        messages: Vec<serde_json::Value>,
+    }
+
+    let gemini_dir = crate::config::session_root(session)?;
+    let path = session_file(&gemini_dir, &session.session_id)
+        .ok_or_else(|| AgfError::SessionNotFound(session.session_id.clone()))?;
+    let file = fs::File::open(&path)?;
+    let chat: ChatFile = serde_json::from_reader(std::io::BufReader::new(file))?;
+    Ok(Box::new(
+        chat.messages
+            .into_iter()
+            .flat_map(|msg| transcript_messages(&msg)),
+    ))
+}
+
+fn transcript_messages(msg: &serde_json::Value) -> Vec<Message> {
+    let ts = parse_timestamp(msg.get("timestamp"));
+    let role = match msg.get("type").and_then(|v| v.as_str()) {
+        Some("user") => Role::User,
+        Some("gemini") | Some("model") => Role::Assistant,
+        _ => return Vec::new(),
+    };
+    let mut out = Vec::new();
+    let text = msg.get("content").map(content_text).unwrap_or_default();
+    if !text.trim().is_empty() {
+        out.push(Message::new(role, text, ts));
+    }
+    if let Some(calls) = msg.get("toolCalls").and_then(|v| v.as_array()) {
+        for call in calls {
+            let name = call
+                .get("displayName")
+                .or_else(|| call.get("name"))
+                .and_then(|n| n.as_str())
+                .unwrap_or("tool");
+            let input = call.get("args").map(tool_input_preview).unwrap_or_default();
+            out.push(Message::tool(name, input, ts));
+            if let Some(display) = call.get("resultDisplay").and_then(|r| r.as_str()) {
+                out.push(Message::tool_result(display, ts));
+            }
+        }
+    }
+    out
+}
@@ -1,12 +1,18 @@
-use rusqlite::Connection;
+use std::path::Path;
+
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::Value;
 
 use crate::error::AgfError;
 use crate::model::{Agent, Session};
+use crate::transcript::{
+    content_text, parse_timestamp, tool_input_preview, Message, MessageStream, Role,
+};
 
 use super::truncate;
 
-pub fn scan() -> Result<Vec<Session>, AgfError> {
-    let db_path = crate::config::kiro_data_dir()?.join("data.sqlite3");
+pub fn scan(data_dir: &Path) -> Result<Vec<Session>, AgfError> {
+    let db_path = data_dir.join("data.sqlite3");
 
     if !db_path.exists() {
         return Ok(Vec::new());
@@ -58,8 +64,13 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
                 summaries,
                 timestamp: updated_at,
                 git_branch: None,
+                commit: None,
+                branch_is_live: false,
                 worktree: None,
                 recap: None,
+                usage: Vec::new(),
+                data_root: None,
+                session_file: None,
             }
         })
         .collect();
@@ -67,6 +78,49 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
     Ok(sessions)
 }
 
+/// `kiro-cli chat --resume` can't be given a conversation id: it resumes
+/// the most recently updated conversation of the current directory. Make
+/// `session` that conversation by bumping its `updated_at`, after checking
+/// it really belongs to the directory agf will launch in.
+pub fn make_latest(session: &Session) -> Result<(), AgfError> {
+    let db_path = crate::config::session_root(session)?.join("data.sqlite3");
+    let conn = Connection::open_with_flags(&db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    conn.busy_timeout(std::time::Duration::from_millis(500))?;
+
+    let key: Option<String> = conn
+        .query_row(
+            "SELECT key FROM conversations_v2 WHERE conversation_id = ?1",
+            [&session.session_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(key) = key else {
+        return Err(AgfError::SessionNotFound(session.session_id.clone()));
+    };
+    if key != session.project_path {
+        return Err(AgfError::SessionNotFound(format!(
+            "{} is stored under {key}",
+            session.session_id
+        )));
+    }
+
+    let (newest_id, newest): (String, i64) = conn.query_row(
+        "SELECT conversation_id, updated_at FROM conversations_v2 \
+         WHERE key = ?1 ORDER BY updated_at DESC LIMIT 1",
+        [&key],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    if newest_id == session.session_id {
+        return Ok(());
+    }
+    let now = chrono::Utc::now().timestamp_millis().max(newest + 1);
+    conn.execute(
+        "UPDATE conversations_v2 SET updated_at = ?1 WHERE key = ?2 AND conversation_id = ?3",
+        rusqlite::params![now, key, session.session_id],
+    )?;
+    Ok(())
+}
+
 /// Extract the first user message from the conversation JSON as a summary.
 fn extract_summary(value: &str) -> Option<String> {
     let parsed: serde_json::Value = serde_json::from_str(value).ok()?;
@@ -95,3 +149,173 @@ fn extract_summary(value: &str) -> Option<String> {
     }
     None
 }
+
+/// Load the conversation stored in the session's `conversations_v2` row.
+/// The whole conversation is one JSON value, so it is decoded once and
+/// converted lazily.
+pub fn transcript(session: &Session) -> Result<MessageStream, AgfError> {
+    let db_path = crate::config::session_root(session)?.join("data.sqlite3");
+    if !db_path.exists() {
+        return Err(AgfError::SessionNotFound(session.session_id.clone()));
+    }
+    let conn = Connection::open_with_flags(
+        &db_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    let value: String = conn
+        .query_row(
+            "SELECT value FROM conversations_v2 WHERE conversation_id = ?1",
+            [&session.session_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AgfError::SessionNotFound(session.session_id.clone())
+            }
+            other => other.into(),
+        })?;
+    let parsed: Value = serde_json::from_str(&value)?;
+
+    // Older rows use a flat `messages` list; current Kiro CLI writes
+    // `history` as `{user, assistant}` pairs (or 2-element arrays).
+    let entries: Vec<Value> = match parsed.get("messages").and_then(|v| v.as_array()) {
+        Some(messages) => messages.clone(),
+        None => parsed
+            .get("history")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default(),
+    };
+    Ok(Box::new(
+        entries
+            .into_iter()
+            .flat_map(|entry| transcript_messages(&entry)),
+    ))
+}
+
+fn transcript_messages(entry: &Value) -> Vec<Message> {
+    // Flat `messages` format: {role, content}.
+    if let Some(role) = entry.get("role").and_then(|r| r.as_str()) {
+        let role = if role == "user" {
+            Role::User
+        } else {
+            Role::Assistant
+        };
+        let text = entry.get("content").map(content_text).unwrap_or_default();
+        return if text.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![Message::new(role, text, None)]
+        };
+    }
+
+    let (user, assistant) = match entry {
+        Value::Array(pair) => (pair.first(), pair.get(1)),
+        _ => (entry.get("user"), entry.get("assistant")),
+    };
+    let mut out = Vec::new();
+    if let Some(user) = user {
+        let ts = parse_timestamp(user.get("timestamp"));
+        let content = user.get("content");
+        if let Some(prompt) = content
+            .and_then(|c| c.get("Prompt"))
+            .and_then(|p| p.get("prompt"))
+            .and_then(|p| p.as_str())
+        {
+            out.push(Message::new(Role::User, prompt, ts));
+        }
+        if let Some(results) = content
+            .and_then(|c| c.get("ToolUseResults"))
+            .and_then(|r| r.get("tool_use_results"))
+            .and_then(|r| r.as_array())
+        {
+            for result in results {
+                let text = result
+                    .get("content")
+                    .and_then(|c| c.as_array())
+                    .map(|parts| {
+                        parts
+                            .iter()
+                            .map(|p| match p.get("Text").and_then(|t| t.as_str()) {
+                                Some(t) => t.to_string(),
+                                None => p.get("Json").map(|j| j.to_string()).unwrap_or_default(),
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default();
+                out.push(Message::tool_result(text, ts));
+            }
+        }
+    }
+    if let Some(assistant) = assistant {
+        if let Some(resp) = assistant.get("Response") {
+            let text = resp.get("content").and_then(|c| c.as_str()).unwrap_or("");
+            if !text.trim().is_empty() {
+                out.push(Message::new(Role::Assistant, text, None));
+            }
+        }
+        if let Some(tool_use) = assistant.get("ToolUse") {
+            let text = tool_use
+                .get("content")
+                .and_then(|c| c.as_str())
+                .unwrap_or("");
+            if !text.trim().is_empty() {
+                out.push(Message::new(Role::Assistant, text, None));
+            }
+            for call in tool_use
+                .get("tool_uses")
+                .and_then(|t| t.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let name = call.get("name").and_then(|n| n.as_str()).unwrap_or("tool");
+                let input = call.get("args").map(tool_input_preview).unwrap_or_default();
+                out.push(Message::tool(name, input, None));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn make_latest_bumps_only_a_conversation_of_the_same_directory() {
+        let dir = std::env::temp_dir().join(format!("agf-kiro-{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        let conn = Connection::open(dir.join("data.sqlite3")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE conversations_v2 (key TEXT, conversation_id TEXT, value TEXT, \
+                 created_at INTEGER, updated_at INTEGER, PRIMARY KEY (key, conversation_id));
+             INSERT INTO conversations_v2 VALUES
+                 ('/p', 'old', '{}', 1, 100),
+                 ('/p', 'new', '{}', 1, 200),
+                 ('/q', 'other', '{}', 1, 300);",
+        )
+        .unwrap();
+        let session = |id: &str, path: &str| Session {
+            project_name: "p".to_string(),
+            project_path: path.to_string(),
+            data_root: Some(dir.clone()),
+            ..Session::stub(Agent::Kiro, id)
+        };
+
+        make_latest(&session("old", "/p")).unwrap();
+        let newest: String = conn
+            .query_row(
+                "SELECT conversation_id FROM conversations_v2 WHERE key = '/p' \
+                 ORDER BY updated_at DESC LIMIT 1",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(newest, "old");
+        // Launching in another directory would resume something else.
+        assert!(make_latest(&session("other", "/p")).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
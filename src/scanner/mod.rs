@@ -1,15 +1,64 @@
-use std::thread;
-
-use crate::model::Session;
-
 pub mod claude;
 pub mod codex;
 pub mod cursor_agent;
+pub mod declarative;
+pub mod file_cache;
 pub mod gemini;
 pub mod kiro;
 pub mod opencode;
 pub mod pi;
 
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::AgfError;
+use crate::model::{Agent, Session};
+
+/// Scan one data root of `agent` (see `config::data_roots`), tagging every
+/// session with it so transcripts, deletes and resumes go back to the same
+/// directory. Custom agents have no roots and are scanned whole.
+pub fn scan_root(agent: Agent, root: Option<&Path>) -> Result<Vec<Session>, AgfError> {
+    let mut sessions = match root {
+        Some(root) => scan_agent_root(agent, root)?,
+        None => match agent {
+            Agent::Custom(c) => c.scan()?,
+            _ => Vec::new(),
+        },
+    };
+    fill_branches(&mut sessions);
+    Ok(sessions)
+}
+
+fn scan_agent_root(agent: Agent, root: &Path) -> Result<Vec<Session>, AgfError> {
+    let mut sessions = match agent {
+        Agent::ClaudeCode => claude::scan(root)?,
+        Agent::Codex => codex::scan(root)?,
+        Agent::OpenCode => opencode::scan(root)?,
+        Agent::Pi => pi::scan(root)?,
+        Agent::Kiro => kiro::scan(root)?,
+        Agent::CursorAgent => cursor_agent::scan(root)?,
+        Agent::Gemini => gemini::scan(root)?,
+        Agent::Custom(c) => c.scan()?,
+    };
+    for session in &mut sessions {
+        session.data_root = Some(root.to_path_buf());
+    }
+    Ok(sessions)
+}
+
+/// Give sessions whose agent doesn't record a branch the one their project
+/// has checked out (`git::head`), read once per project, and mark it as such.
+fn fill_branches(sessions: &mut [Session]) {
+    let mut heads: HashMap<String, Option<String>> = HashMap::new();
+    for session in sessions.iter_mut().filter(|s| s.git_branch.is_none()) {
+        session.git_branch = heads
+            .entry(session.project_path.clone())
+            .or_insert_with(|| crate::git::head(&session.project_path).and_then(|h| h.branch))
+            .clone();
+        session.branch_is_live = session.git_branch.is_some();
+    }
+}
+
 /// Truncate a string to `max` chars, appending "..." if truncated.
 pub(crate) fn truncate(s: &str, max: usize) -> String {
     if s.chars().count() <= max {
@@ -39,6 +88,21 @@ pub(crate) fn read_first_line(path: &std::path::Path) -> Option<String> {
     }
 }
 
+/// Stream a JSONL file one parsed line at a time. Blank and malformed lines
+/// are skipped; nothing beyond the current line is held in memory.
+pub(crate) fn jsonl_values(
+    path: &std::path::Path,
+) -> std::io::Result<impl Iterator<Item = serde_json::Value> + Send> {
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+    let file = File::open(path)?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(&line).ok()))
+}
+
 /// Char-safe slice: take first `max` chars (never panics on UTF-8 boundaries).
 pub(crate) fn char_prefix(s: &str, max: usize) -> String {
     s.chars().take(max).collect()
@@ -130,33 +194,6 @@ fn trim_partial_first_line(bytes: &[u8]) -> String {
     }
 }
 
-pub fn scan_all() -> Vec<Session> {
-    let handles = vec![
-        thread::spawn(|| claude::scan().unwrap_or_default()),
-        thread::spawn(|| codex::scan().unwrap_or_default()),
-        thread::spawn(|| opencode::scan().unwrap_or_default()),
-        thread::spawn(|| pi::scan().unwrap_or_default()),
-        thread::spawn(|| kiro::scan().unwrap_or_default()),
-        thread::spawn(|| cursor_agent::scan().unwrap_or_default()),
-        thread::spawn(|| gemini::scan().unwrap_or_default()),
-    ];
-    let mut sessions: Vec<Session> = handles
-        .into_iter()
-        .flat_map(|h| match h.join() {
-            Ok(v) => v,
-            Err(_) => {
-                if std::env::var("AGF_DEBUG").is_ok() {
-                    eprintln!("[agf] scanner thread panicked");
-                }
-                Vec::new()
-            }
-        })
-        .collect();
-
-    sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
-    sessions
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
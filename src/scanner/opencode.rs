@@ -1,10 +1,15 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
 use rusqlite::Connection;
+use serde_json::Value;
 
 use crate::error::AgfError;
 use crate::model::{Agent, Session};
+use crate::transcript::{tool_input_preview, Message, MessageStream, Role};
 
-pub fn scan() -> Result<Vec<Session>, AgfError> {
-    let db_path = crate::config::opencode_data_dir()?.join("opencode.db");
+pub fn scan(data_dir: &Path) -> Result<Vec<Session>, AgfError> {
+    let db_path = data_dir.join("opencode.db");
 
     if !db_path.exists() {
         return Ok(Vec::new());
@@ -68,11 +73,129 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
                 summaries,
                 timestamp: time_updated,
                 git_branch: None,
+                commit: None,
+                branch_is_live: false,
                 worktree: None,
                 recap: None,
+                usage: Vec::new(),
+                data_root: None,
+                session_file: None,
             }
         })
         .collect();
 
     Ok(sessions)
 }
+
+/// Rows fetched per query when streaming a transcript.
+const PAGE_SIZE: i64 = 200;
+
+/// Stream the conversation from the `message` / `part` tables. Rows are
+/// fetched a page at a time so long sessions don't load into memory at once.
+pub fn transcript(session: &Session) -> Result<MessageStream, AgfError> {
+    let db_path = crate::config::session_root(session)?.join("opencode.db");
+    if !db_path.exists() {
+        return Err(AgfError::SessionNotFound(session.session_id.clone()));
+    }
+    let conn = Connection::open_with_flags(
+        &db_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    Ok(Box::new(PartPages {
+        conn,
+        session_id: session.session_id.clone(),
+        offset: 0,
+        buf: VecDeque::new(),
+        done: false,
+    }))
+}
+
+struct PartPages {
+    conn: Connection,
+    session_id: String,
+    offset: i64,
+    buf: VecDeque<Message>,
+    done: bool,
+}
+
+impl PartPages {
+    fn fetch_page(&mut self) -> Result<(), rusqlite::Error> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT m.data, p.data, p.time_created \
+             FROM part p JOIN message m ON m.id = p.message_id \
+             WHERE p.session_id = ?1 \
+             ORDER BY p.time_created, p.id \
+             LIMIT ?2 OFFSET ?3",
+        )?;
+        let rows: Vec<(String, String, i64)> = stmt
+            .query_map(
+                rusqlite::params![self.session_id, PAGE_SIZE, self.offset],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2).unwrap_or(0))),
+            )?
+            .filter_map(|r| r.ok())
+            .collect();
+        self.offset += rows.len() as i64;
+        if (rows.len() as i64) < PAGE_SIZE {
+            self.done = true;
+        }
+        for (message_data, part_data, time_created) in rows {
+            self.buf
+                .extend(part_messages(&message_data, &part_data, time_created));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for PartPages {
+    type Item = Message;
+
+    fn next(&mut self) -> Option<Message> {
+        while self.buf.is_empty() && !self.done {
+            if self.fetch_page().is_err() {
+                self.done = true;
+            }
+        }
+        self.buf.pop_front()
+    }
+}
+
+fn part_messages(message_data: &str, part_data: &str, time_created: i64) -> Vec<Message> {
+    let (Ok(message), Ok(part)) = (
+        serde_json::from_str::<Value>(message_data),
+        serde_json::from_str::<Value>(part_data),
+    ) else {
+        return Vec::new();
+    };
+    let ts = Some(time_created).filter(|t| *t > 0);
+    match part.get("type").and_then(|t| t.as_str()) {
+        Some("text") => {
+            if part.get("synthetic").and_then(|s| s.as_bool()) == Some(true) {
+                return Vec::new();
+            }
+            let role = match message.get("role").and_then(|r| r.as_str()) {
+                Some("user") => Role::User,
+                _ => Role::Assistant,
+            };
+            let text = part.get("text").and_then(|t| t.as_str()).unwrap_or("");
+            if text.trim().is_empty() {
+                Vec::new()
+            } else {
+                vec![Message::new(role, text, ts)]
+            }
+        }
+        Some("tool") => {
+            let name = part.get("tool").and_then(|t| t.as_str()).unwrap_or("tool");
+            let state = part.get("state");
+            let input = state
+                .and_then(|s| s.get("input"))
+                .map(tool_input_preview)
+                .unwrap_or_default();
+            let mut out = vec![Message::tool(name, input, ts)];
+            if let Some(output) = state.and_then(|s| s.get("output")).and_then(|o| o.as_str()) {
+                out.push(Message::tool_result(output, ts));
+            }
+            out
+        }
+        _ => Vec::new(),
+    }
+}
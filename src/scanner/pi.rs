@@ -1,11 +1,15 @@
-use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
+use serde_json::Value;
 use walkdir::WalkDir;
 
 use crate::error::AgfError;
 use crate::model::{Agent, Session};
-use crate::scanner::read_first_line;
+use crate::scanner::{jsonl_values, read_first_line};
+use crate::transcript::{
+    content_text, parse_timestamp, tool_input_preview, Message, MessageStream, Role,
+};
 
 #[derive(Deserialize)]
 struct PiSessionHeader {
@@ -16,8 +20,8 @@ struct PiSessionHeader {
     cwd: Option<String>,
 }
 
-pub fn scan() -> Result<Vec<Session>, AgfError> {
-    let sessions_dir = crate::config::pi_sessions_dir()?;
+pub fn scan(agent_dir: &Path) -> Result<Vec<Session>, AgfError> {
+    let sessions_dir = agent_dir.join("sessions");
     if !sessions_dir.exists() {
         return Ok(Vec::new());
     }
@@ -87,16 +91,102 @@ pub fn scan() -> Result<Vec<Session>, AgfError> {
             summaries: Vec::new(),
             timestamp,
             git_branch: None,
+            commit: None,
+            branch_is_live: false,
             worktree: None,
             recap: None,
+            usage: Vec::new(),
+            data_root: None,
+            session_file: Some(path.to_path_buf()),
         });
     }
 
-    // Sort by timestamp desc, keep only the most recent session per project
-    // (pi --resume only resumes the latest session for a directory)
+    // Every session is listed: agf resumes them by file (`pi --session`).
     sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
-    let mut seen = HashSet::new();
-    sessions.retain(|s| seen.insert(s.project_path.clone()));
 
     Ok(sessions)
 }
+
+/// Locate the session JSONL whose `session` header carries `session_id`.
+pub(crate) fn session_file(sessions_dir: &Path, session_id: &str) -> Option<PathBuf> {
+    WalkDir::new(sessions_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| p.is_file() && p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .find(|p| session_id_of(p).as_deref() == Some(session_id))
+}
+
+/// Id in the `session` header of a session file.
+pub(crate) fn session_id_of(path: &Path) -> Option<String> {
+    read_first_line(path)
+        .and_then(|line| serde_json::from_str::<PiSessionHeader>(line.trim()).ok())
+        .filter(|h| h.entry_type.as_deref() == Some("session"))
+        .and_then(|h| h.id)
+}
+
+/// Stream the conversation from the session JSONL. Entries are
+/// `{"type":"message","message":{"role":...,"content":[...]}}` lines after
+/// the `session` header.
+pub fn transcript(session: &Session) -> Result<MessageStream, AgfError> {
+    let path = match session.session_file.clone().filter(|p| p.is_file()) {
+        Some(path) => path,
+        None => {
+            let sessions_dir = crate::config::session_root(session)?.join("sessions");
+            session_file(&sessions_dir, &session.session_id)
+                .ok_or_else(|| AgfError::SessionNotFound(session.session_id.clone()))?
+        }
+    };
+    Ok(Box::new(
+        jsonl_values(&path)?.flat_map(|val| transcript_messages(&val)),
+    ))
+}
+
+fn transcript_messages(val: &Value) -> Vec<Message> {
+    if val.get("type").and_then(|t| t.as_str()) != Some("message") {
+        return Vec::new();
+    }
+    let Some(msg) = val.get("message") else {
+        return Vec::new();
+    };
+    let ts =
+        parse_timestamp(val.get("timestamp")).or_else(|| parse_timestamp(msg.get("timestamp")));
+    let content = msg.get("content").unwrap_or(&Value::Null);
+
+    match msg.get("role").and_then(|r| r.as_str()) {
+        Some("user") => {
+            let text = content_text(content);
+            if text.trim().is_empty() {
+                Vec::new()
+            } else {
+                vec![Message::new(Role::User, text, ts)]
+            }
+        }
+        Some("assistant") => {
+            let Some(blocks) = content.as_array() else {
+                return vec![Message::new(Role::Assistant, content_text(content), ts)];
+            };
+            blocks
+                .iter()
+                .filter_map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                    Some("text") => block
+                        .get("text")
+                        .and_then(|t| t.as_str())
+                        .filter(|t| !t.trim().is_empty())
+                        .map(|t| Message::new(Role::Assistant, t, ts)),
+                    Some("toolCall") => {
+                        let name = block.get("name").and_then(|n| n.as_str()).unwrap_or("tool");
+                        let input = block
+                            .get("arguments")
+                            .map(tool_input_preview)
+                            .unwrap_or_default();
+                        Some(Message::tool(name, input, ts))
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+        Some("toolResult") => vec![Message::tool_result(content_text(content), ts)],
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,564 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+use crate::cache;
+use crate::model::{Agent, Session};
+use crate::scanner::file_cache::FileStamp;
+use crate::transcript::{self, Message, Role};
+
+const INDEX_VERSION: u32 = 2;
+/// Shorter words are too common to narrow anything down; the substring
+/// check on candidates still honours them.
+const MIN_TERM_CHARS: usize = 2;
+/// Long tokens (hashes, base64 blobs) are indexed by their prefix only.
+const MAX_TERM_CHARS: usize = 48;
+/// Characters of context kept on each side of a match in a snippet.
+const SNIPPET_CONTEXT: usize = 40;
+const SNIPPET_CHARS: usize = 120;
+
+/// Inverted index over transcript words, persisted next to the session cache.
+///
+/// Documents are keyed by (agent, session id) and fingerprinted with the
+/// stamp of the session's transcript file, or its last-activity timestamp
+/// for agents that keep sessions in a shared database: a session whose
+/// fingerprint hasn't moved keeps its postings, anything new or changed is
+/// re-read on the next `update`. The index only narrows the candidate set — every hit is
+/// confirmed against the transcript itself, so it can never return a session
+/// that doesn't actually contain the pattern.
+#[derive(Serialize, Deserialize, Default)]
+pub struct SearchIndex {
+    version: u32,
+    docs: Vec<IndexedDoc>,
+    postings: HashMap<String, Vec<u32>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedDoc {
+    agent: String,
+    session_id: String,
+    timestamp: i64,
+    #[serde(default)]
+    stamp: Option<FileStamp>, // of `Session::session_file`, when it has one
+}
+
+/// A session as it stands now, to compare an indexed document against.
+/// Stamps are taken before transcripts are read, so a write that lands
+/// mid-read shows up as a change next time.
+fn fingerprint(session: &Session) -> (i64, Option<FileStamp>) {
+    let stamp = session.session_file.as_deref().and_then(FileStamp::of);
+    (session.timestamp, stamp)
+}
+
+impl IndexedDoc {
+    /// A file-backed session changed when its file did, whatever its
+    /// timestamp says; others when their timestamp moved.
+    fn is_current(&self, (timestamp, stamp): (i64, Option<FileStamp>)) -> bool {
+        match stamp {
+            Some(stamp) => self.stamp == Some(stamp),
+            None => self.stamp.is_none() && self.timestamp == timestamp,
+        }
+    }
+}
+
+pub fn index_path() -> PathBuf {
+    cache::cache_path().with_file_name("search-index.json")
+}
+
+impl SearchIndex {
+    /// Load the on-disk index. A missing, unreadable or outdated file yields an
+    /// empty index, which the next `update` rebuilds from scratch.
+    pub fn load() -> Self {
+        let empty = Self {
+            version: INDEX_VERSION,
+            ..Self::default()
+        };
+        let Ok(content) = fs::read_to_string(index_path()) else {
+            return empty;
+        };
+        match serde_json::from_str::<SearchIndex>(&content) {
+            Ok(index) if index.version == INDEX_VERSION => index,
+            Ok(index) => {
+                if std::env::var("AGF_DEBUG").is_ok() {
+                    eprintln!(
+                        "[agf] search index version {} != {} → reindexing",
+                        index.version, INDEX_VERSION
+                    );
+                }
+                empty
+            }
+            Err(e) => {
+                if std::env::var("AGF_DEBUG").is_ok() {
+                    eprintln!("[agf] search index parse failed: {e} → reindexing");
+                }
+                empty
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let path = index_path();
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string(self) {
+            let _ = cache::write_atomic(&path, &json);
+        }
+    }
+
+    /// Bring the index in line with `sessions`, the complete session list:
+    /// drop documents whose session changed since it was indexed or is gone,
+    /// then read and index every session not yet covered. Returns the number
+    /// of sessions (re)indexed.
+    pub fn update(&mut self, sessions: &[Session]) -> usize {
+        let current: HashMap<(Agent, &str), (i64, Option<FileStamp>)> = sessions
+            .par_iter()
+            .map(|s| ((s.agent, s.session_id.as_str()), fingerprint(s)))
+            .collect();
+
+        let mut remap: Vec<Option<u32>> = Vec::with_capacity(self.docs.len());
+        let mut kept: Vec<IndexedDoc> = Vec::with_capacity(self.docs.len());
+        let mut known: HashSet<(Agent, String)> = HashSet::new();
+        for doc in self.docs.drain(..) {
+            let Some(agent) = cache::agent_from_str(&doc.agent) else {
+                remap.push(None);
+                continue;
+            };
+            let now = current.get(&(agent, doc.session_id.as_str()));
+            if !now.is_some_and(|&now| doc.is_current(now)) {
+                remap.push(None);
+            } else {
+                remap.push(Some(kept.len() as u32));
+                known.insert((agent, doc.session_id.clone()));
+                kept.push(doc);
+            }
+        }
+        if kept.len() != remap.len() {
+            for ids in self.postings.values_mut() {
+                *ids = ids.iter().filter_map(|&id| remap[id as usize]).collect();
+            }
+            self.postings.retain(|_, ids| !ids.is_empty());
+        }
+        self.docs = kept;
+
+        let fresh: Vec<&Session> = sessions
+            .iter()
+            .filter(|s| !known.contains(&(s.agent, s.session_id.clone())))
+            .collect();
+        let term_sets: Vec<HashSet<String>> = fresh.par_iter().map(|s| session_terms(s)).collect();
+
+        for (session, terms) in fresh.iter().zip(term_sets) {
+            let id = self.docs.len() as u32;
+            let (timestamp, stamp) = current[&(session.agent, session.session_id.as_str())];
+            self.docs.push(IndexedDoc {
+                agent: cache::agent_to_str(session.agent).to_string(),
+                session_id: session.session_id.clone(),
+                timestamp,
+                stamp,
+            });
+            for term in terms {
+                self.postings.entry(term).or_default().push(id);
+            }
+        }
+        fresh.len()
+    }
+
+    /// `update`, then persist the index if anything was re-read. Returns the
+    /// number of sessions (re)indexed.
+    pub fn refresh(&mut self, sessions: &[Session]) -> usize {
+        let reindexed = self.update(sessions);
+        if reindexed > 0 || !index_path().exists() {
+            self.save();
+        }
+        if std::env::var("AGF_DEBUG").is_ok() {
+            eprintln!(
+                "[agf] search index: {} sessions, {} reindexed",
+                self.docs.len(),
+                reindexed
+            );
+        }
+        reindexed
+    }
+
+    /// Sessions that may contain `pattern`. Each query word matches any
+    /// indexed word containing it, so partial words still find their session.
+    ///
+    /// Known limit: that takes a pass over the whole vocabulary per query
+    /// word. A sorted-term prefix range would be cheaper but would miss words
+    /// that only occur inside longer ones (`config` in `reconfigure`), which
+    /// the transcript check can't win back.
+    pub fn candidates(&self, pattern: &str) -> HashSet<(Agent, String)> {
+        let mut words = Vec::new();
+        tokenize(pattern, |w| words.push(w));
+        words.retain(|w| w.chars().count() <= MAX_TERM_CHARS);
+
+        let mut ids: Option<HashSet<u32>> = None;
+        for word in &words {
+            let matching: HashSet<u32> = self
+                .postings
+                .iter()
+                .filter(|(term, _)| term.contains(word.as_str()))
+                .flat_map(|(_, ids)| ids.iter().copied())
+                .collect();
+            ids = Some(match ids {
+                Some(prev) => prev.intersection(&matching).copied().collect(),
+                None => matching,
+            });
+        }
+
+        let doc_key = |doc: &IndexedDoc| {
+            cache::agent_from_str(&doc.agent).map(|agent| (agent, doc.session_id.clone()))
+        };
+        match ids {
+            Some(ids) => ids
+                .into_iter()
+                .filter_map(|id| self.docs.get(id as usize).and_then(doc_key))
+                .collect(),
+            None => self.docs.iter().filter_map(doc_key).collect(),
+        }
+    }
+}
+
+/// Every distinct indexable word in a session's transcript.
+fn session_terms(session: &Session) -> HashSet<String> {
+    let mut terms = HashSet::new();
+    let Ok(stream) = transcript::open(session) else {
+        return terms;
+    };
+    for msg in stream {
+        tokenize(&msg.text, |w| {
+            terms.insert(w);
+        });
+    }
+    terms
+}
+
+/// Split `text` into lowercase words. Runs of alphanumerics form one word;
+/// wide (CJK) characters are emitted one by one since those scripts don't
+/// separate words with spaces.
+fn tokenize(text: &str, mut emit: impl FnMut(String)) {
+    let mut word = String::new();
+    let mut len = 0usize;
+    let flush = |word: &mut String, len: &mut usize, emit: &mut dyn FnMut(String)| {
+        if *len >= MIN_TERM_CHARS {
+            emit(std::mem::take(word));
+        } else {
+            word.clear();
+        }
+        *len = 0;
+    };
+    for ch in text.chars() {
+        if ch.is_alphanumeric() && ch.width() == Some(2) {
+            flush(&mut word, &mut len, &mut emit);
+            emit(ch.to_lowercase().collect());
+        } else if ch.is_alphanumeric() || ch == '_' {
+            if len < MAX_TERM_CHARS {
+                word.extend(ch.to_lowercase());
+            }
+            len += 1;
+        } else {
+            flush(&mut word, &mut len, &mut emit);
+        }
+    }
+    flush(&mut word, &mut len, &mut emit);
+}
+
+/// One matching line from a transcript, cut down to a snippet around the match.
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub role: Role,
+    pub timestamp: Option<i64>,
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+}
+
+/// All confirmed hits for one session.
+#[derive(Debug, Clone)]
+pub struct SessionMatches {
+    /// Index into the `sessions` slice passed to [`search`].
+    pub index: usize,
+    /// The first few hits, in transcript order.
+    pub hits: Vec<Hit>,
+    /// Number of matching lines in the whole transcript.
+    pub total: usize,
+}
+
+/// Full-text search across `sessions`. Refreshes and persists the index, then
+/// confirms each candidate by re-reading its transcript. Results keep the
+/// order of `sessions`; at most `max_hits` snippets are kept per session.
+pub fn search(sessions: &[Session], pattern: &str, max_hits: usize) -> Vec<SessionMatches> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+
+    // The index covers every session, not just the ones searched, so that
+    // documents of deleted sessions can be dropped without throwing away
+    // those a filtered search left out.
+    let mut all = cache::load_sessions(&cache::SessionFilter::default());
+    let stored: HashSet<(Agent, &str)> = all
+        .iter()
+        .map(|s| (s.agent, s.session_id.as_str()))
+        .collect();
+    let unstored: Vec<Session> = sessions
+        .iter()
+        .filter(|s| !stored.contains(&(s.agent, s.session_id.as_str())))
+        .cloned()
+        .collect();
+    all.extend(unstored);
+
+    let mut index = SearchIndex::load();
+    index.refresh(&all);
+    confirm(sessions, &index.candidates(pattern), pattern, max_hits)
+}
+
+/// The `candidates` among `sessions` whose transcript really contains
+/// `pattern`, as `search` returns them.
+pub fn confirm(
+    sessions: &[Session],
+    candidates: &HashSet<(Agent, String)>,
+    pattern: &str,
+    max_hits: usize,
+) -> Vec<SessionMatches> {
+    let needle = pattern.trim().to_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let picked: Vec<usize> = sessions
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| candidates.contains(&(s.agent, s.session_id.clone())))
+        .map(|(i, _)| i)
+        .collect();
+
+    picked
+        .par_iter()
+        .filter_map(|&i| {
+            let stream = transcript::open(&sessions[i]).ok()?;
+            let (hits, total) = find_hits(stream, &needle, max_hits);
+            (total > 0).then_some(SessionMatches {
+                index: i,
+                hits,
+                total,
+            })
+        })
+        .collect()
+}
+
+/// Scan messages for lines containing `needle` (already lowercased).
+fn find_hits(
+    messages: impl Iterator<Item = Message>,
+    needle: &str,
+    max_hits: usize,
+) -> (Vec<Hit>, usize) {
+    let mut hits = Vec::new();
+    let mut total = 0;
+    for msg in messages {
+        for line in msg.text.lines() {
+            let line = line.trim_end();
+            let lower = line.to_lowercase();
+            let Some(pos) = lower.find(needle) else {
+                continue;
+            };
+            total += 1;
+            if hits.len() < max_hits {
+                hits.push(make_hit(&msg, line, &lower, pos, needle.len()));
+            }
+        }
+    }
+    (hits, total)
+}
+
+fn make_hit(msg: &Message, line: &str, lower: &str, pos: usize, len: usize) -> Hit {
+    // Byte offsets from the lowercased copy only carry over when lowercasing
+    // didn't change the byte layout (true for virtually all text).
+    let aligned =
+        lower.len() == line.len() && line.is_char_boundary(pos) && line.is_char_boundary(pos + len);
+    let (before, matched, after) = if aligned {
+        (&line[..pos], &line[pos..pos + len], &line[pos + len..])
+    } else {
+        ("", "", line)
+    };
+
+    let before = before.trim_start();
+    let before_chars = before.chars().count();
+    let before = if before_chars > SNIPPET_CONTEXT {
+        let tail: String = before
+            .chars()
+            .skip(before_chars - SNIPPET_CONTEXT)
+            .collect();
+        format!("…{tail}")
+    } else {
+        before.to_string()
+    };
+    let room = SNIPPET_CHARS.saturating_sub(before.chars().count() + matched.chars().count());
+    let after = if after.chars().count() > room {
+        let head: String = after.chars().take(room).collect();
+        format!("{head}…")
+    } else {
+        after.to_string()
+    };
+
+    Hit {
+        role: msg.role,
+        timestamp: msg.timestamp,
+        before,
+        matched: matched.to_string(),
+        after,
+    }
+}
+
+/// The `agf grep --json` array.
+pub fn matches_json(sessions: &[Session], matches: &[SessionMatches]) -> serde_json::Value {
+    let items: Vec<serde_json::Value> = matches
+        .iter()
+        .map(|m| {
+            let s = &sessions[m.index];
+            serde_json::json!({
+                "agent": s.agent.to_string(),
+                "session_id": s.session_id,
+                "project_name": s.project_name,
+                "project_path": s.project_path,
+                "timestamp": s.timestamp,
+                "matches": m.total,
+                "hits": m.hits.iter().map(|h| serde_json::json!({
+                    "role": h.role.label(),
+                    "timestamp": h.timestamp,
+                    "text": format!("{}{}{}", h.before, h.matched, h.after),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(items)
+}
+
+/// Print `agf grep` results: one header per session followed by its snippets.
+pub fn print_matches(sessions: &[Session], matches: &[SessionMatches], json: bool) {
+    use std::io::Write;
+
+    if json {
+        if let Ok(json) = serde_json::to_string_pretty(&matches_json(sessions, matches)) {
+            println!("{json}");
+        }
+        return;
+    }
+
+    let a = crate::list::Ansi::new();
+    let mut out = std::io::stdout().lock();
+    for m in matches {
+        let s = &sessions[m.index];
+        let (r, g, b) = s.agent.color();
+        let count = if m.total == 1 {
+            "1 match".to_string()
+        } else {
+            format!("{} matches", m.total)
+        };
+        let _ = writeln!(
+            out,
+            "{}  {}  {}  {}",
+            a.bold_rgb(r, g, b, &s.agent.to_string()),
+            a.bold(&s.project_name),
+            a.dim(&format!("{} · {}", s.time_display(), count)),
+            a.dim(&s.session_id),
+        );
+        for h in &m.hits {
+            let _ = writeln!(
+                out,
+                "  {}  {}{}{}",
+                a.dim(&format!("{:>9}", h.role.label())),
+                h.before,
+                a.bold_rgb(250, 204, 21, &h.matched),
+                h.after,
+            );
+        }
+        if m.total > m.hits.len() {
+            let _ = writeln!(
+                out,
+                "  {}",
+                a.dim(&format!("{:>9}  … {} more", "", m.total - m.hits.len()))
+            );
+        }
+        let _ = writeln!(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        tokenize(text, |w| out.push(w));
+        out
+    }
+
+    #[test]
+    fn tokenize_splits_words_and_cjk() {
+        assert_eq!(
+            words("Fix the RaceCondition in a_b!"),
+            ["fix", "the", "racecondition", "in", "a_b"]
+        );
+        assert_eq!(words("세션 검색"), ["세", "션", "검", "색"]);
+    }
+
+    #[test]
+    fn update_is_incremental_and_candidates_match_substrings() {
+        let dir = std::env::temp_dir().join(format!("agf-search-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (changed, kept) = (dir.join("changed.jsonl"), dir.join("kept.jsonl"));
+        std::fs::write(&changed, "{}\n").unwrap();
+        std::fs::write(&kept, "{}\n").unwrap();
+
+        let mut index = SearchIndex::default();
+        let doc = |id: &str, timestamp: i64, file: Option<&PathBuf>| IndexedDoc {
+            agent: "Codex".to_string(),
+            session_id: id.to_string(),
+            timestamp,
+            stamp: file.and_then(|f| FileStamp::of(f)),
+        };
+        index.docs.push(doc("changed", 9, Some(&changed)));
+        index.docs.push(doc("elsewhere", 2, None));
+        index.docs.push(doc("kept", 5, Some(&kept)));
+        index.postings.insert("tokenizer".to_string(), vec![0, 2]);
+        index.postings.insert("obsolete".to_string(), vec![0]);
+        index.postings.insert("unrelated".to_string(), vec![1]);
+
+        let session = |id: &str, timestamp: i64, file: &PathBuf| Session {
+            timestamp,
+            session_file: Some(file.clone()),
+            ..Session::stub(Agent::Codex, id)
+        };
+        // "changed" grew under the same timestamp and gets re-read (there is
+        // no Codex store here, so it comes back with no terms); "kept" only
+        // moved its timestamp; "elsewhere" is gone and is dropped.
+        std::fs::write(&changed, "{}\n{}\n").unwrap();
+        let sessions = [session("changed", 9, &changed), session("kept", 7, &kept)];
+        assert_eq!(index.update(&sessions), 1);
+        assert_eq!(index.docs.len(), 2);
+        assert!(!index.postings.contains_key("obsolete"));
+        assert!(!index.postings.contains_key("unrelated"));
+        assert_eq!(index.postings["tokenizer"], vec![0]);
+
+        let found = index.candidates("Token");
+        assert!(found.contains(&(Agent::Codex, "kept".to_string())));
+        assert!(index.candidates("obsolete").is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn make_hit_centers_snippet_on_match() {
+        let msg = Message::new(Role::User, "", None);
+        let line = format!("{} needle tail", "x".repeat(80));
+        let lower = line.to_lowercase();
+        let pos = lower.find("needle").unwrap();
+        let hit = make_hit(&msg, &line, &lower, pos, 6);
+        assert_eq!(hit.matched, "needle");
+        assert!(hit.before.starts_with('…'));
+        assert_eq!(hit.after, " tail");
+    }
+}
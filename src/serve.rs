@@ -0,0 +1,685 @@
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::live::Live;
+use crate::model::Session;
+use crate::query::Query;
+use crate::{action, cache, delete, export, list, search, stats, transcript};
+
+/// Invalid JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// Valid JSON, but not a request.
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// The request was fine but carrying it out failed (unreadable transcript,
+/// trash not writable, …).
+pub const FAILED: i64 = -32000;
+/// No session has the given id.
+pub const NO_SESSION: i64 = -32001;
+/// The method isn't offered on this endpoint.
+pub const FORBIDDEN: i64 = -32002;
+
+/// Largest HTTP request body accepted.
+const MAX_BODY: usize = 1024 * 1024;
+
+/// Where `agf serve` listens.
+pub enum Endpoint {
+    /// A Unix socket at this path (the default).
+    Socket(PathBuf),
+    /// `127.0.0.1` on this port.
+    Port(u16),
+}
+
+/// `$XDG_RUNTIME_DIR/agf.sock`, or `agf.sock` next to the session cache
+/// where there is no runtime directory.
+pub fn default_socket() -> PathBuf {
+    dirs::runtime_dir()
+        .or_else(|| cache::cache_path().parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join("agf.sock")
+}
+
+/// What clients of an endpoint may do. Any local user can connect to a TCP
+/// port, so only the owner-only Unix socket may delete. Web pages can reach
+/// the port too, under a rebound DNS name, so HTTP requests on it must be
+/// addressed to `127.0.0.1:<port>` or `localhost:<port>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Full,
+    ReadOnly { port: u16 },
+}
+
+/// The in-memory session index requests are answered from.
+#[derive(Default)]
+pub struct Index {
+    pub sessions: Vec<Session>,
+    pub live: Live,
+    pub pinned: Vec<String>,
+    /// Full-text index over every session, kept warm between requests;
+    /// `None` until it has been loaded.
+    pub search: Arc<Mutex<Option<search::SearchIndex>>>,
+}
+
+/// A failed call, sent back as a JSON-RPC error.
+#[derive(Debug)]
+pub struct CallError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl CallError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Load the index and keep it fresh every `interval` seconds, as
+/// `agf watch` does.
+pub fn start(interval: Duration) -> Arc<RwLock<Index>> {
+    let sessions = cache::load_sessions(&cache::SessionFilter::default());
+    let live = Live::detect(&sessions);
+    // Loaded in the background; searches until then read it from disk.
+    let search_index = Arc::new(Mutex::new(None));
+    let loaded = Arc::clone(&search_index);
+    let all = sessions.clone();
+    std::thread::spawn(move || {
+        let mut index = search::SearchIndex::load();
+        index.refresh(&all);
+        *lock(&loaded) = Some(index);
+    });
+    let index = Arc::new(RwLock::new(Index {
+        sessions,
+        live,
+        pinned: crate::settings::Settings::load().pinned_sessions,
+        search: Arc::clone(&search_index),
+    }));
+
+    let refreshed = Arc::clone(&index);
+    crate::watch::spawn_refresh(interval, move |sessions, live| {
+        if let Some(search_index) = lock(&search_index).as_mut() {
+            search_index.refresh(&sessions);
+        }
+        let mut index = refreshed.write().unwrap_or_else(PoisonError::into_inner);
+        index.sessions = sessions;
+        index.live = live;
+        true
+    });
+    index
+}
+
+/// Serve the index on `endpoint`, refreshed every `interval` seconds, until
+/// killed.
+pub fn run(endpoint: Endpoint, interval: u64) -> anyhow::Result<()> {
+    let index = start(Duration::from_secs(interval));
+    match endpoint {
+        Endpoint::Port(port) => {
+            let listener = TcpListener::bind(("127.0.0.1", port))?;
+            eprintln!("agf: serving on http://127.0.0.1:{port}");
+            for stream in listener.incoming().flatten() {
+                let reader = stream.try_clone()?;
+                spawn_connection(&index, Access::ReadOnly { port }, reader, stream);
+            }
+        }
+        Endpoint::Socket(path) => serve_socket(&index, &path)?,
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn serve_socket(index: &Arc<RwLock<Index>>, path: &Path) -> anyhow::Result<()> {
+    use std::os::unix::net::UnixStream;
+
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            anyhow::bail!("another agf serve is listening on {}", path.display());
+        }
+        // Left behind by a server that didn't shut down cleanly.
+        std::fs::remove_file(path)?;
+    }
+    let listener = bind_private(path)?;
+    eprintln!("agf: serving on {}", path.display());
+    for stream in listener.incoming().flatten() {
+        let reader = stream.try_clone()?;
+        spawn_connection(index, Access::Full, reader, stream);
+    }
+    Ok(())
+}
+
+/// Bind a Unix socket at `path` that only the owner can connect to
+/// (`delete` is on the menu). It is bound in a fresh 0700 directory, made
+/// 0600 and only then moved into place, so it is never reachable with the
+/// umask's looser permissions.
+#[cfg(unix)]
+fn bind_private(path: &Path) -> anyhow::Result<std::os::unix::net::UnixListener> {
+    use std::fs;
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let dir = path
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let private = dir.join(format!(".agf-serve-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&private);
+    fs::DirBuilder::new().mode(0o700).create(&private)?;
+    let staged = private.join("agf.sock");
+    let bound = std::os::unix::net::UnixListener::bind(&staged).and_then(|listener| {
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = fs::remove_dir_all(&private);
+    Ok(bound?)
+}
+
+#[cfg(not(unix))]
+fn serve_socket(_index: &Arc<RwLock<Index>>, _path: &Path) -> anyhow::Result<()> {
+    anyhow::bail!("Unix sockets aren't available here; use `agf serve --port <n>`")
+}
+
+fn spawn_connection<R, W>(index: &Arc<RwLock<Index>>, access: Access, reader: R, writer: W)
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let index = Arc::clone(index);
+    std::thread::spawn(move || {
+        let _ = serve_connection(&index, access, reader, writer);
+    });
+}
+
+/// Answer newline-delimited JSON-RPC 2.0 requests until the client hangs
+/// up, or a single HTTP request when the first line is an HTTP request line.
+fn serve_connection(
+    index: &RwLock<Index>,
+    access: Access,
+    reader: impl Read,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let request = line.trim();
+        if request.is_empty() {
+            continue;
+        }
+        if let Some(path) = http_path(request) {
+            return serve_http(index, access, &path, &mut reader, &mut writer);
+        }
+        writeln!(writer, "{}", rpc(index, access, request))?;
+        writer.flush()?;
+    }
+}
+
+/// The path of an HTTP request line such as `POST /list HTTP/1.1`.
+fn http_path(line: &str) -> Option<String> {
+    let mut parts = line.split_whitespace();
+    let verb = parts.next()?;
+    let path = parts.next()?;
+    let version = parts.next()?;
+    (matches!(verb, "GET" | "POST") && version.starts_with("HTTP/")).then(|| path.to_string())
+}
+
+/// `POST /<method>` with the params as the body answers with the bare
+/// result; `POST /` takes and returns a JSON-RPC envelope.
+fn serve_http(
+    index: &RwLock<Index>,
+    access: Access,
+    path: &str,
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let mut length = 0;
+    let mut from_browser = false;
+    let mut host = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value.trim().parse().unwrap_or(0);
+        }
+        // Web pages may post to localhost too; none of them get an answer.
+        if name.eq_ignore_ascii_case("origin") {
+            from_browser = true;
+        }
+        if name.eq_ignore_ascii_case("host") {
+            host = Some(value.trim().to_ascii_lowercase());
+        }
+    }
+    if length > MAX_BODY {
+        return respond(writer, 413, &json!({ "error": "request body too large" }));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    if from_browser {
+        return respond(
+            writer,
+            403,
+            &json!({ "error": "cross-origin requests are refused" }),
+        );
+    }
+    if let Access::ReadOnly { port } = access {
+        let local = [format!("127.0.0.1:{port}"), format!("localhost:{port}")];
+        if !host.is_some_and(|h| local.contains(&h)) {
+            return respond(
+                writer,
+                403,
+                &json!({ "error": format!("Host must be 127.0.0.1:{port} or localhost:{port}") }),
+            );
+        }
+    }
+    if path == "/" {
+        return respond(
+            writer,
+            200,
+            &rpc(index, access, &String::from_utf8_lossy(&body)),
+        );
+    }
+    let params = if body.iter().all(u8::is_ascii_whitespace) {
+        Value::Null
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(params) => params,
+            Err(e) => return respond(writer, 400, &json!({ "error": e.to_string() })),
+        }
+    };
+    match call_as(index, access, path.trim_start_matches('/'), &params) {
+        Ok(result) => respond(writer, 200, &result),
+        Err(e) => {
+            let status = match e.code {
+                METHOD_NOT_FOUND | NO_SESSION => 404,
+                INVALID_PARAMS => 400,
+                FORBIDDEN => 403,
+                _ => 500,
+            };
+            respond(writer, status, &json!({ "error": e.message }))
+        }
+    }
+}
+
+fn respond(writer: &mut impl Write, status: u16, body: &Value) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    write!(
+        writer,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    writer.flush()
+}
+
+/// Answer one JSON-RPC 2.0 request.
+fn rpc(index: &RwLock<Index>, access: Access, request: &str) -> Value {
+    let request: Value = match serde_json::from_str(request) {
+        Ok(request) => request,
+        Err(e) => return rpc_error(Value::Null, &CallError::new(PARSE_ERROR, e.to_string())),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return rpc_error(id, &CallError::new(INVALID_REQUEST, "missing method"));
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    match call_as(index, access, method, &params) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => rpc_error(id, &e),
+    }
+}
+
+pub fn rpc_error(id: Value, error: &CallError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+/// `call`, refusing what `access` doesn't allow.
+fn call_as(
+    index: &RwLock<Index>,
+    access: Access,
+    method: &str,
+    params: &Value,
+) -> Result<Value, CallError> {
+    if access != Access::Full && method.replace('-', "_") == "delete" {
+        return Err(CallError::new(
+            FORBIDDEN,
+            "delete is only offered on the Unix socket",
+        ));
+    }
+    call(index, method, params)
+}
+
+/// Run `method` with `params` (an object, or null for no params) against
+/// the index. Hyphens in the method name are read as underscores, so
+/// `get-transcript` and `get_transcript` are the same call.
+pub fn call(index: &RwLock<Index>, method: &str, params: &Value) -> Result<Value, CallError> {
+    match method.replace('-', "_").as_str() {
+        "list" => list(&read(index), params),
+        "search" => search(&read(index), params),
+        "get_transcript" => get_transcript(&read(index), params),
+        "stats" => Ok(stats::stats_json(&scoped(&read(index), params))),
+        "resume_command" => resume_command(&read(index), params),
+        "delete" => delete(index, params),
+        _ => Err(CallError::new(
+            METHOD_NOT_FOUND,
+            format!("unknown method '{method}'"),
+        )),
+    }
+}
+
+pub fn read(index: &RwLock<Index>) -> RwLockReadGuard<'_, Index> {
+    index.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn string<'a>(params: &'a Value, key: &str) -> Option<&'a str> {
+    params.get(key).and_then(Value::as_str)
+}
+
+fn number(params: &Value, key: &str, default: usize) -> usize {
+    params
+        .get(key)
+        .and_then(Value::as_u64)
+        .map_or(default, |n| usize::try_from(n).unwrap_or(usize::MAX))
+}
+
+/// Sessions of the agent named by `params.agent` in the project named by
+/// `params.project`; either left out means any.
+fn scoped(index: &Index, params: &Value) -> Vec<Session> {
+    let agent = string(params, "agent");
+    let project = string(params, "project");
+    index
+        .sessions
+        .iter()
+        .filter(|s| agent.is_none_or(|name| s.agent.matches_name(name)))
+        .filter(|s| project.is_none_or(|project| in_project(s, project)))
+        .cloned()
+        .collect()
+}
+
+/// Whether `session` ran in `project`: a directory (the session's own or
+/// one above it) or a project name.
+pub fn in_project(session: &Session, project: &str) -> bool {
+    let project = project.trim_end_matches('/');
+    if project.is_empty() {
+        return false;
+    }
+    if project.contains('/') {
+        return Path::new(&session.project_path).starts_with(project);
+    }
+    session.project_name.eq_ignore_ascii_case(project)
+}
+
+/// `list {query?, agent?, project?, limit?}`: sessions as `agf list --format json`
+/// prints them, best match first.
+fn list(index: &Index, params: &Value) -> Result<Value, CallError> {
+    let sessions = scoped(index, params);
+    let query = Query::parse(string(params, "query").unwrap_or(""));
+    if let Some(err) = query.error() {
+        return Err(CallError::new(INVALID_PARAMS, err));
+    }
+    let chosen: Vec<Session> = query
+        .rank(&sessions, &index.pinned)
+        .into_iter()
+        .take(number(params, "limit", 20))
+        .map(|i| sessions[i].clone())
+        .collect();
+    Ok(list::sessions_json(&chosen, &index.live))
+}
+
+/// `search {pattern, agent?, project?, limit?, hits?}`: full-text matches as
+/// `agf grep --json` prints them.
+fn search(index: &Index, params: &Value) -> Result<Value, CallError> {
+    let pattern = string(params, "pattern")
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| CallError::new(INVALID_PARAMS, "missing pattern"))?;
+    let sessions = scoped(index, params);
+    let hits = number(params, "hits", 3);
+    let candidates = lock(&index.search).as_ref().map(|i| i.candidates(pattern));
+    let mut matches = match candidates {
+        Some(candidates) => search::confirm(&sessions, &candidates, pattern, hits),
+        None => search::search(&sessions, pattern, hits),
+    };
+    matches.truncate(number(params, "limit", 20));
+    Ok(search::matches_json(&sessions, &matches))
+}
+
+/// `get_transcript {session_id}`: the session as `agf export --format json`
+/// writes it.
+fn get_transcript(index: &Index, params: &Value) -> Result<Value, CallError> {
+    let session = session(index, params)?;
+    let messages: Vec<transcript::Message> = transcript::open(session)
+        .map_err(|e| CallError::new(FAILED, e.to_string()))?
+        .collect();
+    Ok(export::transcript_json(session, &messages))
+}
+
+/// `resume_command {session_id, mode?}`: the shell command that resumes the
+/// session in the caller's terminal, plus the running agent holding it, if
+/// any. Read-only: the agent's data, tmux and zellij are left alone.
+fn resume_command(index: &Index, params: &Value) -> Result<Value, CallError> {
+    let session = session(index, params)?;
+    let flags = match string(params, "mode") {
+        Some(mode) => session.agent.mode_flags(mode).ok_or_else(|| {
+            CallError::new(
+                INVALID_PARAMS,
+                format!("{} has no mode '{mode}'", session.agent),
+            )
+        })?,
+        None => "",
+    };
+    let held_by = index.live.holder(session);
+    let fork_command = held_by
+        .and(session.agent.fork_flag())
+        .map(|fork| action::resume_here(session, &format!("{flags}{fork}")));
+    Ok(json!({
+        "session_id": session.session_id,
+        "command": action::resume_here(session, flags),
+        "held_by": held_by,
+        "fork_command": fork_command,
+    }))
+}
+
+/// `delete {session_ids, dry_run?}`: move the sessions to the trash as one
+/// batch, reported as `agf delete --json` reports it.
+fn delete(index: &RwLock<Index>, params: &Value) -> Result<Value, CallError> {
+    let ids: Vec<&str> = params
+        .get("session_ids")
+        .and_then(Value::as_array)
+        .map(|ids| ids.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    if ids.is_empty() {
+        return Err(CallError::new(INVALID_PARAMS, "missing session_ids"));
+    }
+    let dry_run = params
+        .get("dry_run")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut index = index.write().unwrap_or_else(PoisonError::into_inner);
+    let mut seen = HashSet::new();
+    let mut chosen: Vec<&Session> = Vec::new();
+    for id in ids {
+        let session = find(&index.sessions, id)
+            .ok_or_else(|| CallError::new(NO_SESSION, format!("no session '{id}'")))?;
+        if seen.insert(std::ptr::from_ref(session)) {
+            chosen.push(session);
+        }
+    }
+    if dry_run {
+        let plans = delete::plan_sessions(&chosen);
+        return Ok(delete::report_json(&chosen, &plans, &[], None, true));
+    }
+
+    let (batch, failed) = delete::delete_sessions(&chosen)
+        .map_err(|e| CallError::new(FAILED, format!("delete failed: {e}")))?;
+    let failed: Vec<(&Session, String)> = failed
+        .into_iter()
+        .map(|(s, e)| (s, e.to_string()))
+        .collect();
+    let deleted: Vec<&Session> = chosen
+        .iter()
+        .copied()
+        .filter(|s| !failed.iter().any(|(f, _)| std::ptr::eq(*f, *s)))
+        .collect();
+    let report = delete::report_json(
+        &deleted,
+        &delete::moved(&deleted, batch.as_ref()),
+        &failed,
+        batch.as_ref().map(|b| b.id.as_str()),
+        false,
+    );
+    let gone: HashSet<(crate::model::Agent, String)> = deleted
+        .iter()
+        .map(|s| (s.agent, s.session_id.clone()))
+        .collect();
+    index
+        .sessions
+        .retain(|s| !gone.contains(&(s.agent, s.session_id.clone())));
+    Ok(report)
+}
+
+/// The session `params.session_id` names.
+pub fn session<'a>(index: &'a Index, params: &Value) -> Result<&'a Session, CallError> {
+    let id = string(params, "session_id")
+        .ok_or_else(|| CallError::new(INVALID_PARAMS, "missing session_id"))?;
+    find(&index.sessions, id)
+        .ok_or_else(|| CallError::new(NO_SESSION, format!("no session '{id}'")))
+}
+
+/// An exact session id, or a prefix only one session id starts with.
+fn find<'a>(sessions: &'a [Session], id: &str) -> Option<&'a Session> {
+    if id.is_empty() {
+        return None;
+    }
+    if let Some(s) = sessions.iter().find(|s| s.session_id == id) {
+        return Some(s);
+    }
+    let mut prefixed = sessions.iter().filter(|s| s.session_id.starts_with(id));
+    match (prefixed.next(), prefixed.next()) {
+        (Some(s), None) => Some(s),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Agent;
+
+    fn session(agent: Agent, id: &str, project: &str) -> Session {
+        Session {
+            project_name: project.to_string(),
+            project_path: format!("/src/{project}"),
+            ..Session::stub(agent, id)
+        }
+    }
+
+    #[test]
+    fn answers_json_rpc_lines_and_http_requests() {
+        let index = RwLock::new(Index {
+            sessions: vec![
+                session(Agent::ClaudeCode, "abc-123", "agf"),
+                session(Agent::Codex, "abd-456", "website"),
+            ],
+            ..Index::default()
+        });
+
+        let requests = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"list","params":{"agent":"codex"}}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"get-transcript","params":{"session_id":"ab"}}"#,
+            "\n",
+            "not json\n",
+        );
+        let mut out = Vec::new();
+        serve_connection(&index, Access::Full, requests.as_bytes(), &mut out).unwrap();
+        let replies: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[0]["result"][0]["session_id"], "abd-456");
+        assert_eq!(replies[0]["result"].as_array().unwrap().len(), 1);
+        // "ab" is a prefix of both ids, so it names neither.
+        assert_eq!(replies[1]["error"]["code"], NO_SESSION);
+        assert_eq!(replies[2]["error"]["code"], PARSE_ERROR);
+
+        let body = r#"{"query":"agf"}"#;
+        let request = format!(
+            "POST /list HTTP/1.1\r\nHost: agf\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let mut out = Vec::new();
+        serve_connection(&index, Access::Full, request.as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\n"));
+        let (_, json) = out.split_once("\r\n\r\n").unwrap();
+        let result: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(result[0]["session_id"], "abc-123");
+
+        let mut out = Vec::new();
+        let request = "POST /resume HTTP/1.1\r\nContent-Length: 0\r\n\r\n";
+        serve_connection(&index, Access::Full, request.as_bytes(), &mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("HTTP/1.1 404 Not Found"));
+
+        let mut out = Vec::new();
+        let request = "POST /list HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n";
+        serve_connection(&index, Access::Full, request.as_bytes(), &mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("HTTP/1.1 413 Payload Too Large"));
+
+        // Over TCP: no delete, and only requests addressed to the port.
+        let tcp = |request: &str| {
+            let mut out = Vec::new();
+            let access = Access::ReadOnly { port: 7777 };
+            serve_connection(&index, access, request.as_bytes(), &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let delete = "POST /delete HTTP/1.1\r\nHost: 127.0.0.1:7777\r\nContent-Length: 0\r\n\r\n";
+        assert!(tcp(delete).starts_with("HTTP/1.1 403 Forbidden"));
+        assert!(tcp(delete).contains("only offered on the Unix socket"));
+        assert!(tcp("GET /list HTTP/1.1\r\nHost: localhost:7777\r\n\r\n")
+            .starts_with("HTTP/1.1 200 OK"));
+        for host in ["Host: rebound.example:7777\r\n", ""] {
+            let request = format!("GET /list HTTP/1.1\r\n{host}\r\n");
+            assert!(tcp(&request).contains("Host must be"));
+        }
+    }
+}
@@ -1,11 +1,17 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::launch::LaunchConfig;
+use crate::plugin::PluginConfig;
+use crate::scanner::declarative::AgentConfig;
+use crate::usage::ModelPrice;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Settings {
     #[serde(default)]
-    pub sort_by: Option<String>, // "time", "name", "agent"
+    pub sort_by: Option<String>, // "time", "name", "agent", "frecency"
     #[serde(default)]
     pub max_sessions: Option<usize>, // limit number of sessions loaded
     #[serde(default = "default_summary_search_count")]
@@ -18,6 +24,16 @@ pub struct Settings {
     pub pinned_sessions: Vec<String>, // session IDs pinned to top of list
     #[serde(default)]
     pub show_recap: bool, // show Claude Code recap (away_summary) instead of last prompt
+    #[serde(default)]
+    pub prices: HashMap<String, ModelPrice>, // [prices."<model>"] overrides for cost estimates
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>, // [[plugins]] external agents
+    #[serde(default)]
+    pub agents: Vec<AgentConfig>, // [[agents]] declarative JSONL agents
+    #[serde(default)]
+    pub data_dirs: HashMap<String, Vec<String>>, // [data_dirs] claude = ["~/.claude", "~/.claude-work"]
+    #[serde(default)]
+    pub launch: LaunchConfig, // [launch] resume = "tmux-window"
 }
 
 fn default_summary_search_count() -> usize {
@@ -38,6 +54,11 @@ impl Default for Settings {
             editor: None,
             pinned_sessions: Vec::new(),
             show_recap: false,
+            prices: HashMap::new(),
+            plugins: Vec::new(),
+            agents: Vec::new(),
+            data_dirs: HashMap::new(),
+            launch: LaunchConfig::default(),
         }
     }
 }
@@ -57,6 +57,28 @@ impl CommandShell {
         }
     }
 
+    /// Run `cmd` with environment variable `var` set to `value`.
+    ///
+    /// POSIX scopes it to the command with a `VAR='value' cmd` prefix.
+    /// PowerShell has no such form, so it sets `$env:VAR` first.
+    pub fn with_env(&self, var: &str, value: &str, cmd: &str) -> String {
+        match self {
+            Self::Posix => format!("{var}={} {cmd}", self.quote(value)),
+            Self::PowerShell => format!("$env:{var} = {}; {cmd}", self.quote(value)),
+        }
+    }
+
+    /// Print `msg` to stderr, then run `cmd`.
+    pub fn warn_and(&self, msg: &str, cmd: &str) -> String {
+        match self {
+            Self::Posix => format!("echo {} >&2 && {cmd}", self.quote(&format!("agf: {msg}"))),
+            Self::PowerShell => format!(
+                "Write-Warning {}; {cmd}",
+                self.quote(&format!("agf: {msg}"))
+            ),
+        }
+    }
+
     /// Build a "cd only, no follow-up" command.
     pub fn cd_only(&self, quoted_path: &str) -> String {
         match self {
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 use std::io::{self, IsTerminal, Write};
 
-use crate::model::{Agent, Session};
+use crate::model::{Agent, Session, TokenCounts};
+use crate::usage::{self, PriceTable};
 
 pub fn print_stats(sessions: &[Session], json: bool) {
     if json {
@@ -111,26 +112,20 @@ fn print_text(sessions: &[Session]) {
         );
     }
 
-    // Top projects
+    // Top repositories (sessions from subdirectories, worktrees and other
+    // clones count towards the same one)
     let _ = writeln!(out);
-    let _ = writeln!(out, "  {}", a.bold("Top Projects"));
+    let _ = writeln!(out, "  {}", a.bold("Top Repositories"));
     let _ = writeln!(out);
 
-    let mut by_project: HashMap<String, (usize, Option<Agent>)> = HashMap::new();
+    // Keyed on the identity so repos that share a short name stay apart
+    let mut by_project: HashMap<String, (String, usize, Agent)> = HashMap::new();
     for s in sessions {
-        let entry = by_project
-            .entry(s.project_name.clone())
-            .or_insert((0, None));
-        entry.0 += 1;
+        let (key, name) = crate::git::identity(s);
         // Keep first-seen agent (project color)
-        if entry.1.is_none() {
-            entry.1 = Some(s.agent);
-        }
+        by_project.entry(key).or_insert((name, 0, s.agent)).1 += 1;
     }
-    let mut project_list: Vec<(String, usize, Agent)> = by_project
-        .into_iter()
-        .map(|(name, (count, agent))| (name, count, agent.unwrap_or(Agent::ClaudeCode)))
-        .collect();
+    let mut project_list: Vec<(String, usize, Agent)> = by_project.into_values().collect();
     project_list.sort_by_key(|y| std::cmp::Reverse(y.1));
     project_list.truncate(10);
     let max_proj_count = project_list.first().map(|(_, c, _)| *c).unwrap_or(1);
@@ -212,10 +207,141 @@ fn print_text(sessions: &[Session]) {
             count,
         );
     }
+
+    // Token usage (only agents that log it contribute)
+    let prices = PriceTable::load();
+    let groups = [
+        (
+            "Tokens by Agent",
+            token_groups(sessions, &prices, TokenKey::Agent),
+        ),
+        (
+            "Tokens by Repository",
+            token_groups(sessions, &prices, TokenKey::Repo),
+        ),
+        (
+            "Tokens by Model",
+            token_groups(sessions, &prices, TokenKey::Model),
+        ),
+    ];
+    for (title, mut rows) in groups {
+        if rows.is_empty() {
+            continue;
+        }
+        rows.truncate(10);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "  {}", a.bold(title));
+        let _ = writeln!(out);
+
+        let max_total = rows.first().map(|r| r.tokens.total()).unwrap_or(1).max(1);
+        let name_len = rows
+            .iter()
+            .map(|r| r.name.chars().count())
+            .max()
+            .unwrap_or(10)
+            .min(22);
+        for row in &rows {
+            let (r, g, b) = row.agent.color();
+            let display = truncate(&row.name, name_len);
+            let total = row.tokens.total();
+            let filled = ((total * bar_width as u64) / max_total) as usize;
+            let filled = filled.max(if total > 0 { 1 } else { 0 });
+            let empty = bar_width.saturating_sub(filled);
+            let pad = name_len.saturating_sub(display.chars().count());
+            let cost = row.cost.map(usage::format_cost).unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "   {}{} {} {:>7} {} {:>8}",
+                a.bold(&display),
+                " ".repeat(pad),
+                a.bar_rgb(r, g, b, filled, empty),
+                usage::format_tokens(total),
+                a.dim(&format!(
+                    "in {:>6} out {:>6} cache {:>6}",
+                    usage::format_tokens(row.tokens.input),
+                    usage::format_tokens(row.tokens.output),
+                    usage::format_tokens(row.tokens.cache_read + row.tokens.cache_write),
+                )),
+                cost,
+            );
+        }
+    }
     let _ = writeln!(out);
 }
 
+#[derive(Clone, Copy)]
+enum TokenKey {
+    Agent,
+    Project,
+    Repo,
+    Model,
+}
+
+struct TokenRow {
+    key: String,  // what rows are grouped on: the repo identity for `Repo`
+    name: String, // label
+    agent: Agent, // color: the agent, or the first agent seen for the project/model
+    tokens: TokenCounts,
+    cost: Option<f64>,
+}
+
+/// Token totals grouped by `key`, largest first. Sessions without usage are
+/// skipped, so the result is empty when no agent recorded any.
+fn token_groups(sessions: &[Session], prices: &PriceTable, key: TokenKey) -> Vec<TokenRow> {
+    let mut rows: HashMap<String, TokenRow> = HashMap::new();
+    for s in sessions {
+        for u in &s.usage {
+            let (id, name) = match key {
+                TokenKey::Agent => (s.agent.to_string(), s.agent.to_string()),
+                TokenKey::Project => (s.project_name.clone(), s.project_name.clone()),
+                TokenKey::Repo => crate::git::identity(s),
+                TokenKey::Model => (u.model.clone(), u.model.clone()),
+            };
+            let row = rows.entry(id.clone()).or_insert(TokenRow {
+                key: id,
+                name,
+                agent: s.agent,
+                tokens: TokenCounts::default(),
+                cost: None,
+            });
+            row.tokens.add(&u.tokens);
+            if let Some(c) = prices.cost(std::slice::from_ref(u)) {
+                *row.cost.get_or_insert(0.0) += c;
+            }
+        }
+    }
+    let mut rows: Vec<TokenRow> = rows.into_values().collect();
+    rows.sort_by_key(|r| std::cmp::Reverse(r.tokens.total()));
+    rows
+}
+
+fn token_json(rows: &[TokenRow]) -> serde_json::Value {
+    let map: serde_json::Map<String, serde_json::Value> = rows
+        .iter()
+        .map(|r| {
+            (
+                r.key.clone(),
+                serde_json::json!({
+                    "input": r.tokens.input,
+                    "output": r.tokens.output,
+                    "cache_read": r.tokens.cache_read,
+                    "cache_write": r.tokens.cache_write,
+                    "cost_usd": r.cost,
+                }),
+            )
+        })
+        .collect();
+    serde_json::Value::Object(map)
+}
+
 fn print_json(sessions: &[Session]) {
+    if let Ok(s) = serde_json::to_string_pretty(&stats_json(sessions)) {
+        println!("{s}");
+    }
+}
+
+/// The `agf stats --json` object.
+pub fn stats_json(sessions: &[Session]) -> serde_json::Value {
     let mut by_agent: HashMap<String, usize> = HashMap::new();
     for s in sessions {
         *by_agent.entry(s.agent.to_string()).or_insert(0) += 1;
@@ -226,6 +352,11 @@ fn print_json(sessions: &[Session]) {
         *by_project.entry(s.project_name.clone()).or_insert(0) += 1;
     }
 
+    let mut by_repo: HashMap<String, usize> = HashMap::new();
+    for s in sessions {
+        *by_repo.entry(crate::git::identity(s).0).or_insert(0) += 1;
+    }
+
     let now = chrono::Utc::now().timestamp_millis();
     let day_ms: i64 = 86_400_000;
     let week_ms: i64 = 7 * day_ms;
@@ -249,20 +380,25 @@ fn print_json(sessions: &[Session]) {
         }
     }
 
-    let json = serde_json::json!({
+    let prices = PriceTable::load();
+    serde_json::json!({
         "total": sessions.len(),
         "by_agent": by_agent,
         "by_project": by_project,
+        "by_repo": by_repo,
         "activity": {
             "today": today,
             "this_week": this_week,
             "this_month": this_month,
             "older": older,
+        },
+        "tokens": {
+            "by_agent": token_json(&token_groups(sessions, &prices, TokenKey::Agent)),
+            "by_project": token_json(&token_groups(sessions, &prices, TokenKey::Project)),
+            "by_repo": token_json(&token_groups(sessions, &prices, TokenKey::Repo)),
+            "by_model": token_json(&token_groups(sessions, &prices, TokenKey::Model)),
         }
-    });
-    if let Ok(s) = serde_json::to_string_pretty(&json) {
-        println!("{s}");
-    }
+    })
 }
 
 fn truncate(s: &str, max: usize) -> String {
@@ -274,3 +410,31 @@ fn truncate(s: &str, max: usize) -> String {
         format!("{prefix}…")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, path: &str) -> Session {
+        Session {
+            project_name: "api".to_string(),
+            project_path: path.to_string(),
+            ..Session::stub(Agent::ClaudeCode, id)
+        }
+    }
+
+    #[test]
+    fn repos_sharing_a_name_are_counted_apart() {
+        let dir = std::env::temp_dir().join(format!("agf-stats-{}", std::process::id()));
+        let (work, home) = (dir.join("work/api"), dir.join("home/api"));
+        let sessions = [
+            session("a", &work.to_string_lossy()),
+            session("b", &work.to_string_lossy()),
+            session("c", &home.to_string_lossy()),
+        ];
+        let by_repo = &stats_json(&sessions)["by_repo"];
+        assert_eq!(by_repo.as_object().map(|m| m.len()), Some(2));
+        assert_eq!(by_repo[work.to_string_lossy().as_ref()], 2);
+        assert_eq!(by_repo[home.to_string_lossy().as_ref()], 1);
+    }
+}
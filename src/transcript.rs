@@ -0,0 +1,238 @@
+use serde_json::Value;
+
+use crate::error::AgfError;
+use crate::model::{Agent, Session};
+use crate::scanner;
+
+/// Who produced a transcript entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Assistant,
+    Tool,
+    ToolResult,
+}
+
+impl Role {
+    pub fn label(self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+            Role::ToolResult => "result",
+        }
+    }
+}
+
+/// One normalized conversation entry, independent of the agent that wrote it.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub role: Role,
+    pub text: String,
+    pub timestamp: Option<i64>, // Unix ms
+    pub tool: Option<String>,   // tool name for `Role::Tool` / `Role::ToolResult`
+}
+
+impl Message {
+    pub fn new(role: Role, text: impl Into<String>, timestamp: Option<i64>) -> Self {
+        Self {
+            role,
+            text: text.into(),
+            timestamp,
+            tool: None,
+        }
+    }
+
+    pub fn tool(name: impl Into<String>, text: impl Into<String>, timestamp: Option<i64>) -> Self {
+        Self {
+            role: Role::Tool,
+            text: text.into(),
+            timestamp,
+            tool: Some(name.into()),
+        }
+    }
+
+    pub fn tool_result(text: impl Into<String>, timestamp: Option<i64>) -> Self {
+        Self {
+            role: Role::ToolResult,
+            text: text.into(),
+            timestamp,
+            tool: None,
+        }
+    }
+}
+
+/// Lazily-produced transcript entries. JSONL stores are read line by line and
+/// SQLite stores page through rows, so callers only pay for what they pull.
+pub type MessageStream = Box<dyn Iterator<Item = Message> + Send>;
+
+/// Open the full transcript of `session` from its agent's local store.
+pub fn open(session: &Session) -> Result<MessageStream, AgfError> {
+    match session.agent {
+        Agent::ClaudeCode => scanner::claude::transcript(session),
+        Agent::Codex => scanner::codex::transcript(session),
+        Agent::OpenCode => scanner::opencode::transcript(session),
+        Agent::Pi => scanner::pi::transcript(session),
+        Agent::Kiro => scanner::kiro::transcript(session),
+        Agent::CursorAgent => scanner::cursor_agent::transcript(session),
+        Agent::Gemini => scanner::gemini::transcript(session),
+        Agent::Custom(c) => c.transcript(&session.session_id),
+    }
+}
+
+/// A transcript that is pulled from its stream on demand. The TUI keeps one
+/// of these per previewed session and asks for more as the user scrolls.
+pub struct Transcript {
+    stream: Option<MessageStream>,
+    pub messages: Vec<Message>,
+    pub error: Option<String>,
+}
+
+impl Transcript {
+    pub fn open(session: &Session) -> Self {
+        match open(session) {
+            Ok(stream) => Self {
+                stream: Some(stream),
+                messages: Vec::new(),
+                error: None,
+            },
+            Err(e) => Self {
+                stream: None,
+                messages: Vec::new(),
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    /// Pull up to `n` more messages. Returns how many were added.
+    pub fn load_more(&mut self, n: usize) -> usize {
+        let Some(stream) = self.stream.as_mut() else {
+            return 0;
+        };
+        let before = self.messages.len();
+        self.messages.extend(stream.by_ref().take(n));
+        let added = self.messages.len() - before;
+        if added < n {
+            self.stream = None;
+        }
+        added
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.stream.is_none()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shared helpers for the per-agent loaders
+// ---------------------------------------------------------------------------
+
+/// Flatten a message `content` field that is either a plain string or an
+/// array of `{ "text": ... }` blocks.
+pub(crate) fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| match p {
+                Value::String(s) => Some(s.as_str()),
+                _ => p.get("text").and_then(|t| t.as_str()),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// One-line description of a tool call's arguments. Prefers the fields that
+/// identify what the call touched (command, path, pattern, …) over dumping
+/// the whole argument object.
+pub(crate) fn tool_input_preview(input: &Value) -> String {
+    // Some agents (Codex `function_call`) encode arguments as a JSON string.
+    let parsed;
+    let input = match input {
+        Value::String(s) => match serde_json::from_str::<Value>(s) {
+            Ok(v) => {
+                parsed = v;
+                &parsed
+            }
+            Err(_) => return s.clone(),
+        },
+        other => other,
+    };
+    for key in [
+        "command",
+        "cmd",
+        "file_path",
+        "path",
+        "absolute_path",
+        "filePath",
+        "pattern",
+        "query",
+        "url",
+        "description",
+    ] {
+        match input.get(key) {
+            Some(Value::String(s)) => return s.clone(),
+            Some(Value::Array(parts)) => {
+                let joined: Vec<&str> = parts.iter().filter_map(|p| p.as_str()).collect();
+                if !joined.is_empty() {
+                    return joined.join(" ");
+                }
+            }
+            _ => {}
+        }
+    }
+    if input.is_null() {
+        return String::new();
+    }
+    input.to_string()
+}
+
+/// Parse an RFC 3339 string or a Unix-ms number into Unix milliseconds.
+pub(crate) fn parse_timestamp(value: Option<&Value>) -> Option<i64> {
+    match value? {
+        Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.timestamp_millis()),
+        Value::Number(n) => n.as_i64(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_text_flattens_string_and_blocks() {
+        assert_eq!(content_text(&serde_json::json!("hi")), "hi");
+        let blocks =
+            serde_json::json!([{"type": "text", "text": "a"}, {"type": "image"}, {"text": "b"}]);
+        assert_eq!(content_text(&blocks), "a\nb");
+    }
+
+    #[test]
+    fn tool_input_preview_prefers_identifying_fields() {
+        let input = serde_json::json!({"command": "cargo test", "timeout": 5});
+        assert_eq!(tool_input_preview(&input), "cargo test");
+        let encoded = serde_json::json!("{\"cmd\":[\"ls\",\"-la\"]}");
+        assert_eq!(tool_input_preview(&encoded), "ls -la");
+    }
+
+    #[test]
+    fn transcript_load_more_marks_completion() {
+        let mut t = Transcript {
+            stream: Some(Box::new(
+                (0..3).map(|i| Message::new(Role::User, i.to_string(), None)),
+            )),
+            messages: Vec::new(),
+            error: None,
+        };
+        assert_eq!(t.load_more(2), 2);
+        assert!(!t.is_complete());
+        assert_eq!(t.load_more(2), 1);
+        assert!(t.is_complete());
+        assert_eq!(t.load_more(2), 0);
+    }
+}
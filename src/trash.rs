@@ -0,0 +1,783 @@
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use rusqlite::types::{Value, ValueRef};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::model::Session;
+
+/// `~/.local/share/agf/trash` (platform data dir). One subdirectory per
+/// batch holds the moved files and a `manifest.json` describing how to put
+/// everything back.
+pub fn trash_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".local/share"))
+        .join("agf")
+        .join("trash")
+}
+
+/// What one deletion took away, in the order it happened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Item {
+    /// A file or directory moved into the batch under `stored`.
+    Path { original: PathBuf, stored: String },
+    /// JSONL lines cut out of a shared file such as `history.jsonl`, with
+    /// the position each had among the file's lines (empty in batches
+    /// trashed before positions were kept).
+    Lines {
+        file: PathBuf,
+        lines: Vec<String>,
+        #[serde(default)]
+        at: Vec<usize>,
+    },
+    /// SQLite rows deleted from `table`.
+    Rows {
+        db: PathBuf,
+        table: String,
+        columns: Vec<String>,
+        rows: Vec<Vec<SqlValue>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SqlValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl SqlValue {
+    /// Approximate storage size, for space-reclaimed estimates.
+    fn size(&self) -> u64 {
+        match self {
+            Self::Null => 0,
+            Self::Integer(_) | Self::Real(_) => 8,
+            Self::Text(t) => t.len() as u64,
+            Self::Blob(b) => b.len() as u64,
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for SqlValue {
+    fn from(v: ValueRef<'_>) -> Self {
+        match v {
+            ValueRef::Null => Self::Null,
+            ValueRef::Integer(i) => Self::Integer(i),
+            ValueRef::Real(f) => Self::Real(f),
+            ValueRef::Text(t) => Self::Text(String::from_utf8_lossy(t).into_owned()),
+            ValueRef::Blob(b) => Self::Blob(b.to_vec()),
+        }
+    }
+}
+
+impl From<&SqlValue> for Value {
+    fn from(v: &SqlValue) -> Self {
+        match v {
+            SqlValue::Null => Value::Null,
+            SqlValue::Integer(i) => Value::Integer(*i),
+            SqlValue::Real(f) => Value::Real(*f),
+            SqlValue::Text(t) => Value::Text(t.clone()),
+            SqlValue::Blob(b) => Value::Blob(b.clone()),
+        }
+    }
+}
+
+/// A deleted session and everything removed on its behalf.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedSession {
+    /// Store key of the agent (`cache::agent_to_str`).
+    pub agent: String,
+    pub session_id: String,
+    pub project_name: String,
+    pub project_path: String,
+    pub timestamp: i64,
+    #[serde(default)]
+    pub summaries: Vec<String>,
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    #[serde(default)]
+    pub commit: Option<String>,
+    #[serde(default)]
+    pub branch_is_live: bool,
+    #[serde(default)]
+    pub worktree: Option<String>,
+    #[serde(default)]
+    pub data_root: Option<PathBuf>,
+    #[serde(default)]
+    pub session_file: Option<PathBuf>,
+    pub items: Vec<Item>,
+    /// Disk space the items took up where they came from.
+    #[serde(default)]
+    pub bytes: u64,
+}
+
+impl TrashedSession {
+    /// An entry for `session` with nothing removed yet.
+    pub fn new(session: &Session) -> Self {
+        Self {
+            agent: crate::cache::agent_to_str(session.agent).to_string(),
+            session_id: session.session_id.clone(),
+            project_name: session.project_name.clone(),
+            project_path: session.project_path.clone(),
+            timestamp: session.timestamp,
+            summaries: session.summaries.iter().take(3).cloned().collect(),
+            git_branch: session.git_branch.clone(),
+            commit: session.commit.clone(),
+            branch_is_live: session.branch_is_live,
+            worktree: session.worktree.clone(),
+            data_root: session.data_root.clone(),
+            session_file: session.session_file.clone(),
+            items: Vec::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Display name of the agent, falling back to its store key.
+    pub fn agent_name(&self) -> String {
+        crate::cache::agent_from_str(&self.agent)
+            .map(|a| a.to_string())
+            .unwrap_or_else(|| self.agent.clone())
+    }
+
+    /// The session as it was listed, for putting it back into the TUI
+    /// without a rescan. `None` when its agent is no longer configured.
+    pub fn to_session(&self) -> Option<Session> {
+        Some(Session {
+            agent: crate::cache::agent_from_str(&self.agent)?,
+            session_id: self.session_id.clone(),
+            project_name: self.project_name.clone(),
+            project_path: self.project_path.clone(),
+            summaries: self.summaries.clone(),
+            timestamp: self.timestamp,
+            git_branch: self.git_branch.clone(),
+            commit: self.commit.clone(),
+            branch_is_live: self.branch_is_live,
+            worktree: self.worktree.clone(),
+            recap: None,
+            usage: Vec::new(),
+            data_root: self.data_root.clone(),
+            session_file: self.session_file.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub id: String,
+    pub created: i64, // Unix ms
+    pub sessions: Vec<TrashedSession>,
+}
+
+/// Lines of one session to take out of a file: (session index, key, value).
+type LineRemoval = (usize, String, String);
+
+/// One undo step: every session deleted by a single command or TUI
+/// confirmation. The manifest is written as each session is done, so a
+/// crash mid-batch still leaves the sessions before it restorable.
+///
+/// A batch made with [`Batch::plan`] records the same items without
+/// touching anything, which is how deletions are previewed.
+pub struct Batch {
+    dir: PathBuf,
+    manifest: Manifest,
+    next_file: usize,
+    dry_run: bool,
+    /// Line removals waiting for [`Batch::remove_queued_lines`], by file.
+    queued_lines: Vec<(PathBuf, Vec<LineRemoval>)>,
+}
+
+impl Batch {
+    /// A dry-run batch: see [`Batch::into_plan`].
+    pub fn plan() -> Self {
+        Self {
+            dry_run: true,
+            ..Self::new()
+        }
+    }
+
+    pub fn is_plan(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn new() -> Self {
+        let now = chrono::Local::now();
+        let id = format!("{}-{}", now.format("%Y%m%d-%H%M%S-%3f"), std::process::id());
+        Self {
+            dir: trash_dir().join(&id),
+            manifest: Manifest {
+                id,
+                created: now.timestamp_millis(),
+                sessions: Vec::new(),
+            },
+            next_file: 0,
+            dry_run: false,
+            queued_lines: Vec::new(),
+        }
+    }
+
+    /// Start recording removals for `session`, saving what the previous
+    /// session moved.
+    pub fn begin(&mut self, session: &Session) {
+        if !self.dry_run && self.manifest.sessions.iter().any(|s| !s.items.is_empty()) {
+            if let Err(e) = self.save() {
+                eprintln!("[agf] trash manifest not saved: {e}");
+            }
+        }
+        self.manifest.sessions.push(TrashedSession::new(session));
+    }
+
+    fn record(&mut self, session: usize, item: Item, bytes: u64) {
+        if let Some(s) = self.manifest.sessions.get_mut(session) {
+            s.items.push(item);
+            s.bytes += bytes;
+        }
+    }
+
+    /// Index of the session `begin` started last.
+    fn current(&self) -> usize {
+        self.manifest.sessions.len().saturating_sub(1)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(&self.manifest).map_err(io::Error::other)?;
+        let tmp = self.dir.join("manifest.json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, self.dir.join("manifest.json"))
+    }
+
+    /// Move a file or directory into the batch.
+    pub fn remove_path(&mut self, path: &Path) -> io::Result<()> {
+        let bytes = disk_size(path);
+        let stored = format!("files/{}", self.next_file);
+        self.next_file += 1;
+        if !self.dry_run {
+            let target = self.dir.join(&stored);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            move_path(path, &target)?;
+        }
+        self.record(
+            self.current(),
+            Item::Path {
+                original: path.to_path_buf(),
+                stored,
+            },
+            bytes,
+        );
+        Ok(())
+    }
+
+    /// Take the lines of a JSONL file whose `key` equals `value` out for the
+    /// current session. Removals are queued so a file shared by the batch's
+    /// sessions is rewritten once, by [`Batch::remove_queued_lines`].
+    pub fn remove_lines(&mut self, path: &Path, key: &str, value: &str) {
+        let entry = (self.current(), key.to_string(), value.to_string());
+        match self.queued_lines.iter_mut().find(|(file, _)| file == path) {
+            Some((_, queued)) => queued.push(entry),
+            None => self.queued_lines.push((path.to_path_buf(), vec![entry])),
+        }
+    }
+
+    /// Rewrite each file queued by `remove_lines` once, keeping the removed
+    /// lines and where they were (see [`rewrite`]). Positions are recorded
+    /// as if the sessions' lines had been removed one session after the
+    /// other, which is the order `restore` undoes them in. Returns the
+    /// sessions (by `begin` order) whose lines could not be removed.
+    pub fn remove_queued_lines(&mut self) -> Vec<(usize, io::Error)> {
+        let mut failed = Vec::new();
+        for (path, queued) in std::mem::take(&mut self.queued_lines) {
+            match self.remove_lines_now(&path, &queued) {
+                Ok(removed) => {
+                    for ((session, ..), removed) in queued.iter().zip(removed) {
+                        if removed.is_empty() {
+                            continue;
+                        }
+                        let bytes = removed.iter().map(|(_, l)| l.len() as u64 + 1).sum();
+                        let (at, lines) = removed.into_iter().unzip();
+                        let file = path.clone();
+                        self.record(*session, Item::Lines { file, lines, at }, bytes);
+                    }
+                }
+                Err(e) => failed.extend(
+                    queued
+                        .iter()
+                        .map(|(session, ..)| (*session, io::Error::new(e.kind(), e.to_string()))),
+                ),
+            }
+        }
+        failed
+    }
+
+    /// Removed lines of `path` with their positions, one list per entry of
+    /// `queued`.
+    fn remove_lines_now(
+        &self,
+        path: &Path,
+        queued: &[LineRemoval],
+    ) -> io::Result<Vec<Vec<(usize, String)>>> {
+        let split = |content: &str| {
+            let mut removed: Vec<Vec<(usize, String)>> = vec![Vec::new(); queued.len()];
+            let mut kept = String::new();
+            for (i, line) in content.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+                let owner = queued
+                    .iter()
+                    .position(|(_, key, value)| line_has_field_value(line.trim(), key, value));
+                match owner {
+                    Some(q) => {
+                        // Lines of earlier sessions are gone by then.
+                        let earlier: usize = removed[..q].iter().map(Vec::len).sum();
+                        removed[q].push((i - earlier, line.to_string()));
+                    }
+                    None => {
+                        kept.push_str(line);
+                        kept.push('\n');
+                    }
+                }
+            }
+            removed
+                .iter()
+                .any(|r| !r.is_empty())
+                .then_some((kept, removed))
+        };
+        let removed = if self.dry_run {
+            split(&fs::read_to_string(path)?).map(|(_, removed)| removed)
+        } else {
+            let mut file = fs::OpenOptions::new().read(true).write(true).open(path)?;
+            rewrite(&mut file, split)?
+        };
+        Ok(removed.unwrap_or_else(|| vec![Vec::new(); queued.len()]))
+    }
+
+    /// Delete the rows of `table` whose `column` equals `value`, keeping a
+    /// copy of them.
+    pub fn delete_rows(
+        &mut self,
+        db: &Path,
+        table: &str,
+        column: &str,
+        value: &str,
+    ) -> io::Result<()> {
+        let sql_err = |e: rusqlite::Error| io::Error::other(format!("SQLite error: {e}"));
+        let conn = rusqlite::Connection::open(db).map_err(sql_err)?;
+        let (columns, rows) = {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT * FROM \"{table}\" WHERE \"{column}\" = ?1"
+                ))
+                .map_err(sql_err)?;
+            let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+            let n = columns.len();
+            let rows = stmt
+                .query_map([value], |r| {
+                    (0..n)
+                        .map(|i| r.get_ref(i).map(SqlValue::from))
+                        .collect::<rusqlite::Result<Vec<_>>>()
+                })
+                .map_err(sql_err)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sql_err)?;
+            (columns, rows)
+        };
+        if rows.is_empty() {
+            return Ok(());
+        }
+        if !self.dry_run {
+            conn.execute(
+                &format!("DELETE FROM \"{table}\" WHERE \"{column}\" = ?1"),
+                [value],
+            )
+            .map_err(sql_err)?;
+        }
+        let bytes = rows.iter().flatten().map(SqlValue::size).sum();
+        self.record(
+            self.current(),
+            Item::Rows {
+                db: db.to_path_buf(),
+                table: table.to_string(),
+                columns,
+                rows,
+            },
+            bytes,
+        );
+        Ok(())
+    }
+
+    /// What a dry-run batch would have removed, one entry per `begin`.
+    pub fn into_plan(mut self) -> Vec<TrashedSession> {
+        self.remove_queued_lines();
+        self.manifest.sessions
+    }
+
+    /// Finish the batch, after [`Batch::remove_queued_lines`]. Returns its
+    /// manifest, or `None` (and leaves no trace) when nothing was removed.
+    pub fn finish(mut self) -> io::Result<Option<Manifest>> {
+        debug_assert!(self.queued_lines.is_empty(), "line removals left queued");
+        // Nothing to restore for sessions that failed before anything moved,
+        // or whose plugin deleted them for good.
+        self.manifest.sessions.retain(|s| !s.items.is_empty());
+        if self.manifest.sessions.is_empty() {
+            let _ = fs::remove_dir_all(&self.dir);
+            return Ok(None);
+        }
+        self.save()?;
+        Ok(Some(self.manifest))
+    }
+}
+
+/// Bytes of a file, or of every file below a directory.
+fn disk_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Rewrite `file` in place (same inode, since agents may hold it open for
+/// appending) with what `edit` makes of its content; `edit` returns `None`
+/// when there is nothing to change. An agent may append between the read
+/// and the write, so the file is read again right before it is truncated,
+/// and edited afresh if it changed.
+fn rewrite<T>(
+    file: &mut fs::File,
+    mut edit: impl FnMut(&str) -> Option<(String, T)>,
+) -> io::Result<Option<T>> {
+    let read = |file: &mut fs::File| -> io::Result<String> {
+        let mut content = String::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_string(&mut content)?;
+        Ok(content)
+    };
+    let mut content = read(file)?;
+    loop {
+        let Some((new_content, out)) = edit(&content) else {
+            return Ok(None);
+        };
+        let now = read(file)?;
+        if now != content {
+            content = now;
+            continue;
+        }
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(new_content.as_bytes())?;
+        return Ok(Some(out));
+    }
+}
+
+/// Check if a JSON line contains `"key": "value"`.
+fn line_has_field_value(line: &str, key: &str, value: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()
+        .and_then(|v| v.get(key)?.as_str().map(|s| s == value))
+        .unwrap_or(false)
+}
+
+/// Rename, falling back to copy + remove across filesystems.
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.is_dir() {
+        for entry in WalkDir::new(from).into_iter().filter_map(|e| e.ok()) {
+            let rel = entry.path().strip_prefix(from).unwrap_or(entry.path());
+            let dest = to.join(rel);
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&dest)?;
+            } else {
+                fs::copy(entry.path(), &dest)?;
+            }
+        }
+        fs::remove_dir_all(from)
+    } else {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    }
+}
+
+/// Every batch in the trash, newest first.
+pub fn list() -> Vec<Manifest> {
+    let Ok(entries) = fs::read_dir(trash_dir()) else {
+        return Vec::new();
+    };
+    let mut batches: Vec<Manifest> = entries
+        .flatten()
+        .filter_map(|e| fs::read_to_string(e.path().join("manifest.json")).ok())
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect();
+    batches.sort_by(|a, b| b.id.cmp(&a.id));
+    batches
+}
+
+/// Plain-text listing for `agf trash list`.
+pub fn print_list(batches: &[Manifest]) {
+    if batches.is_empty() {
+        eprintln!("Trash is empty.");
+        return;
+    }
+    for batch in batches {
+        let when = chrono::DateTime::from_timestamp_millis(batch.created)
+            .map(|dt| {
+                dt.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        println!("{}  {when}  {} session(s)", batch.id, batch.sessions.len());
+        for s in &batch.sessions {
+            println!(
+                "    {:<12} {}  {}",
+                s.agent_name(),
+                s.session_id,
+                s.project_name
+            );
+        }
+    }
+}
+
+/// Put batch `id` back where it came from, undoing the removals in reverse,
+/// last session first. Items that can't be restored (e.g. a file was recreated in
+/// the meantime) stay in the trash and are reported; the batch disappears
+/// once it is empty.
+pub fn restore(id: &str) -> io::Result<Manifest> {
+    let dir = batch_dir(id)?;
+    let json = fs::read_to_string(dir.join("manifest.json"))?;
+    let mut manifest: Manifest = serde_json::from_str(&json).map_err(io::Error::other)?;
+    let mut restored = manifest.clone();
+    let mut errors = Vec::new();
+
+    for session in manifest.sessions.iter_mut().rev() {
+        let mut left = Vec::new();
+        while let Some(item) = session.items.pop() {
+            if let Err(e) = restore_item(&dir, &item) {
+                errors.push(format!("{}: {e}", session.session_id));
+                left.push(item);
+            }
+        }
+        left.reverse();
+        session.items = left;
+    }
+    manifest.sessions.retain(|s| !s.items.is_empty());
+    let agents: Vec<_> = restored
+        .sessions
+        .iter()
+        .filter_map(|s| crate::cache::agent_from_str(&s.agent))
+        .collect();
+    crate::cache::invalidate(&agents);
+
+    if manifest.sessions.is_empty() {
+        fs::remove_dir_all(&dir)?;
+    } else {
+        let failed: Vec<&str> = manifest
+            .sessions
+            .iter()
+            .map(|s| s.session_id.as_str())
+            .collect();
+        restored
+            .sessions
+            .retain(|s| !failed.contains(&s.session_id.as_str()));
+        let json = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+        fs::write(dir.join("manifest.json"), json)?;
+        if restored.sessions.is_empty() {
+            return Err(io::Error::other(errors.join("; ")));
+        }
+        eprintln!("[agf] partially restored {id}: {}", errors.join("; "));
+    }
+    Ok(restored)
+}
+
+fn restore_item(dir: &Path, item: &Item) -> io::Result<()> {
+    match item {
+        Item::Path { original, stored } => {
+            if original.exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", original.display()),
+                ));
+            }
+            if let Some(parent) = original.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            move_path(&dir.join(stored), original)
+        }
+        Item::Lines { file, lines, at } if at.len() == lines.len() => {
+            let mut f = fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(file)?;
+            // Back at their old positions; lines written since shift down.
+            rewrite(&mut f, |content| {
+                let mut all: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+                for (line, &i) in lines.iter().zip(at) {
+                    all.insert(i.min(all.len()), line);
+                }
+                Some((all.iter().map(|l| format!("{l}\n")).collect(), ()))
+            })?;
+            Ok(())
+        }
+        Item::Lines { file, lines, .. } => {
+            let mut f = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file)?;
+            for line in lines {
+                writeln!(f, "{line}")?;
+            }
+            Ok(())
+        }
+        Item::Rows {
+            db,
+            table,
+            columns,
+            rows,
+        } => {
+            let sql_err = |e: rusqlite::Error| io::Error::other(format!("SQLite error: {e}"));
+            let conn = rusqlite::Connection::open(db).map_err(sql_err)?;
+            let cols: Vec<String> = columns.iter().map(|c| format!("\"{c}\"")).collect();
+            let placeholders = vec!["?"; columns.len()].join(", ");
+            let sql = format!(
+                "INSERT OR IGNORE INTO \"{table}\" ({}) VALUES ({placeholders})",
+                cols.join(", ")
+            );
+            for row in rows {
+                conn.execute(
+                    &sql,
+                    rusqlite::params_from_iter(row.iter().map(Value::from)),
+                )
+                .map_err(sql_err)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Permanently delete batch `id`.
+pub fn purge(id: &str) -> io::Result<()> {
+    fs::remove_dir_all(batch_dir(id)?)
+}
+
+/// Resolve a batch id or unique prefix to its directory.
+fn batch_dir(id: &str) -> io::Result<PathBuf> {
+    let matches: Vec<String> = list()
+        .into_iter()
+        .map(|m| m.id)
+        .filter(|b| b.starts_with(id))
+        .collect();
+    match matches.as_slice() {
+        [one] => Ok(trash_dir().join(one)),
+        [] => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no trash batch '{id}'"),
+        )),
+        _ => Err(io::Error::other(format!("'{id}' matches several batches"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_puts_files_lines_and_rows_back() {
+        let root = std::env::temp_dir().join(format!("agf-trash-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("proj/abc")).unwrap();
+        fs::write(root.join("proj/abc/log.jsonl"), "{}\n").unwrap();
+        let history = root.join("history.jsonl");
+        fs::write(
+            &history,
+            "{\"sessionId\":\"abc\",\"n\":1}\n{\"sessionId\":\"def\"}\n\
+             {\"sessionId\":\"abc\",\"n\":2}\n{\"sessionId\":\"xyz\"}\n",
+        )
+        .unwrap();
+        let db = root.join("data.sqlite3");
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE session (id TEXT PRIMARY KEY, body BLOB);
+             INSERT INTO session VALUES ('abc', x'00ff'), ('def', NULL);",
+        )
+        .unwrap();
+
+        let mut batch = Batch {
+            dir: root.join("trash/b1"),
+            manifest: Manifest {
+                id: "b1".into(),
+                created: 0,
+                sessions: vec![],
+            },
+            next_file: 0,
+            dry_run: false,
+            queued_lines: Vec::new(),
+        };
+        batch.begin(&Session {
+            project_name: "proj".into(),
+            project_path: "/tmp/proj".into(),
+            ..Session::stub(crate::model::Agent::ClaudeCode, "abc")
+        });
+        batch.remove_lines(&history, "sessionId", "abc");
+        // Written by the agent while the lines sat in the trash.
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&history)
+            .unwrap()
+            .write_all(b"{\"sessionId\":\"ghi\"}\n")
+            .unwrap();
+        batch.remove_path(&root.join("proj/abc")).unwrap();
+        batch.delete_rows(&db, "session", "id", "abc").unwrap();
+        // A second session's lines in the same file go in the same rewrite.
+        batch.begin(&Session::stub(crate::model::Agent::ClaudeCode, "xyz"));
+        batch.remove_lines(&history, "sessionId", "xyz");
+        assert!(batch.remove_queued_lines().is_empty());
+        let dir = batch.dir.clone();
+        batch.finish().unwrap().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&history).unwrap(),
+            "{\"sessionId\":\"def\"}\n{\"sessionId\":\"ghi\"}\n"
+        );
+        assert!(!root.join("proj/abc").exists());
+        let count = |c: &rusqlite::Connection| -> i64 {
+            c.query_row("SELECT COUNT(*) FROM session", [], |r| r.get(0))
+                .unwrap()
+        };
+        assert_eq!(count(&conn), 1);
+
+        let json = fs::read_to_string(dir.join("manifest.json")).unwrap();
+        let manifest: Manifest = serde_json::from_str(&json).unwrap();
+        for item in manifest
+            .sessions
+            .iter()
+            .rev()
+            .flat_map(|s| s.items.iter().rev())
+        {
+            restore_item(&dir, item).unwrap();
+        }
+        assert!(root.join("proj/abc/log.jsonl").is_file());
+        assert_eq!(
+            fs::read_to_string(&history).unwrap(),
+            "{\"sessionId\":\"abc\",\"n\":1}\n{\"sessionId\":\"def\"}\n\
+             {\"sessionId\":\"abc\",\"n\":2}\n{\"sessionId\":\"xyz\"}\n\
+             {\"sessionId\":\"ghi\"}\n"
+        );
+        assert_eq!(count(&conn), 2);
+        let blob: Vec<u8> = conn
+            .query_row("SELECT body FROM session WHERE id = 'abc'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(blob, [0x00, 0xff]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
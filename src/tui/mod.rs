@@ -3,11 +3,19 @@ use std::sync::mpsc::Receiver;
 
 use unicode_width::UnicodeWidthStr;
 
+mod search;
+mod transcript;
+
 use crate::action;
 use crate::cache::ScanResult;
 use crate::config::installed_agents;
+use crate::frecency::{self, Frecency};
 use crate::fuzzy::FuzzyMatcher;
+use crate::here::{Closeness, Here};
 use crate::model::{Action, Agent, Session, SortMode};
+use crate::query::Query;
+use search::SearchView;
+use transcript::TranscriptView;
 
 // Color constants
 const HIGHLIGHT_BG: slt::Color = slt::Color::Rgb(59, 59, 59);
@@ -29,15 +37,20 @@ pub enum Mode {
     AgentSelect,
     PermissionSelect,
     ResumeSelect,
+    ResumeHeld,
     DeleteConfirm,
     BulkDelete,
     Preview,
+    Transcript,
+    Search,
     Help,
 }
 
 #[derive(Debug, Clone)]
 pub struct ProjectGroup {
-    pub project_path: String,
+    /// Repository identity (remote URL or main working tree, see
+    /// `git::Repo::key`); the project path outside a repository.
+    pub repo: String,
     pub project_name: String,
     pub sessions: Vec<usize>, // indices into App.sessions
 }
@@ -76,9 +89,15 @@ pub struct App {
     pub help_selected: usize,
     pub search_textarea: slt::TextareaState,
     pub cwd: Option<String>,
+    /// Where `cwd` sits relative to each session's project; sessions of the
+    /// current project sort first.
+    here: Option<Here>,
+    /// Only show sessions of the current project (`Ctrl+R`).
+    pub scope_here: bool,
     pub agent_counts: HashMap<Agent, usize>,
     pub pinned_sessions: Vec<String>,
     pub settings: crate::settings::Settings,
+    pub prices: crate::usage::PriceTable,
     pub groups: Vec<ProjectGroup>,
     pub group_expanded: HashSet<String>,
     pub grouped_selected: usize,
@@ -93,6 +112,44 @@ pub struct App {
     /// Agents whose background scan is still running. Drives the
     /// "Refreshing N agents…" footer indicator.
     pub scanning_agents: HashSet<Agent>,
+    /// Transcript pane for the previewed session, loaded on first open and
+    /// kept while the same session stays selected.
+    pub transcript: Option<TranscriptView>,
+    /// What became of each previewed session's branch, looked up once per
+    /// session since it may run `git`.
+    pub branch_states: HashMap<String, Option<crate::git::BranchState>>,
+    /// Full-text search pane (`Ctrl+F`). Kept across visits so returning to
+    /// it shows the last results.
+    pub search: SearchView,
+    /// One-line outcome of the last action that stays in the TUI (e.g.
+    /// export), shown in the browse footer until the query changes.
+    pub notice: Option<(String, bool)>, // (message, is_error)
+    /// Trash batches deleted in this TUI session, most recent last; `Ctrl+Z`
+    /// restores them one at a time.
+    pub undo_stack: Vec<String>,
+    /// What the pending delete will touch on disk, planned on a worker
+    /// thread when the confirm dialog opens (`None` while it runs).
+    pub delete_impact: Option<crate::delete::Impact>,
+    delete_plan_rx: Option<Receiver<crate::delete::Impact>>,
+    /// The agent picker was opened by "Hand off to…" rather than "New
+    /// Session".
+    pub handoff: bool,
+    /// Running agents and the sessions they hold, refreshed every
+    /// [`LIVE_REFRESH`].
+    pub live: crate::live::Live,
+    live_checked: Option<std::time::Instant>,
+    /// Process listing running on a worker thread since `live_checked`.
+    live_rx: Option<Receiver<crate::live::Processes>>,
+    /// Pid and focus command of the last live session the action menu
+    /// looked up, so tmux isn't queried every frame.
+    focus: Option<(u32, Option<String>)>,
+    /// Resume command stopped because a running agent holds the session,
+    /// with what the confirm dialog offers instead.
+    pub held: Option<(String, action::Held)>,
+    pub held_index: usize,
+    /// Past resumes and opens, for `SortMode::Frecency`; the matcher has a
+    /// copy to break ties with.
+    frecency: Frecency,
     fuzzy: FuzzyMatcher,
 }
 
@@ -124,6 +181,10 @@ impl App {
         });
         let mut new_session_options = Vec::new();
         for agent in &sorted_agents {
+            // Custom agents without a `new_session` command can't start one.
+            if agent.new_session_cmd().is_empty() {
+                continue;
+            }
             new_session_options.push(NewSessionOption {
                 agent: *agent,
                 label: format!("{agent}"),
@@ -143,6 +204,8 @@ impl App {
             ta
         };
         let show_recap = settings.show_recap;
+        let prices = crate::usage::PriceTable::new(&settings.prices);
+        let frecency = Frecency::load();
         let mut app = Self {
             sessions,
             filtered_indices,
@@ -169,10 +232,13 @@ impl App {
             show_recap,
             help_selected: 0,
             search_textarea,
+            here: cwd.as_deref().map(Here::new),
+            scope_here: false,
             cwd,
             agent_counts,
             pinned_sessions,
             settings,
+            prices,
             groups: Vec::new(),
             group_expanded: HashSet::new(),
             grouped_selected: 0,
@@ -180,7 +246,22 @@ impl App {
             name_col_width_cache: None,
             scan_rx,
             scanning_agents,
-            fuzzy: FuzzyMatcher::new(),
+            transcript: None,
+            branch_states: HashMap::new(),
+            search: SearchView::new(),
+            notice: None,
+            undo_stack: Vec::new(),
+            delete_impact: None,
+            delete_plan_rx: None,
+            handoff: false,
+            live: crate::live::Live::default(),
+            live_checked: None,
+            live_rx: None,
+            focus: None,
+            held: None,
+            held_index: 0,
+            fuzzy: FuzzyMatcher::new().with_frecency(frecency.clone()),
+            frecency,
         };
         if !app.query.is_empty() {
             app.update_filter();
@@ -208,22 +289,29 @@ impl App {
                     .cmp(&b.agent.to_string())
                     .then(b.timestamp.cmp(&a.timestamp))
             }),
+            SortMode::Frecency => {
+                let now = chrono::Utc::now().timestamp_millis();
+                let frecency = &self.frecency;
+                self.sessions.sort_by(|a, b| {
+                    frecency
+                        .rank(b, now)
+                        .total_cmp(&frecency.rank(a, now))
+                        .then(b.timestamp.cmp(&a.timestamp))
+                });
+            }
         }
 
-        // Boost: pinned first, then $PWD matches (stable sort preserves primary order within groups)
+        // Boost: pinned first, then sessions of the current directory, the
+        // directories above it and the rest of its repository (stable sort
+        // preserves primary order within groups)
         let pinned = self.pinned_sessions.clone();
-        let cwd = self.cwd.clone();
-        self.sessions.sort_by(|a, b| {
-            let rank = |s: &Session| -> u8 {
-                if pinned.contains(&s.session_id) {
-                    0
-                } else if cwd.as_ref().is_some_and(|c| s.project_path == *c) {
-                    1
-                } else {
-                    2
-                }
-            };
-            rank(a).cmp(&rank(b))
+        let here = self.here.clone();
+        self.sessions.sort_by_cached_key(|s| {
+            (
+                !pinned.contains(&s.session_id),
+                here.as_ref()
+                    .map_or(Closeness::Elsewhere, |h| h.closeness(s)),
+            )
         });
 
         // Sessions reordered → cached column width no longer valid.
@@ -245,6 +333,7 @@ impl App {
     }
 
     pub fn update_filter(&mut self) {
+        self.notice = None;
         let agent_filtered: Vec<usize> = self
             .sessions
             .iter()
@@ -253,10 +342,20 @@ impl App {
                 Some(agent) => s.agent == agent,
                 None => true,
             })
+            .filter(|(_, s)| !self.scope_here || self.here.as_ref().is_some_and(|h| h.contains(s)))
             .map(|(i, _)| i)
             .collect();
 
-        if self.query.is_empty() {
+        // Qualifiers (`agent:`, `since:`, `-term`, …) narrow the list first;
+        // only the remaining free text goes through the fuzzy matcher.
+        let parsed = Query::parse(&self.query);
+        let agent_filtered = if parsed.has_filters() {
+            parsed.filter_indices(&self.sessions, &agent_filtered, &self.pinned_sessions)
+        } else {
+            agent_filtered
+        };
+
+        if parsed.text.is_empty() {
             self.match_positions = vec![Vec::new(); agent_filtered.len()];
             self.filtered_indices = agent_filtered;
         } else {
@@ -265,7 +364,7 @@ impl App {
             let results = self.fuzzy.filter(
                 &self.sessions,
                 &agent_filtered,
-                &self.query,
+                &parsed.text,
                 self.summary_search_count,
                 self.include_summaries,
             );
@@ -322,6 +421,49 @@ impl App {
         self.summary_offsets.insert(id, new_offset);
     }
 
+    /// Switch to the transcript pane for the selected session, reusing the
+    /// loaded view when the same session is reopened.
+    pub fn open_transcript(&mut self) {
+        let Some(session) = self.selected_session() else {
+            return;
+        };
+        if self
+            .transcript
+            .as_ref()
+            .is_none_or(|t| t.session_id != session.session_id)
+        {
+            self.transcript = Some(TranscriptView::open(session));
+        }
+        self.mode = Mode::Transcript;
+    }
+
+    /// Move the browse cursor onto `session_id`, clearing the query and agent
+    /// filter if they hide it. Returns false if the session is gone.
+    pub fn focus_session(&mut self, session_id: &str) -> bool {
+        let Some(idx) = self
+            .sessions
+            .iter()
+            .position(|s| s.session_id == session_id)
+        else {
+            return false;
+        };
+        if !self.filtered_indices.contains(&idx) {
+            self.search_textarea.lines = vec![String::new()];
+            self.search_textarea.cursor_col = 0;
+            self.query.clear();
+            self.agent_filter = None;
+            self.update_filter();
+        }
+        match self.filtered_indices.iter().position(|&i| i == idx) {
+            Some(pos) => {
+                self.selected = pos;
+                self.adjust_scroll();
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn save_settings(&self) {
         let mut settings = self.settings.clone();
         settings.summary_search_count = self.summary_search_count;
@@ -355,23 +497,18 @@ impl App {
     pub fn build_groups(&mut self) {
         let mut map: std::collections::BTreeMap<String, Vec<usize>> =
             std::collections::BTreeMap::new();
+        let mut names: HashMap<String, String> = HashMap::new();
         for &idx in &self.filtered_indices {
-            let s = &self.sessions[idx];
-            map.entry(s.project_path.clone()).or_default().push(idx);
+            let (repo, name) = crate::git::identity(&self.sessions[idx]);
+            map.entry(repo.clone()).or_default().push(idx);
+            names.entry(repo).or_insert(name);
         }
         self.groups = map
             .into_iter()
-            .map(|(path, sessions)| {
-                let name = std::path::Path::new(&path)
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-                ProjectGroup {
-                    project_path: path,
-                    project_name: name,
-                    sessions,
-                }
+            .map(|(repo, sessions)| ProjectGroup {
+                project_name: names.remove(&repo).unwrap_or_default(),
+                repo,
+                sessions,
             })
             .collect();
         // Sort groups: most recent session first
@@ -395,7 +532,7 @@ impl App {
         self.groups
             .iter()
             .map(|g| {
-                if self.group_expanded.contains(&g.project_path) {
+                if self.group_expanded.contains(&g.repo) {
                     1 + g.sessions.len()
                 } else {
                     1
@@ -412,7 +549,7 @@ impl App {
                 return Some((gi, None));
             }
             current += 1;
-            if self.group_expanded.contains(&g.project_path) {
+            if self.group_expanded.contains(&g.repo) {
                 for ci in 0..g.sessions.len() {
                     if current == row {
                         return Some((gi, Some(ci)));
@@ -500,6 +637,116 @@ impl App {
         }
     }
 
+    /// Open the delete confirmation for the bulk selection, or the selected
+    /// session when nothing is marked. Its impact is planned on a worker
+    /// thread, since that may walk every agent's data directory.
+    fn open_delete_confirm(&mut self) {
+        let targets: Vec<Session> = if self.selected_set.is_empty() {
+            self.selected_session().into_iter().cloned().collect()
+        } else {
+            let mut indices: Vec<usize> = self.selected_set.iter().copied().collect();
+            indices.sort_unstable();
+            indices
+                .iter()
+                .filter_map(|&i| self.sessions.get(i))
+                .cloned()
+                .collect()
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let targets: Vec<&Session> = targets.iter().collect();
+            let plans = crate::delete::plan_sessions(&targets);
+            let _ = tx.send(crate::delete::Impact::of(&plans));
+        });
+        self.delete_impact = None;
+        self.delete_plan_rx = Some(rx);
+        self.delete_index = 1;
+        self.mode = Mode::DeleteConfirm;
+    }
+
+    /// Collect a finished delete plan, if any. A plan still running when the
+    /// dialog is reopened is abandoned with its receiver.
+    fn poll_delete_plan(&mut self) {
+        let Some(rx) = &self.delete_plan_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(impact) => {
+                self.delete_impact = Some(impact);
+                self.delete_plan_rx = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => self.delete_plan_rx = None,
+        }
+    }
+
+    /// Delete the sessions at `indices` (into `sessions`) as one trash batch
+    /// and remember it for undo. Sessions that fail to delete stay listed.
+    fn delete_indices(&mut self, mut indices: Vec<usize>) {
+        indices.retain(|&i| i < self.sessions.len());
+        let targets: Vec<&Session> = indices.iter().map(|&i| &self.sessions[i]).collect();
+        let (batch, failed) = match crate::delete::delete_sessions(&targets) {
+            Ok((batch, failed)) => (
+                batch,
+                failed
+                    .into_iter()
+                    .map(|(s, e)| (s.session_id.clone(), e))
+                    .collect::<Vec<_>>(),
+            ),
+            Err(e) => {
+                self.notice = Some((format!("delete failed: {e}"), true));
+                return;
+            }
+        };
+
+        indices.sort_unstable_by(|a, b| b.cmp(a));
+        let mut deleted = 0;
+        for idx in indices {
+            let session = &self.sessions[idx];
+            if failed.iter().any(|(id, _)| *id == session.session_id) {
+                continue;
+            }
+            let agent = session.agent;
+            self.sessions.remove(idx);
+            decrement_agent_count(&mut self.agent_counts, agent);
+            deleted += 1;
+        }
+        self.update_filter();
+
+        let undoable = batch.is_some();
+        self.undo_stack.extend(batch.map(|m| m.id));
+        self.notice = Some(match failed.first() {
+            Some((id, e)) => (format!("could not delete {id}: {e}"), true),
+            None if undoable => (format!("deleted {deleted} · ^Z to undo"), false),
+            None => (format!("deleted {deleted}"), false),
+        });
+    }
+
+    /// Restore the most recent trash batch deleted in this TUI session.
+    fn undo_delete(&mut self) {
+        let Some(id) = self.undo_stack.pop() else {
+            self.notice = Some(("nothing to undo".to_string(), false));
+            return;
+        };
+        match crate::trash::restore(&id) {
+            Ok(manifest) => {
+                let restored: Vec<Session> = manifest
+                    .sessions
+                    .iter()
+                    .filter_map(|s| s.to_session())
+                    .collect();
+                let n = restored.len();
+                for session in restored {
+                    *self.agent_counts.entry(session.agent).or_insert(0) += 1;
+                    self.sessions.push(session);
+                }
+                self.apply_sort();
+                self.notice = Some((format!("restored {n}"), false));
+            }
+            Err(e) => self.notice = Some((format!("undo failed: {e}"), true)),
+        }
+    }
+
     /// Replace all sessions for `agent` with `new_sessions`. Caller is
     /// responsible for re-sorting / re-filtering.
     fn merge_agent_sessions(&mut self, agent: Agent, new_sessions: Vec<Session>) {
@@ -530,6 +777,47 @@ impl App {
         }
     }
 
+    /// Re-read which sessions are open in a running agent. Processes are
+    /// listed on a worker thread; the result lands on a later frame.
+    fn refresh_live(&mut self) {
+        if let Some(rx) = &self.live_rx {
+            match rx.try_recv() {
+                Ok(procs) => {
+                    self.live = procs.resolve(&self.sessions);
+                    self.live_rx = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => return,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => self.live_rx = None,
+            }
+        }
+        if self
+            .live_checked
+            .is_some_and(|t| t.elapsed() < LIVE_REFRESH)
+        {
+            return;
+        }
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(crate::live::Processes::list());
+        });
+        self.live_rx = Some(rx);
+        self.live_checked = Some(std::time::Instant::now());
+    }
+
+    /// Command that switches to the terminal already running the selected
+    /// session, when there is one and agf can reach it.
+    fn focus_cmd(&mut self) -> Option<String> {
+        let pid = self.live.pid(self.selected_session()?)?;
+        match &self.focus {
+            Some((cached, cmd)) if *cached == pid => cmd.clone(),
+            _ => {
+                let cmd = crate::live::focus_cmd(pid);
+                self.focus = Some((pid, cmd.clone()));
+                cmd
+            }
+        }
+    }
+
     pub fn run(&mut self) -> anyhow::Result<Option<String>> {
         let mut result: Option<String> = None;
         let app = self;
@@ -537,6 +825,9 @@ impl App {
             slt::RunConfig::default().title("agf").mouse(true),
             |ui: &mut slt::Context| {
                 app.ingest_scan_results();
+                app.refresh_live();
+                app.search.poll();
+                app.poll_delete_plan();
                 app.viewport_height = (ui.height() as usize).saturating_sub(4).max(1);
                 app.adjust_scroll();
                 match app.mode {
@@ -546,9 +837,12 @@ impl App {
                     Mode::AgentSelect => ui_agent_select(ui, app, &mut result),
                     Mode::PermissionSelect => ui_permission_select(ui, app, &mut result),
                     Mode::ResumeSelect => ui_resume_select(ui, app, &mut result),
+                    Mode::ResumeHeld => ui_resume_held(ui, app, &mut result),
                     Mode::DeleteConfirm => ui_delete_confirm(ui, app),
                     Mode::BulkDelete => ui_bulk_delete(ui, app),
                     Mode::Preview => ui_preview(ui, app),
+                    Mode::Transcript => ui_transcript(ui, app),
+                    Mode::Search => ui_search(ui, app),
                     Mode::Help => ui_help(ui, app),
                 }
             },
@@ -559,6 +853,8 @@ impl App {
 
 type StyledChunk = (String, slt::Style);
 
+const LIVE_REFRESH: std::time::Duration = std::time::Duration::from_secs(2);
+
 fn agent_color(agent: Agent) -> slt::Color {
     let (r, g, b) = agent.color();
     slt::Color::Rgb(r, g, b)
@@ -585,6 +881,9 @@ fn ui_browse(ui: &mut slt::Context, app: &mut App) {
     let ctrl_clear = ui.key_mod('u', slt::KeyModifiers::CONTROL);
     let ctrl_right = ui.key_mod('l', slt::KeyModifiers::CONTROL);
     let ctrl_group = ui.key_mod('g', slt::KeyModifiers::CONTROL);
+    let ctrl_find = ui.key_mod('f', slt::KeyModifiers::CONTROL);
+    let ctrl_undo = ui.key_mod('z', slt::KeyModifiers::CONTROL);
+    let ctrl_scope = ui.key_mod('r', slt::KeyModifiers::CONTROL);
     // Consume ctrl chars to prevent textarea insertion
     if ctrl_up {
         ui.consume_key('p');
@@ -609,6 +908,15 @@ fn ui_browse(ui: &mut slt::Context, app: &mut App) {
     if ctrl_group {
         ui.consume_key('g');
     }
+    if ctrl_find {
+        ui.consume_key('f');
+    }
+    if ctrl_undo {
+        ui.consume_key('z');
+    }
+    if ctrl_scope {
+        ui.consume_key('r');
+    }
 
     // Consume special chars that have bindings
     let help = ui.consume_key('?');
@@ -660,6 +968,16 @@ fn ui_browse(ui: &mut slt::Context, app: &mut App) {
         app.grouped_scroll = 0;
         app.mode = Mode::GroupedBrowse;
     }
+    if ctrl_find {
+        app.mode = Mode::Search;
+    }
+    if ctrl_undo {
+        app.undo_delete();
+    }
+    if ctrl_scope && app.here.is_some() {
+        app.scope_here = !app.scope_here;
+        app.update_filter();
+    }
     if tab {
         app.cycle_agent_filter(true);
     }
@@ -753,12 +1071,19 @@ fn ui_browse(ui: &mut slt::Context, app: &mut App) {
             }
             ui.text(format!(" sort:{}", app.sort_mode.label()))
                 .fg(GRAY_500);
+            if app.scope_here {
+                ui.text(" • this project").fg(GRAY_400);
+            }
             // Background-scan progress: appears while stale agents refresh
             // and disappears once every worker has reported in.
             if !app.scanning_agents.is_empty() {
                 ui.text(format!(" • scanning {}…", app.scanning_agents.len()))
                     .fg(YELLOW);
             }
+            if let Some((msg, is_error)) = &app.notice {
+                ui.text(format!(" • {msg}"))
+                    .fg(if *is_error { RED } else { GREEN_400 });
+            }
         });
 
         // Separator between content and statusbar
@@ -776,7 +1101,9 @@ fn ui_browse(ui: &mut slt::Context, app: &mut App) {
                     ("Enter", "select"),
                     ("^S", "sort"),
                     ("^G", "group"),
+                    ("^F", "full text"),
                     ("^D", "delete"),
+                    ("^Z", "undo"),
                     ("?", "help"),
                     ("Esc", "quit"),
                 ],
@@ -849,7 +1176,7 @@ fn ui_grouped_browse(ui: &mut slt::Context, app: &mut App) {
         if let Some((gi, child)) = app.grouped_row_at(app.grouped_selected) {
             match child {
                 None => {
-                    let path = app.groups[gi].project_path.clone();
+                    let path = app.groups[gi].repo.clone();
                     if app.group_expanded.contains(&path) {
                         app.group_expanded.remove(&path);
                     } else {
@@ -904,7 +1231,7 @@ fn ui_grouped_browse(ui: &mut slt::Context, app: &mut App) {
             let end = (app.grouped_scroll + visible).min(total_rows);
             let mut row_idx = 0;
             for group in app.groups.iter() {
-                let expanded = app.group_expanded.contains(&group.project_path);
+                let expanded = app.group_expanded.contains(&group.repo);
                 let session_count = group.sessions.len();
 
                 // Get most recent timestamp for the group
@@ -932,15 +1259,13 @@ fn ui_grouped_browse(ui: &mut slt::Context, app: &mut App) {
                     };
                     let arrow = if expanded { "\u{25be}" } else { "\u{25b8}" };
                     let display_path = if let Some(home) = dirs::home_dir() {
-                        if let Some(rest) =
-                            group.project_path.strip_prefix(home.to_str().unwrap_or(""))
-                        {
+                        if let Some(rest) = group.repo.strip_prefix(home.to_str().unwrap_or("")) {
                             format!("~{rest}")
                         } else {
-                            group.project_path.clone()
+                            group.repo.clone()
                         }
                     } else {
-                        group.project_path.clone()
+                        group.repo.clone()
                     };
 
                     let _ = ui.row(|ui| {
@@ -987,9 +1312,15 @@ fn ui_grouped_browse(ui: &mut slt::Context, app: &mut App) {
                             let is_pinned = app.pinned_sessions.contains(&s.session_id);
                             let pin_str = if is_pinned { "*" } else { " " };
 
+                            // Worktree or subdirectory of the repository
+                            let location = crate::git::repo(&s.project_path)
+                                .and_then(|r| r.location())
+                                .or_else(|| s.worktree.clone());
+
                             // Calculate available space for summary
                             let fixed_width = 5 + 1 + 12 + 2 + 16; // tree + pin + agent + gap + time
-                            let git_width = s.git_branch.as_ref().map(|b| b.len() + 2).unwrap_or(0);
+                            let git_width = s.git_branch.as_ref().map(|b| b.len() + 2).unwrap_or(0)
+                                + location.as_ref().map(|l| l.len() + 2).unwrap_or(0);
                             let summary_max =
                                 total_width.saturating_sub(fixed_width + git_width + 2);
 
@@ -1039,6 +1370,12 @@ fn ui_grouped_browse(ui: &mut slt::Context, app: &mut App) {
                                     }
                                 }
                                 ui.spacer();
+                                if let Some(location) = &location {
+                                    ui.styled(
+                                        format!("{location}  "),
+                                        slt::Style::new().fg(CYAN).bg(bg),
+                                    );
+                                }
                                 if let Some(branch) = &s.git_branch {
                                     ui.styled(
                                         branch.to_string(),
@@ -1111,11 +1448,7 @@ fn ui_action_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<St
             // picker instead of dispatching Resume directly. Other actions
             // dispatch immediately.
             if actions[app.action_index] == Action::Resume {
-                if let Some(session) = app.selected_session() {
-                    app.resume_mode_options = session.agent.resume_mode_options().to_vec();
-                    app.resume_mode_index = 0;
-                    app.mode = Mode::ResumeSelect;
-                }
+                open_resume(ui, app, result);
             } else {
                 dispatch_action(ui, app, actions[app.action_index], result);
             }
@@ -1129,11 +1462,7 @@ fn ui_action_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<St
             let clicked = y - 4;
             app.action_index = clicked;
             if actions[app.action_index] == Action::Resume {
-                if let Some(session) = app.selected_session() {
-                    app.resume_mode_options = session.agent.resume_mode_options().to_vec();
-                    app.resume_mode_index = 0;
-                    app.mode = Mode::ResumeSelect;
-                }
+                open_resume(ui, app, result);
             } else {
                 dispatch_action(ui, app, actions[app.action_index], result);
             }
@@ -1143,20 +1472,18 @@ fn ui_action_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<St
     if ui.key_code(slt::KeyCode::Enter) {
         // Resume → go to mode picker; others → dispatch directly
         if actions[app.action_index] == Action::Resume {
-            if let Some(session) = app.selected_session() {
-                app.resume_mode_options = session.agent.resume_mode_options().to_vec();
-                app.resume_mode_index = 0;
-                app.mode = Mode::ResumeSelect;
-            }
+            open_resume(ui, app, result);
         } else {
             dispatch_action(ui, app, actions[app.action_index], result);
         }
     }
 
+    let focus = app.focus_cmd();
     let Some(session) = app.selected_session() else {
         app.mode = Mode::Browse;
         return;
     };
+    let pid = app.live.pid(session);
 
     let _ = ui.col(|ui| {
         ui.separator_colored(SEPARATOR);
@@ -1198,6 +1525,8 @@ fn ui_action_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<St
                     } else {
                         "Pin Session".to_string()
                     }
+                } else if *act == Action::Resume && focus.is_some() {
+                    "Focus Session".to_string()
                 } else {
                     act.to_string()
                 };
@@ -1213,7 +1542,13 @@ fn ui_action_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<St
                 } else {
                     base_style
                 };
-                let preview = action::action_preview(session, *act);
+                let preview = match (act, pid) {
+                    (Action::Resume, Some(pid)) if focus.is_some() => {
+                        format!("switch to the tmux pane running it (pid {pid})")
+                    }
+                    (Action::Resume, Some(pid)) => format!("already running (pid {pid})"),
+                    _ => action::action_preview(session, *act),
+                };
                 let mut preview_text = format!("    {preview}");
                 let used = UnicodeWidthStr::width(indicator.as_str())
                     + UnicodeWidthStr::width(label.as_str())
@@ -1262,6 +1597,24 @@ fn ui_action_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<St
     });
 }
 
+/// Resume from the action menu: switch to the session's terminal when it is
+/// already running there, otherwise pick a permission mode.
+fn open_resume(ui: &mut slt::Context, app: &mut App, result: &mut Option<String>) {
+    if let Some(cmd) = app.focus_cmd() {
+        if let Some(session) = app.selected_session() {
+            frecency::record(session);
+        }
+        result.replace(cmd);
+        ui.quit();
+        return;
+    }
+    if let Some(session) = app.selected_session() {
+        app.resume_mode_options = session.agent.resume_mode_options().to_vec();
+        app.resume_mode_index = 0;
+        app.mode = Mode::ResumeSelect;
+    }
+}
+
 fn dispatch_action(
     ui: &mut slt::Context,
     app: &mut App,
@@ -1272,13 +1625,17 @@ fn dispatch_action(
         Action::Back => {
             app.mode = Mode::Browse;
         }
-        Action::NewSession => {
+        Action::NewSession | Action::Handoff => {
+            app.handoff = selected_action == Action::Handoff;
             app.agent_index = 0;
             app.mode = Mode::AgentSelect;
         }
-        Action::Delete => {
-            app.delete_index = 1;
-            app.mode = Mode::DeleteConfirm;
+        Action::Delete => app.open_delete_confirm(),
+        Action::Export => {
+            if let Some(session) = app.selected_session().cloned() {
+                app.notice = Some(export_session(&session, app.cwd.as_deref()));
+            }
+            app.mode = Mode::Browse;
         }
         Action::Pin => {
             if let Some(session) = app.selected_session() {
@@ -1296,6 +1653,13 @@ fn dispatch_action(
         _ => {
             if let Some(session) = app.selected_session().cloned() {
                 if let Some(cmd) = action::generate_command(&session, selected_action, None) {
+                    if matches!(selected_action, Action::Resume | Action::Open) {
+                        frecency::record(&session);
+                    }
+                    let cmd = match selected_action {
+                        Action::Resume => action::prepare_resume(&session, cmd),
+                        _ => cmd,
+                    };
                     result.replace(cmd);
                     ui.quit();
                 }
@@ -1304,6 +1668,20 @@ fn dispatch_action(
     }
 }
 
+/// Write `session` as Markdown into `dir` (the launch directory), next to
+/// any earlier export rather than over it. Returns the footer notice
+/// describing the outcome.
+fn export_session(session: &Session, dir: Option<&str>) -> (String, bool) {
+    use crate::export::{self, ExportFormat};
+    let format = ExportFormat::Markdown;
+    let dir = std::path::Path::new(dir.unwrap_or("."));
+    match export::export(session, format).map(|doc| export::write_new(dir, session, format, &doc)) {
+        Ok(Ok(path)) => (format!("exported to {}", path.display()), false),
+        Ok(Err(e)) => (format!("export failed: {e}"), true),
+        Err(e) => (format!("export failed: {e}"), true),
+    }
+}
+
 fn ui_agent_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<String>) {
     let option_count = app.new_session_options.len();
 
@@ -1358,7 +1736,12 @@ fn ui_agent_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<Str
     let _ = ui.col(|ui| {
         ui.separator_colored(SEPARATOR);
         ui.line(|ui| {
-            ui.text(" New session in ").fg(BRIGHT_WHITE);
+            let title = if app.handoff {
+                " Hand off to another agent in "
+            } else {
+                " New session in "
+            };
+            ui.text(title).fg(BRIGHT_WHITE);
             ui.text(session.display_path()).fg(GRAY_500);
             ui.text("  (enter -> permission mode)").fg(GRAY_500);
         });
@@ -1451,12 +1834,37 @@ fn dispatch_agent_option(ui: &mut slt::Context, app: &mut App, result: &mut Opti
     if let Some(opt) = app.new_session_options.get(app.agent_index) {
         let agent = opt.agent;
         let suffix = opt.command_suffix;
-        if let Some(session) = app.selected_session().cloned() {
-            if let Some(cmd) = action::new_session_with_flags(&session, agent, suffix) {
-                result.replace(cmd);
-                ui.quit();
+        launch_new_session(ui, app, agent, suffix, result);
+    }
+}
+
+/// Start `agent` in the selected session's project: a plain new session,
+/// or a hand-off carrying the session's context.
+fn launch_new_session(
+    ui: &mut slt::Context,
+    app: &mut App,
+    agent: Agent,
+    flags: &str,
+    result: &mut Option<String>,
+) {
+    let Some(session) = app.selected_session().cloned() else {
+        return;
+    };
+    let cmd = if app.handoff {
+        match action::handoff(&session, agent, flags) {
+            Ok(cmd) => Some(cmd),
+            Err(e) => {
+                app.notice = Some((format!("hand-off failed: {e}"), true));
+                app.mode = Mode::Browse;
+                None
             }
         }
+    } else {
+        action::new_session_with_flags(&session, agent, flags)
+    };
+    if let Some(cmd) = cmd {
+        result.replace(cmd);
+        ui.quit();
     }
 }
 
@@ -1570,15 +1978,10 @@ fn ui_permission_select(ui: &mut slt::Context, app: &mut App, result: &mut Optio
 }
 
 fn dispatch_mode_option(ui: &mut slt::Context, app: &mut App, result: &mut Option<String>) {
-    if let Some((_, flags)) = app.mode_options.get(app.mode_index) {
+    if let Some(&(_, flags)) = app.mode_options.get(app.mode_index) {
         if let Some(opt) = app.new_session_options.get(app.agent_index) {
             let agent = opt.agent;
-            if let Some(session) = app.selected_session().cloned() {
-                if let Some(cmd) = action::new_session_with_flags(&session, agent, flags) {
-                    result.replace(cmd);
-                    ui.quit();
-                }
-            }
+            launch_new_session(ui, app, agent, flags, result);
         }
     }
 }
@@ -1689,15 +2092,156 @@ fn ui_resume_select(ui: &mut slt::Context, app: &mut App, result: &mut Option<St
 }
 
 fn dispatch_resume_mode(ui: &mut slt::Context, app: &mut App, result: &mut Option<String>) {
-    if let Some((_, flags)) = app.resume_mode_options.get(app.resume_mode_index) {
+    if let Some(&(_, flags)) = app.resume_mode_options.get(app.resume_mode_index) {
         if let Some(session) = app.selected_session().cloned() {
             let cmd = action::resume_with_flags(&session, flags);
-            result.replace(cmd);
+            // Check against the processes running now, not the last refresh.
+            app.live = crate::live::Live::detect(&app.sessions);
+            if let Some(held) = action::resume_guard(&session, flags, &app.live) {
+                app.held = Some((cmd, held));
+                app.held_index = 0;
+                app.mode = Mode::ResumeHeld;
+                return;
+            }
+            frecency::record(&session);
+            result.replace(action::prepare_resume(&session, cmd));
             ui.quit();
         }
     }
 }
 
+/// Choices offered for a session that is already running: the label and
+/// the command to run (`None` cancels).
+fn held_options(held: &(String, action::Held)) -> Vec<(&'static str, Option<String>)> {
+    let (cmd, held) = held;
+    let mut options = Vec::new();
+    if let Some(fork) = &held.fork {
+        options.push(("Fork into a new session", Some(fork.clone())));
+    }
+    options.push(("Resume anyway", Some(cmd.clone())));
+    options.push(("Cancel", None));
+    options
+}
+
+fn ui_resume_held(ui: &mut slt::Context, app: &mut App, result: &mut Option<String>) {
+    let Some(held) = &app.held else {
+        app.mode = Mode::Browse;
+        return;
+    };
+    let options = held_options(held);
+    let pid = held.1.pid;
+    let option_count = options.len();
+
+    if ui.key_code(slt::KeyCode::Esc) {
+        app.held = None;
+        app.mode = Mode::ResumeSelect;
+        return;
+    }
+    if (ui.key_code(slt::KeyCode::Up)
+        || ui.key_code(slt::KeyCode::BackTab)
+        || ui.key_mod('p', slt::KeyModifiers::CONTROL)
+        || ui.key_mod('k', slt::KeyModifiers::CONTROL))
+        && app.held_index > 0
+    {
+        app.held_index -= 1;
+    }
+    if (ui.key_code(slt::KeyCode::Down)
+        || ui.key_code(slt::KeyCode::Tab)
+        || ui.key_mod('n', slt::KeyModifiers::CONTROL)
+        || ui.key_mod('j', slt::KeyModifiers::CONTROL))
+        && app.held_index + 1 < option_count
+    {
+        app.held_index += 1;
+    }
+    let mut chosen = None;
+    for i in 0..option_count {
+        let key = char::from_u32((b'1' + i as u8) as u32).unwrap_or('1');
+        if ui.key(key) {
+            chosen = Some(i);
+        }
+    }
+    if ui.key_code(slt::KeyCode::Enter) {
+        chosen = Some(app.held_index);
+    }
+    if let Some(i) = chosen {
+        match options.get(i).and_then(|(_, cmd)| cmd.clone()) {
+            Some(cmd) => {
+                let cmd = match app.selected_session() {
+                    Some(session) => {
+                        frecency::record(session);
+                        action::prepare_resume(session, cmd)
+                    }
+                    None => cmd,
+                };
+                result.replace(cmd);
+                ui.quit();
+            }
+            None => app.mode = Mode::Browse,
+        }
+        app.held = None;
+        return;
+    }
+
+    let Some(session) = app.selected_session() else {
+        app.mode = Mode::Browse;
+        return;
+    };
+
+    let _ = ui.col(|ui| {
+        ui.separator_colored(SEPARATOR);
+        ui.text(" Session already running").fg(YELLOW).bold();
+        ui.separator_colored(SEPARATOR);
+        ui.text("");
+        ui.line(|ui| {
+            ui.text(format!("  {} ", session.agent))
+                .fg(agent_color(session.agent))
+                .bold();
+            ui.text(&session.project_name).fg(BRIGHT_WHITE).bold();
+            ui.text(format!(" is open in pid {pid}")).fg(GRAY_400);
+        });
+        ui.text("  Resuming it again would interleave writes to the same session log.")
+            .fg(GRAY_500);
+        ui.text("");
+
+        let _ = ui.container().grow(1).col(|ui| {
+            let total_width = ui.width() as usize;
+            for (i, (label, _)) in options.iter().enumerate() {
+                let is_selected = i == app.held_index;
+                let bg = if is_selected {
+                    HIGHLIGHT_BG
+                } else {
+                    slt::Color::Reset
+                };
+                let indicator = format!(" {}) ", i + 1);
+                let pad = total_width.saturating_sub(
+                    UnicodeWidthStr::width(indicator.as_str()) + UnicodeWidthStr::width(*label),
+                );
+                let _ = ui.row(|ui| {
+                    ui.styled(indicator.clone(), slt::Style::new().fg(GRAY_400).bg(bg));
+                    let base = slt::Style::new().fg(BRIGHT_WHITE).bg(bg);
+                    ui.styled(
+                        (*label).to_string(),
+                        if is_selected { base.bold() } else { base },
+                    );
+                    if pad > 0 {
+                        ui.styled(" ".repeat(pad), slt::Style::new().bg(bg));
+                    }
+                });
+            }
+        });
+
+        ui.text("");
+        ui.separator_colored(SEPARATOR);
+        let _ = ui.container().pl(1).row(|ui| {
+            let _ = ui.help_colored(
+                &[("1-9", "select"), ("Enter", "confirm"), ("Esc", "back")],
+                GRAY_500,
+                SEPARATOR,
+            );
+        });
+    });
+}
+
 fn ui_bulk_delete(ui: &mut slt::Context, app: &mut App) {
     if ui.key_code(slt::KeyCode::Esc) {
         app.selected_set.clear();
@@ -1736,8 +2280,7 @@ fn ui_bulk_delete(ui: &mut slt::Context, app: &mut App) {
     }
 
     if ui.key_code(slt::KeyCode::Enter) && !app.selected_set.is_empty() {
-        app.delete_index = 1;
-        app.mode = Mode::DeleteConfirm;
+        app.open_delete_confirm();
     }
 
     let _ = ui.col(|ui| {
@@ -1802,24 +2345,10 @@ fn ui_delete_confirm(ui: &mut slt::Context, app: &mut App) {
     if ui.key_code(slt::KeyCode::Enter) {
         if app.delete_index == 0 {
             if is_bulk {
-                let mut indices: Vec<usize> = app.selected_set.drain().collect();
-                indices.sort_unstable_by(|a, b| b.cmp(a));
-                for idx in indices {
-                    if idx < app.sessions.len() {
-                        let agent = app.sessions[idx].agent;
-                        let _ = crate::delete::delete_session(&app.sessions[idx]);
-                        app.sessions.remove(idx);
-                        decrement_agent_count(&mut app.agent_counts, agent);
-                    }
-                }
-                app.selected_set.clear();
-                app.update_filter();
+                let indices: Vec<usize> = app.selected_set.drain().collect();
+                app.delete_indices(indices);
             } else if let Some(idx) = app.filtered_indices.get(app.selected).copied() {
-                let agent = app.sessions[idx].agent;
-                let _ = crate::delete::delete_session(&app.sessions[idx]);
-                app.sessions.remove(idx);
-                decrement_agent_count(&mut app.agent_counts, agent);
-                app.update_filter();
+                app.delete_indices(vec![idx]);
             }
             app.mode = Mode::Browse;
         } else if is_bulk {
@@ -1836,6 +2365,39 @@ fn ui_delete_confirm(ui: &mut slt::Context, app: &mut App) {
     }
 }
 
+/// Files, JSONL lines and SQLite rows the pending delete touches.
+fn render_delete_impact(ui: &mut slt::Context, app: &App) {
+    const MAX_LINES: usize = 6;
+    let Some(impact) = &app.delete_impact else {
+        if app.delete_plan_rx.is_some() {
+            ui.text("");
+            ui.text("  planning…").fg(GRAY_500);
+        }
+        return;
+    };
+    let lines = impact.lines();
+    ui.text("");
+    if lines.is_empty() {
+        ui.text("  nothing on disk to remove (plugin sessions are deleted by their plugin)")
+            .fg(GRAY_500);
+        return;
+    }
+    let max_width = (ui.width() as usize).saturating_sub(6);
+    for line in lines.iter().take(MAX_LINES) {
+        ui.text(format!("  {}", truncate_str(line, max_width)))
+            .fg(GRAY_400);
+    }
+    if lines.len() > MAX_LINES {
+        ui.text(format!("  ... and {} more", lines.len() - MAX_LINES))
+            .fg(GRAY_500);
+    }
+    ui.text(format!(
+        "  frees {}",
+        crate::prune::format_bytes(impact.bytes)
+    ))
+    .fg(GRAY_500);
+}
+
 fn render_single_delete_confirm(ui: &mut slt::Context, app: &App) {
     let Some(session) = app.selected_session() else {
         return;
@@ -1864,6 +2426,7 @@ fn render_single_delete_confirm(ui: &mut slt::Context, app: &App) {
             ui.text(format!("  \"{truncated}\"")).fg(GRAY_400);
         }
 
+        render_delete_impact(ui, app);
         ui.text("");
         let options = ["Yes, delete", "Cancel"];
         for (i, opt) in options.iter().enumerate() {
@@ -1880,7 +2443,7 @@ fn render_single_delete_confirm(ui: &mut slt::Context, app: &App) {
                 slt::Style::new().fg(BRIGHT_WHITE).bg(bg)
             };
             let desc = if i == 0 {
-                "removes session data only"
+                "moves session data to the trash (^Z undoes)"
             } else {
                 "go back"
             };
@@ -1924,6 +2487,7 @@ fn render_bulk_delete_confirm(ui: &mut slt::Context, app: &App) {
             ui.text(format!("  - {name}")).fg(BRIGHT_WHITE);
         }
 
+        render_delete_impact(ui, app);
         ui.text("");
         let options = ["Yes, delete all", "Cancel"];
         for (i, opt) in options.iter().enumerate() {
@@ -1940,7 +2504,7 @@ fn render_bulk_delete_confirm(ui: &mut slt::Context, app: &App) {
                 slt::Style::new().fg(BRIGHT_WHITE).bg(bg)
             };
             let desc = if i == 0 {
-                "removes session data only"
+                "moves session data to the trash (^Z undoes)"
             } else {
                 "go back"
             };
@@ -1975,6 +2539,13 @@ fn ui_preview(ui: &mut slt::Context, app: &mut App) {
         app.mode = Mode::ActionSelect;
         return;
     }
+    if ui.key_code(slt::KeyCode::Right)
+        || ui.key('t')
+        || ui.key_mod('l', slt::KeyModifiers::CONTROL)
+    {
+        app.open_transcript();
+        return;
+    }
 
     // Up/Down (and Ctrl-p/n, Ctrl-k/j) cycle to the previous/next session
     // within the current filter, keeping the preview open.
@@ -1997,6 +2568,28 @@ fn ui_preview(ui: &mut slt::Context, app: &mut App) {
         app.mode = Mode::Browse;
         return;
     };
+    // A live branch is checked out by definition; its state says nothing
+    // about the branch the session worked on.
+    if let Some(branch) = session
+        .git_branch
+        .as_ref()
+        .filter(|_| !session.branch_is_live)
+    {
+        if !app.branch_states.contains_key(&session.session_id) {
+            let state =
+                crate::git::branch_state(&session.project_path, branch, session.commit.as_deref());
+            let id = session.session_id.clone();
+            app.branch_states.insert(id, state);
+        }
+    }
+    let Some(session) = app.selected_session() else {
+        return;
+    };
+    let branch_state = app
+        .branch_states
+        .get(&session.session_id)
+        .copied()
+        .flatten();
 
     let _ = ui.col(|ui| {
         ui.separator_colored(SEPARATOR);
@@ -2018,6 +2611,15 @@ fn ui_preview(ui: &mut slt::Context, app: &mut App) {
             ui.text("  Path:     ").fg(GRAY_500);
             ui.text(session.display_path()).fg(GRAY_400);
         });
+        if let Some(repo) = crate::git::repo(&session.project_path) {
+            ui.line(|ui| {
+                ui.text("  Repo:     ").fg(GRAY_500);
+                ui.text(&repo.key).fg(GRAY_400);
+                if let Some(location) = repo.location() {
+                    ui.text(format!("  ({location})")).fg(CYAN);
+                }
+            });
+        }
         ui.line(|ui| {
             ui.text("  Session:  ").fg(GRAY_500);
             ui.text(&session.session_id).fg(GRAY_400);
@@ -2026,11 +2628,32 @@ fn ui_preview(ui: &mut slt::Context, app: &mut App) {
             ui.text("  Time:     ").fg(GRAY_500);
             ui.text(session.time_display()).fg(VIOLET);
         });
+        if let Some(pid) = app.live.pid(session) {
+            ui.line(|ui| {
+                ui.text("  Status:   ").fg(GRAY_500);
+                ui.text(format!("running (pid {pid})")).fg(GREEN_400);
+            });
+        }
 
         if let Some(branch) = &session.git_branch {
             ui.line(|ui| {
                 ui.text("  Branch:   ").fg(GRAY_500);
                 ui.text(branch).fg(GREEN_400);
+                if session.branch_is_live {
+                    ui.text("  (checked out now, not recorded)").fg(GRAY_500);
+                } else if let Some(state) = branch_state {
+                    let color = match state {
+                        crate::git::BranchState::Deleted => GRAY_500,
+                        _ => GRAY_400,
+                    };
+                    ui.text(format!("  ({})", state.label())).fg(color);
+                }
+            });
+        }
+        if let Some(commit) = &session.commit {
+            ui.line(|ui| {
+                ui.text("  Commit:   ").fg(GRAY_500);
+                ui.text(commit.get(..12).unwrap_or(commit)).fg(GRAY_400);
             });
         }
         if let Some(wt) = &session.worktree {
@@ -2039,6 +2662,27 @@ fn ui_preview(ui: &mut slt::Context, app: &mut App) {
                 ui.text(wt).fg(CYAN);
             });
         }
+        if !session.usage.is_empty() {
+            let tokens = session.token_totals();
+            ui.line(|ui| {
+                ui.text("  Tokens:   ").fg(GRAY_500);
+                ui.text(format!(
+                    "in {}  out {}  cache {}",
+                    crate::usage::format_tokens(tokens.input),
+                    crate::usage::format_tokens(tokens.output),
+                    crate::usage::format_tokens(tokens.cache_read + tokens.cache_write),
+                ))
+                .fg(GRAY_400);
+                if let Some(cost) = app.prices.cost(&session.usage) {
+                    ui.text(format!("  ≈{}", crate::usage::format_cost(cost)))
+                        .fg(YELLOW);
+                }
+            });
+            ui.line(|ui| {
+                ui.text("  Models:   ").fg(GRAY_500);
+                ui.text(session.models().join(", ")).fg(GRAY_400);
+            });
+        }
 
         if let Some(recap) = &session.recap {
             ui.line(|ui| {
@@ -2070,12 +2714,337 @@ fn ui_preview(ui: &mut slt::Context, app: &mut App) {
         ui.separator_colored(SEPARATOR);
         let _ = ui.container().pl(1).row(|ui| {
             let _ = ui.help_colored(
-                &[("↑↓", "cycle"), ("Enter", "actions"), ("Esc/←", "back")],
+                &[
+                    ("↑↓", "cycle"),
+                    ("→/t", "transcript"),
+                    ("Enter", "actions"),
+                    ("Esc/←", "back"),
+                ],
+                GRAY_500,
+                SEPARATOR,
+            );
+        });
+    });
+}
+
+fn ui_transcript(ui: &mut slt::Context, app: &mut App) {
+    if ui.key_code(slt::KeyCode::Esc)
+        || ui.key_code(slt::KeyCode::Left)
+        || ui.key_mod('h', slt::KeyModifiers::CONTROL)
+    {
+        app.mode = Mode::Preview;
+        return;
+    }
+    let Some(session) = app.selected_session().cloned() else {
+        app.mode = Mode::Browse;
+        return;
+    };
+    let Some(view) = app.transcript.as_mut() else {
+        app.mode = Mode::Preview;
+        return;
+    };
+
+    // Title (1) + separators (3) + help bar (1).
+    let height = (ui.height() as usize).saturating_sub(5).max(1);
+    let width = (ui.width() as usize).saturating_sub(1);
+
+    let up = ui.key_code(slt::KeyCode::Up)
+        || ui.key('k')
+        || ui.key_mod('p', slt::KeyModifiers::CONTROL)
+        || ui.key_mod('k', slt::KeyModifiers::CONTROL)
+        || ui.scroll_up();
+    let down = ui.key_code(slt::KeyCode::Down)
+        || ui.key('j')
+        || ui.key_mod('n', slt::KeyModifiers::CONTROL)
+        || ui.key_mod('j', slt::KeyModifiers::CONTROL)
+        || ui.scroll_down();
+    if up {
+        view.scroll = view.scroll.saturating_sub(1);
+    }
+    if down {
+        view.scroll += 1;
+    }
+    if ui.key_code(slt::KeyCode::PageUp) {
+        view.scroll = view.scroll.saturating_sub(height);
+    }
+    if ui.key_code(slt::KeyCode::PageDown) || ui.key(' ') {
+        view.scroll += height;
+    }
+    if ui.key_code(slt::KeyCode::Home) || ui.key('g') {
+        view.scroll = 0;
+    }
+    if ui.key_code(slt::KeyCode::End) || ui.key('G') {
+        view.load_all();
+        view.scroll = view.line_count();
+    }
+
+    // Pull just enough of the transcript to fill the current page.
+    view.ensure_lines(view.scroll + height, width);
+    view.clamp_scroll(height);
+
+    let count = view.message_count();
+    let count_label = if view.is_complete() {
+        format!("{count} messages")
+    } else {
+        format!("{count}+ messages")
+    };
+
+    let _ = ui.col(|ui| {
+        ui.separator_colored(SEPARATOR);
+        ui.line(|ui| {
+            ui.text(" Transcript ").fg(BRIGHT_WHITE).bold();
+            ui.text(format!("{} ", session.agent))
+                .fg(agent_color(session.agent))
+                .bold();
+            ui.text("| ").fg(SEPARATOR);
+            ui.text(&session.project_name).fg(BRIGHT_WHITE);
+            ui.text(" | ").fg(SEPARATOR);
+            ui.text(count_label).fg(GRAY_500);
+        });
+        ui.separator_colored(SEPARATOR);
+
+        let _ = ui.container().grow(1).col(|ui| {
+            if let Some(err) = view.error() {
+                let _ = ui.container().pl(2).col(|ui| {
+                    let _ = ui.empty_state("Transcript unavailable", err);
+                });
+                return;
+            }
+            if view.line_count() == 0 {
+                let _ = ui.container().pl(2).col(|ui| {
+                    let _ = ui.empty_state("Empty transcript", "No messages recorded");
+                });
+                return;
+            }
+            for line in view.visible_lines(height) {
+                let _ = ui.row(|ui| {
+                    if line.is_empty() {
+                        ui.text("");
+                    }
+                    render_chunks(ui, line);
+                });
+            }
+        });
+
+        ui.separator_colored(SEPARATOR);
+        let _ = ui.container().pl(1).row(|ui| {
+            let _ = ui.help_colored(
+                &[
+                    ("↑↓/jk", "scroll"),
+                    ("PgUp/PgDn", "page"),
+                    ("g/G", "top/end"),
+                    ("Esc/←", "back"),
+                ],
+                GRAY_500,
+                SEPARATOR,
+            );
+        });
+    });
+}
+
+fn ui_search(ui: &mut slt::Context, app: &mut App) {
+    let esc = ui.consume_key_code(slt::KeyCode::Esc);
+    let enter = ui.consume_key_code(slt::KeyCode::Enter);
+    let up = ui.consume_key_code(slt::KeyCode::Up);
+    let down = ui.consume_key_code(slt::KeyCode::Down);
+    let right = ui.consume_key_code(slt::KeyCode::Right);
+    let ctrl_up =
+        ui.key_mod('p', slt::KeyModifiers::CONTROL) || ui.key_mod('k', slt::KeyModifiers::CONTROL);
+    let ctrl_down =
+        ui.key_mod('n', slt::KeyModifiers::CONTROL) || ui.key_mod('j', slt::KeyModifiers::CONTROL);
+    let ctrl_right = ui.key_mod('l', slt::KeyModifiers::CONTROL);
+    let ctrl_clear = ui.key_mod('u', slt::KeyModifiers::CONTROL);
+    if ctrl_up {
+        ui.consume_key('p');
+        ui.consume_key('k');
+    }
+    if ctrl_down {
+        ui.consume_key('n');
+        ui.consume_key('j');
+    }
+    if ctrl_right {
+        ui.consume_key('l');
+    }
+    if ctrl_clear {
+        ui.consume_key('u');
+    }
+
+    // Header (2) + separators (3) + status (1) + help bar (1).
+    let rows = (ui.height() as usize).saturating_sub(7).max(1);
+    let view = &mut app.search;
+
+    if esc {
+        app.mode = Mode::Browse;
+        return;
+    }
+    if ctrl_clear {
+        view.textarea.lines = vec![String::new()];
+        view.textarea.cursor_col = 0;
+    }
+    if (up || ctrl_up || ui.scroll_up()) && view.selected > 0 {
+        view.selected -= 1;
+    }
+    if (down || ctrl_down || ui.scroll_down()) && view.selected + 1 < view.results.len() {
+        view.selected += 1;
+    }
+    view.adjust_scroll(rows);
+
+    // Enter runs the search when the pattern changed; on an unchanged
+    // pattern it opens the highlighted session instead.
+    let jump_to = if enter && view.pattern() != view.searched {
+        let sessions = app.sessions.clone();
+        app.search.start(&sessions);
+        None
+    } else if enter || right || ctrl_right {
+        view.selected_result().map(|s| {
+            (
+                s.session_id.clone(),
+                if enter {
+                    Mode::ActionSelect
+                } else {
+                    Mode::Preview
+                },
+            )
+        })
+    } else {
+        None
+    };
+    if let Some((id, mode)) = jump_to {
+        if app.focus_session(&id) {
+            app.action_index = 0;
+            app.mode = mode;
+            return;
+        }
+    }
+
+    let view = &mut app.search;
+    let _ = ui.col(|ui| {
+        ui.text("");
+        let _ = ui.container().pl(2).pr(1).row(|ui| {
+            let _ = ui.container().grow(1).row(|ui| {
+                let _ = ui.textarea(&mut view.textarea, 1);
+            });
+            let _ = ui.badge_colored("Full text", VIOLET);
+        });
+        ui.separator_colored(SEPARATOR);
+
+        let _ = ui.container().grow(1).pr(1).col(|ui| {
+            if view.is_running() {
+                let _ = ui.container().pl(2).col(|ui| {
+                    let _ = ui.empty_state(
+                        "Searching…",
+                        "The first search indexes every transcript; later ones are quick",
+                    );
+                });
+                return;
+            }
+            if view.results.is_empty() {
+                let (title, hint) = if view.searched.is_empty() {
+                    (
+                        "Search session transcripts",
+                        "Type a phrase and press Enter",
+                    )
+                } else {
+                    ("No matches", "No transcript contains this phrase")
+                };
+                let _ = ui.container().pl(2).col(|ui| {
+                    let _ = ui.empty_state(title, hint);
+                });
+                return;
+            }
+            let width = (ui.width() as usize).saturating_sub(2);
+            let mut used = 0;
+            for (i, (session, m)) in view.results.iter().enumerate().skip(view.scroll) {
+                let needed = search::result_rows(m);
+                if used + needed > rows && used > 0 {
+                    break;
+                }
+                used += needed;
+                let is_selected = i == view.selected;
+                let bg = if is_selected {
+                    HIGHLIGHT_BG
+                } else {
+                    slt::Color::Reset
+                };
+                let count = if m.total == 1 {
+                    "1 match".to_string()
+                } else {
+                    format!("{} matches", m.total)
+                };
+                let _ = ui.row(|ui| {
+                    ui.styled(
+                        if is_selected { "> " } else { "  " }.to_string(),
+                        slt::Style::new().fg(slt::Color::White).bg(bg),
+                    );
+                    ui.styled(
+                        format!("{:<12} ", session.agent.to_string()),
+                        slt::Style::new()
+                            .fg(agent_color(session.agent))
+                            .bold()
+                            .bg(bg),
+                    );
+                    ui.styled(
+                        format!("{} ", truncate_str(&session.project_name, 30)),
+                        slt::Style::new().fg(BRIGHT_WHITE).bold().bg(bg),
+                    );
+                    ui.styled(
+                        format!("{} · {count}", session.time_display()),
+                        slt::Style::new().fg(GRAY_500).bg(bg),
+                    );
+                });
+                for hit in &m.hits {
+                    let label = format!("    {:>9}  ", hit.role.label());
+                    let room = width.saturating_sub(UnicodeWidthStr::width(label.as_str()));
+                    let before = truncate_str(&hit.before, room);
+                    let room = room.saturating_sub(UnicodeWidthStr::width(before.as_str()));
+                    let matched = truncate_str(&hit.matched, room);
+                    let room = room.saturating_sub(UnicodeWidthStr::width(matched.as_str()));
+                    let after = truncate_str(&hit.after, room);
+                    let _ = ui.row(|ui| {
+                        ui.styled(label, slt::Style::new().fg(SEPARATOR));
+                        ui.styled(before, slt::Style::new().fg(GRAY_400));
+                        ui.styled(matched, slt::Style::new().fg(YELLOW).bold());
+                        ui.styled(after, slt::Style::new().fg(GRAY_400));
+                    });
+                }
+                ui.text("");
+            }
+        });
+
+        let _ = ui.container().pl(2).pr(1).row(|ui| {
+            if view.is_running() {
+                ui.text(format!("searching for \"{}\"…", view.searched))
+                    .fg(YELLOW);
+            } else if !view.searched.is_empty() {
+                ui.text(format!("{} sessions", view.results.len()))
+                    .fg(GRAY_500);
+            }
+        });
+        ui.separator_colored(SEPARATOR);
+        let _ = ui.container().pr(1).row(|ui| {
+            ui.spacer();
+            let _ = ui.help_colored(
+                &[
+                    ("Enter", "search/select"),
+                    ("↑↓", "nav"),
+                    ("→", "detail"),
+                    ("^U", "clear"),
+                    ("Esc", "back"),
+                ],
                 GRAY_500,
                 SEPARATOR,
             );
         });
     });
+
+    // Keep the pattern single-line (Enter is consumed above, but pasted
+    // newlines still split the textarea).
+    if view.textarea.lines.len() > 1 {
+        let merged: String = view.textarea.lines.join("");
+        view.textarea.lines = vec![merged.clone()];
+        view.textarea.cursor_row = 0;
+        view.textarea.cursor_col = merged.chars().count();
+    }
 }
 
 fn ui_help(ui: &mut slt::Context, app: &mut App) {
@@ -2148,10 +3117,14 @@ fn ui_help(ui: &mut slt::Context, app: &mut App) {
             help_line(ui, "↑ / ↓", "Navigate sessions");
             help_line(ui, "[ / ]", "Cycle summary");
             help_line(ui, "→", "Session detail");
+            help_line(ui, "→ → / t", "Session transcript");
             help_line(ui, "Enter", "Action menu");
             help_line(ui, "Tab", "Cycle agent filter");
             help_line(ui, "^S", "Cycle sort");
+            help_line(ui, "^R", "Only this project");
+            help_line(ui, "^F", "Full-text search");
             help_line(ui, "^D", "Bulk delete");
+            help_line(ui, "^Z", "Undo last delete");
             help_line(ui, "?", "Help");
             help_line(ui, "Esc", "Quit");
 
@@ -2336,6 +3309,7 @@ fn render_session_list(ui: &mut slt::Context, app: &App, bulk_mode: bool) {
                 None,
                 summary_text,
                 name_col_width,
+                app.live.pid(session).is_some(),
             );
 
             let _ = ui.row(|ui| {
@@ -2373,6 +3347,7 @@ fn render_session_list(ui: &mut slt::Context, app: &App, bulk_mode: bool) {
                 match_positions,
                 summary_text,
                 name_col_width,
+                app.live.pid(session).is_some(),
             );
 
             let _ = ui.row(|ui| {
@@ -2432,6 +3407,12 @@ fn render_session_list_compact(ui: &mut slt::Context, app: &App) {
             } else {
                 ui.styled("        ", slt::Style::new().bg(bg));
             }
+            let live_mark = if app.live.pid(session).is_some() {
+                " ●"
+            } else {
+                "  "
+            };
+            ui.styled(live_mark, slt::Style::new().fg(GREEN_400).bg(bg));
             ui.styled(
                 format!("{:>12}", session.time_display()),
                 slt::Style::new().fg(GRAY_500).bg(bg),
@@ -2450,6 +3431,7 @@ fn build_session_row(
     match_positions: Option<&[u32]>,
     summary_text: Option<&str>,
     name_col_width: usize,
+    live: bool,
 ) -> Vec<StyledChunk> {
     let mut chunks: Vec<StyledChunk> = Vec::new();
 
@@ -2463,7 +3445,8 @@ fn build_session_row(
     ));
 
     let time_str = session.time_display();
-    let time_width = UnicodeWidthStr::width(time_str.as_str()) + 2;
+    let live_mark = if live { "  ●" } else { "" };
+    let time_width = UnicodeWidthStr::width(time_str.as_str()) + 2 + live_mark.chars().count();
     let right_display_width = time_width + right_margin;
 
     let git_info_str = if let Some(wt) = &session.worktree {
@@ -2534,6 +3517,12 @@ fn build_session_row(
         };
         chunks.push((git_str, slt::Style::new().fg(color).bg(bg)));
     }
+    if live {
+        chunks.push((
+            live_mark.to_string(),
+            slt::Style::new().fg(GREEN_400).bg(bg),
+        ));
+    }
     chunks.push((
         format!("  {time_str}"),
         slt::Style::new().fg(GRAY_500).bg(bg),
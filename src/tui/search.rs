@@ -0,0 +1,113 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::model::Session;
+use crate::search::{self, SessionMatches};
+
+/// Snippets kept per session in the TUI result list.
+const HITS_PER_SESSION: usize = 2;
+
+/// Full-text search pane state. Searches run on a worker thread (the first
+/// one may have to build the index) and report back through `rx`, which the
+/// render loop polls every frame.
+pub struct SearchView {
+    pub textarea: slt::TextareaState,
+    /// Pattern of the last search that was started.
+    pub searched: String,
+    /// Sessions paired with their hits. Sessions are snapshots taken when the
+    /// search started; jump back into the list by id.
+    pub results: Vec<(Session, SessionMatches)>,
+    pub selected: usize,
+    pub scroll: usize,
+    rx: Option<Receiver<Vec<(Session, SessionMatches)>>>,
+}
+
+impl SearchView {
+    pub fn new() -> Self {
+        Self {
+            textarea: slt::TextareaState::new(),
+            searched: String::new(),
+            results: Vec::new(),
+            selected: 0,
+            scroll: 0,
+            rx: None,
+        }
+    }
+
+    pub fn pattern(&self) -> String {
+        self.textarea.lines.join("").trim().to_string()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// Kick off a search over `sessions`. A search still in flight is
+    /// abandoned; its worker finishes but nobody listens.
+    pub fn start(&mut self, sessions: &[Session]) {
+        let pattern = self.pattern();
+        self.searched = pattern.clone();
+        self.results.clear();
+        self.selected = 0;
+        self.scroll = 0;
+        if pattern.is_empty() {
+            self.rx = None;
+            return;
+        }
+        let sessions = sessions.to_vec();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let matches = search::search(&sessions, &pattern, HITS_PER_SESSION);
+            let results = matches
+                .into_iter()
+                .map(|m| (sessions[m.index].clone(), m))
+                .collect();
+            let _ = tx.send(results);
+        });
+        self.rx = Some(rx);
+    }
+
+    /// Collect a finished search, if any.
+    pub fn poll(&mut self) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(results) => {
+                self.results = results;
+                self.rx = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.rx = None,
+        }
+    }
+
+    pub fn selected_result(&self) -> Option<&Session> {
+        self.results.get(self.selected).map(|(s, _)| s)
+    }
+
+    /// Keep the selected result on screen. Results are variable height, so
+    /// scroll just far enough that the selection's last row fits in `rows`.
+    pub fn adjust_scroll(&mut self, rows: usize) {
+        if self.results.is_empty() {
+            self.scroll = 0;
+            return;
+        }
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+            return;
+        }
+        let mut first = self.selected;
+        let mut used = result_rows(&self.results[first].1);
+        while first > 0 && used + result_rows(&self.results[first - 1].1) <= rows {
+            first -= 1;
+            used += result_rows(&self.results[first].1);
+        }
+        self.scroll = self.scroll.max(first);
+    }
+}
+
+/// Display rows taken by one result: header, snippets, blank spacer.
+pub fn result_rows(m: &SessionMatches) -> usize {
+    m.hits.len() + 2
+}
@@ -0,0 +1,182 @@
+use unicode_width::UnicodeWidthChar;
+
+use super::{agent_color, StyledChunk, BRIGHT_WHITE, CYAN, GRAY_400, GRAY_500, SEPARATOR, VIOLET};
+use crate::model::{Agent, Session};
+use crate::transcript::{Message, Role, Transcript};
+
+/// Messages pulled from the underlying stream per refill.
+const LOAD_BATCH: usize = 50;
+/// Tool output is usually noise in a transcript read-through; show a head.
+const TOOL_RESULT_LINES: usize = 6;
+
+/// Scrollable transcript pane state. Messages are pulled lazily from the
+/// agent's store and wrapped into display lines only as the user scrolls
+/// toward the end of what has been loaded so far.
+pub struct TranscriptView {
+    pub session_id: String,
+    agent: Agent,
+    transcript: Transcript,
+    lines: Vec<Vec<StyledChunk>>,
+    wrapped_messages: usize,
+    wrap_width: usize,
+    pub scroll: usize,
+}
+
+impl TranscriptView {
+    pub fn open(session: &Session) -> Self {
+        Self {
+            session_id: session.session_id.clone(),
+            agent: session.agent,
+            transcript: Transcript::open(session),
+            lines: Vec::new(),
+            wrapped_messages: 0,
+            wrap_width: 0,
+            scroll: 0,
+        }
+    }
+
+    /// Make sure at least `rows` display lines are available at `width`,
+    /// pulling more messages from the stream if needed.
+    pub fn ensure_lines(&mut self, rows: usize, width: usize) {
+        if width != self.wrap_width {
+            self.lines.clear();
+            self.wrapped_messages = 0;
+            self.wrap_width = width;
+        }
+        loop {
+            self.wrap_pending();
+            if self.lines.len() >= rows || self.transcript.is_complete() {
+                break;
+            }
+            self.transcript.load_more(LOAD_BATCH);
+        }
+        self.wrap_pending();
+    }
+
+    /// Drain the whole stream (used by `End`).
+    pub fn load_all(&mut self) {
+        while !self.transcript.is_complete() {
+            self.transcript.load_more(LOAD_BATCH);
+        }
+        self.wrap_pending();
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn visible_lines(&self, height: usize) -> &[Vec<StyledChunk>] {
+        let start = self.scroll.min(self.lines.len());
+        let end = (start + height).min(self.lines.len());
+        &self.lines[start..end]
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.transcript.is_complete()
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.transcript.messages.len()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.transcript.error.as_deref()
+    }
+
+    /// Clamp `scroll` so the last page stays full once everything is loaded.
+    pub fn clamp_scroll(&mut self, height: usize) {
+        if self.transcript.is_complete() {
+            let max = self.lines.len().saturating_sub(height);
+            self.scroll = self.scroll.min(max);
+        }
+    }
+
+    fn wrap_pending(&mut self) {
+        let width = self.wrap_width.max(20);
+        while self.wrapped_messages < self.transcript.messages.len() {
+            let msg = &self.transcript.messages[self.wrapped_messages];
+            let lines = message_lines(msg, self.agent, width);
+            self.lines.extend(lines);
+            self.wrapped_messages += 1;
+        }
+    }
+}
+
+fn message_lines(msg: &Message, agent: Agent, width: usize) -> Vec<Vec<StyledChunk>> {
+    let (label_color, body_color) = match msg.role {
+        Role::User => (CYAN, BRIGHT_WHITE),
+        Role::Assistant => (agent_color(agent), GRAY_400),
+        Role::Tool => (VIOLET, GRAY_400),
+        Role::ToolResult => (GRAY_500, GRAY_500),
+    };
+
+    let mut header: Vec<StyledChunk> = vec![(
+        format!("  {}", msg.role.label()),
+        slt::Style::new().fg(label_color).bold(),
+    )];
+    if let Some(tool) = &msg.tool {
+        header.push((format!(" {tool}"), slt::Style::new().fg(label_color)));
+    }
+    if let Some(ts) = msg.timestamp.and_then(format_time) {
+        header.push((format!("  {ts}"), slt::Style::new().fg(SEPARATOR)));
+    }
+
+    let mut out = vec![header];
+    let body_width = width.saturating_sub(4).max(10);
+    let source: Vec<&str> = msg.text.lines().collect();
+    let (shown, hidden) = if msg.role == Role::ToolResult && source.len() > TOOL_RESULT_LINES {
+        (
+            &source[..TOOL_RESULT_LINES],
+            source.len() - TOOL_RESULT_LINES,
+        )
+    } else {
+        (&source[..], 0)
+    };
+    for line in shown {
+        for piece in wrap_line(&line.replace('\t', "    "), body_width) {
+            out.push(vec![(
+                format!("    {piece}"),
+                slt::Style::new().fg(body_color),
+            )]);
+        }
+    }
+    if hidden > 0 {
+        out.push(vec![(
+            format!("    … {hidden} more lines"),
+            slt::Style::new().fg(SEPARATOR),
+        )]);
+    }
+    out.push(Vec::new());
+    out
+}
+
+/// Hard-wrap a single line to `width` display columns (CJK-safe).
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    if line.is_empty() {
+        return vec![String::new()];
+    }
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    for ch in line.chars() {
+        let w = ch.width().unwrap_or(0);
+        if current_width + w > width && !current.is_empty() {
+            out.push(std::mem::take(&mut current));
+            current_width = 0;
+        }
+        current.push(ch);
+        current_width += w;
+    }
+    if !current.is_empty() {
+        out.push(current);
+    }
+    out
+}
+
+fn format_time(ms: i64) -> Option<String> {
+    use chrono::{Local, TimeZone};
+    match Local.timestamp_millis_opt(ms) {
+        chrono::LocalResult::Single(dt) => Some(dt.format("%m/%d %H:%M").to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{ModelUsage, TokenCounts};
+use crate::settings::Settings;
+
+/// USD per million tokens. `cache_read`/`cache_write` default to the input
+/// price when a config entry leaves them out.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read: Some(cache_read),
+            cache_write: Some(cache_write),
+        }
+    }
+
+    pub fn cost(&self, tokens: &TokenCounts) -> f64 {
+        let per = |n: u64, price: f64| n as f64 * price / 1_000_000.0;
+        per(tokens.input, self.input)
+            + per(tokens.output, self.output)
+            + per(tokens.cache_read, self.cache_read.unwrap_or(self.input))
+            + per(tokens.cache_write, self.cache_write.unwrap_or(self.input))
+    }
+}
+
+/// Built-in list prices, matched as substrings of the model name (longest
+/// key wins). They are estimates; override or extend them with
+/// `[prices."<model>"]` tables in config.toml.
+const DEFAULT_PRICES: &[(&str, ModelPrice)] = &[
+    ("claude-opus-4", ModelPrice::new(15.0, 75.0, 1.5, 18.75)),
+    ("claude-opus-4-5", ModelPrice::new(5.0, 25.0, 0.5, 6.25)),
+    ("claude-sonnet", ModelPrice::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-haiku-4", ModelPrice::new(1.0, 5.0, 0.1, 1.25)),
+    ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0, 0.08, 1.0)),
+    ("gpt-5", ModelPrice::new(1.25, 10.0, 0.125, 1.25)),
+    ("gpt-5-mini", ModelPrice::new(0.25, 2.0, 0.025, 0.25)),
+    ("gpt-4.1", ModelPrice::new(2.0, 8.0, 0.5, 2.0)),
+    ("o3", ModelPrice::new(2.0, 8.0, 0.5, 2.0)),
+    ("gemini-2.5-pro", ModelPrice::new(1.25, 10.0, 0.31, 1.25)),
+    ("gemini-2.5-flash", ModelPrice::new(0.3, 2.5, 0.075, 0.3)),
+];
+
+/// Price lookup: built-in defaults overlaid with the user's config.
+#[derive(Debug, Clone)]
+pub struct PriceTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    pub fn new(overrides: &HashMap<String, ModelPrice>) -> Self {
+        let mut prices: HashMap<String, ModelPrice> = DEFAULT_PRICES
+            .iter()
+            .map(|(name, price)| (name.to_string(), *price))
+            .collect();
+        for (name, price) in overrides {
+            prices.insert(name.to_lowercase(), *price);
+        }
+        Self { prices }
+    }
+
+    pub fn load() -> Self {
+        Self::new(&Settings::load().prices)
+    }
+
+    /// Exact match first, then the longest key contained in the model name
+    /// (`claude-sonnet-4-5-20250929` → `claude-sonnet`).
+    pub fn lookup(&self, model: &str) -> Option<&ModelPrice> {
+        let model = model.to_lowercase();
+        self.prices.get(&model).or_else(|| {
+            self.prices
+                .iter()
+                .filter(|(key, _)| model.contains(key.as_str()))
+                .max_by_key(|(key, _)| key.len())
+                .map(|(_, price)| price)
+        })
+    }
+
+    /// Estimated cost of `usage` in USD. `None` when no model has a price.
+    pub fn cost(&self, usage: &[ModelUsage]) -> Option<f64> {
+        let mut total = None;
+        for u in usage {
+            if let Some(price) = self.lookup(&u.model) {
+                *total.get_or_insert(0.0) += price.cost(&u.tokens);
+            }
+        }
+        total
+    }
+}
+
+/// Add `tokens` to the entry for `model`, creating it if needed.
+pub fn accumulate(usage: &mut Vec<ModelUsage>, model: &str, tokens: &TokenCounts) {
+    if tokens.is_empty() {
+        return;
+    }
+    match usage.iter_mut().find(|u| u.model == model) {
+        Some(u) => u.tokens.add(tokens),
+        None => usage.push(ModelUsage {
+            model: model.to_string(),
+            tokens: *tokens,
+        }),
+    }
+}
+
+/// Compact token count: `950`, `12.3k`, `4.5M`.
+pub fn format_tokens(n: u64) -> String {
+    match n {
+        0..=999 => n.to_string(),
+        1_000..=999_999 => format!("{:.1}k", n as f64 / 1_000.0),
+        _ => format!("{:.1}M", n as f64 / 1_000_000.0),
+    }
+}
+
+pub fn format_cost(usd: f64) -> String {
+    if usd < 0.01 {
+        "<$0.01".to_string()
+    } else {
+        format!("${usd:.2}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_prefers_exact_then_longest_substring() {
+        let mut overrides = HashMap::new();
+        overrides.insert("my-model".to_string(), ModelPrice::new(1.0, 2.0, 0.0, 0.0));
+        let table = PriceTable::new(&overrides);
+
+        assert_eq!(table.lookup("gpt-5-mini-2025-08-07").unwrap().input, 0.25);
+        assert_eq!(table.lookup("claude-opus-4-5-20251101").unwrap().input, 5.0);
+        assert_eq!(
+            table.lookup("claude-opus-4-1-20250805").unwrap().input,
+            15.0
+        );
+        assert_eq!(table.lookup("MY-MODEL").unwrap().output, 2.0);
+        assert!(table.lookup("llama-3").is_none());
+
+        let usage = vec![ModelUsage {
+            model: "my-model".to_string(),
+            tokens: TokenCounts {
+                input: 1_000_000,
+                output: 500_000,
+                ..Default::default()
+            },
+        }];
+        assert_eq!(table.cost(&usage), Some(2.0));
+    }
+}
@@ -1,58 +1,45 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{mpsc, Arc};
+use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
-use crate::model::{Agent, Session};
-use crate::scanner;
+use crate::cache;
+use crate::live::Live;
+use crate::model::Session;
 
 struct WatchState {
     sessions: Vec<Session>,
-    running_agents: Vec<Agent>,
+    live: Live,
     last_refresh: Instant,
     selected: usize,
     scroll_offset: usize,
 }
 
 pub fn run_watch(interval_secs: u64) -> anyhow::Result<()> {
-    let sessions = scanner::scan_all();
-    let running_agents = detect_running_agents();
+    let sessions = cache::load_sessions(&cache::SessionFilter::default());
+    let live = Live::detect(&sessions);
 
     let mut state = WatchState {
         sessions,
-        running_agents,
+        live,
         last_refresh: Instant::now(),
         selected: 0,
         scroll_offset: 0,
     };
 
-    let (tx, rx) = mpsc::channel::<(Vec<Session>, Vec<Agent>)>();
-    let refreshing = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel::<(Vec<Session>, Live)>();
+    spawn_refresh(Duration::from_secs(interval_secs), move |sessions, live| {
+        tx.send((sessions, live)).is_ok()
+    });
 
     slt::run_with(
         slt::RunConfig::default().title("agf watch").mouse(true),
         |ui: &mut slt::Context| {
             // Check for background refresh results
-            if let Ok((new_sessions, new_running)) = rx.try_recv() {
+            if let Ok((new_sessions, live)) = rx.try_recv() {
                 state.sessions = new_sessions;
-                state.running_agents = new_running;
+                state.live = live;
                 state.last_refresh = Instant::now();
             }
 
-            // Trigger background refresh (guard against overlapping scans)
-            if state.last_refresh.elapsed() >= Duration::from_secs(interval_secs)
-                && !refreshing.swap(true, Ordering::SeqCst)
-            {
-                state.last_refresh = Instant::now();
-                let tx = tx.clone();
-                let r = Arc::clone(&refreshing);
-                std::thread::spawn(move || {
-                    let sessions = scanner::scan_all();
-                    let running = detect_running_agents();
-                    let _ = tx.send((sessions, running));
-                    r.store(false, Ordering::SeqCst);
-                });
-            }
-
             // Input
             if ui.key_code(slt::KeyCode::Esc) || ui.key('q') {
                 ui.quit();
@@ -78,7 +65,7 @@ pub fn run_watch(interval_secs: u64) -> anyhow::Result<()> {
 
             // Render
             let running_names: Vec<String> =
-                state.running_agents.iter().map(|a| a.to_string()).collect();
+                state.live.agents().iter().map(|a| a.to_string()).collect();
             let elapsed = state.last_refresh.elapsed().as_secs();
 
             let _ = ui.col(|ui| {
@@ -118,7 +105,7 @@ pub fn run_watch(interval_secs: u64) -> anyhow::Result<()> {
                             slt::Color::Reset
                         };
 
-                        let is_running = state.running_agents.contains(&s.agent);
+                        let is_running = state.live.pid(s).is_some();
                         let status = if is_running {
                             ("\u{25cf} ", slt::Color::Rgb(52, 211, 153))
                         } else {
@@ -167,18 +154,21 @@ pub fn run_watch(interval_secs: u64) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn detect_running_agents() -> Vec<Agent> {
-    Agent::all()
-        .iter()
-        .copied()
-        .filter(|agent| {
-            std::process::Command::new("pgrep")
-                .args(["-x", agent.cli_name()])
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false)
-        })
-        .collect()
+/// Reload sessions and running agents every `interval` on a background
+/// thread, handing each result to `deliver` until it returns false. Scans
+/// never overlap: the next one starts `interval` after the last finished.
+pub fn spawn_refresh<F>(interval: Duration, deliver: F)
+where
+    F: Fn(Vec<Session>, Live) -> bool + Send + 'static,
+{
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+        let sessions = cache::load_sessions(&cache::SessionFilter::default());
+        let live = Live::detect(&sessions);
+        if !deliver(sessions, live) {
+            break;
+        }
+    });
 }
 
 fn truncate(s: &str, max: usize) -> String {